@@ -1,3 +1,65 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
+
 fn main() {
     embuild::espidf::sysenv::output();
+    embed_static();
+}
+
+/* ------------ static/ → $OUT_DIR/static_assets.rs --------------------- */
+
+/// Fiecare fişier din `static/` intră în binar o singură dată: varianta
+/// gzip dacă e mai mică, altfel cea brută. ETag-ul = lungime + FNV-1a.
+fn embed_static() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("static");
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let mut files = Vec::new();
+    walk(&root, &mut files);
+    files.sort();
+
+    let mut table = String::from("pub static ASSETS: &[Asset] = &[\n");
+    for file in &files {
+        let rel = file.strip_prefix(&root).unwrap().to_str().unwrap().replace('\\', "/");
+        println!("cargo:rerun-if-changed={}", file.display());
+        let raw = fs::read(file).unwrap();
+        let etag = format!("{:x}-{:016x}", raw.len(), fnv1a(&raw));
+
+        let mut gz = GzEncoder::new(Vec::new(), Compression::best());
+        gz.write_all(&raw).unwrap();
+        let gz = gz.finish().unwrap();
+
+        let (data, gzip) = if gz.len() < raw.len() { (gz, true) } else { (raw.clone(), false) };
+        let blob = out.join(format!("static_{}", rel.replace('/', "__")));
+        fs::write(&blob, &data).unwrap();
+
+        table.push_str(&format!(
+            "    Asset {{ path: {rel:?}, etag: {etag:?}, len: {}, gzip: {gzip}, data: include_bytes!({:?}) }},\n",
+            raw.len(),
+            blob.display().to_string(),
+        ));
+    }
+    table.push_str("];\n");
+    fs::write(out.join("static_assets.rs"), table).unwrap();
+}
+
+fn walk(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            walk(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
 }
@@ -0,0 +1,755 @@
+//! Stand-in local pentru OpenAI (Whisper + Chat) şi Azure TTS.
+//!
+//! ```no_run
+//! let cloud = mock_cloud::MockCloud::start();
+//! cloud.push_transcript("Ce faci?").push_reply(mock_cloud::Reply::text("Bine!"));
+//! println!("OpenAI şi Azure la {}", cloud.url());
+//! ```
+//!
+//! Rute:
+//! * `POST /v1/audio/transcriptions` – multipart (model, language, file WAV, response_format)
+//! * `POST /v1/chat/completions`     – JSON, `stream: true` (SSE) şi `tools`
+//! * `POST /cognitiveservices/v1`    – SSML validat, PCM 16 kHz/16 bit/mono
+//! * `GET /cognitiveservices/voices/list` – câteva voci, în formatul Azure
+//! * `POST /sts/v1.0/issueToken`     – token pentru `Authorization: Bearer`
+//!
+//! Erorile se injectează cu [`MockCloud::fail_next`].
+
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+mod ssml;
+
+pub use ssml::validate_ssml;
+
+pub const DEFAULT_TRANSCRIPT: &str = "Salut, robotule!";
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// Eroare aplicată următoarei cereri.
+#[derive(Clone, Debug)]
+pub enum Fault {
+    /// status + corpul de eroare al providerului (`Retry-After` opţional)
+    Status { status: u16, retry_after: Option<u64> },
+    /// credit epuizat: OpenAI 429 `insufficient_quota`, Azure 403
+    QuotaExceeded,
+    /// status + corp arbitrar (`application/json`), ex. JSON stricat cu 200
+    Body { status: u16, body: String },
+    /// răspunsul normal, dar după o pauză
+    Delay(Duration),
+    /// `Content-Length` complet, dar doar primii N octeţi din corp
+    Truncate(usize),
+    /// închide conexiunea fără răspuns
+    Drop,
+}
+
+impl Fault {
+    pub fn status(status: u16) -> Self {
+        Fault::Status { status, retry_after: None }
+    }
+
+    pub fn rate_limited(retry_after: u64) -> Self {
+        Fault::Status { status: 429, retry_after: Some(retry_after) }
+    }
+}
+
+/// Răspunsul chat-ului pentru o cerere.
+#[derive(Clone, Debug)]
+pub enum Reply {
+    Text(String),
+    ToolCall { name: String, arguments: Value },
+}
+
+impl Reply {
+    pub fn text(t: &str) -> Self {
+        Reply::Text(t.into())
+    }
+
+    pub fn tool_call(name: &str, arguments: Value) -> Self {
+        Reply::ToolCall { name: name.into(), arguments }
+    }
+}
+
+/// O cerere primită de mock.
+#[derive(Clone, Debug)]
+pub struct Recorded {
+    pub method:  String,
+    pub path:    String,
+    pub headers: Vec<(String, String)>,
+    pub body:    Vec<u8>,
+}
+
+impl Recorded {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    transcripts:  VecDeque<String>,
+    replies:      VecDeque<Reply>,
+    faults:       VecDeque<(Option<String>, Fault)>,
+    requests:     Vec<Recorded>,
+    openai_key:   Option<String>,
+    azure_key:    Option<String>,
+    /// emise de `issueToken` şi încă valabile
+    azure_tokens: Vec<String>,
+    ms_per_char:  u32,
+    counter:      u64,
+}
+
+impl State {
+    fn take_fault(&mut self, path: &str) -> Option<Fault> {
+        let i = self
+            .faults
+            .iter()
+            .position(|(p, _)| p.as_deref().map_or(true, |p| path.starts_with(p)))?;
+        self.faults.remove(i).map(|(_, f)| f)
+    }
+}
+
+pub struct MockCloud {
+    addr:  SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockCloud {
+    /// Porneşte pe `127.0.0.1:<port liber>`; fiecare conexiune are thread-ul ei.
+    pub fn start() -> Self {
+        Self::bind("127.0.0.1:0").expect("bind mock-cloud")
+    }
+
+    pub fn bind(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State { ms_per_char: 60, ..Default::default() }));
+
+        let st = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let st = st.clone();
+                thread::spawn(move || {
+                    let _ = handle(stream, &st);
+                });
+            }
+        });
+
+        Ok(Self { addr, state })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// `http://127.0.0.1:<port>` – acelaşi host serveşte OpenAI şi Azure
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn push_transcript(&self, text: &str) -> &Self {
+        self.state.lock().unwrap().transcripts.push_back(text.into());
+        self
+    }
+
+    pub fn push_reply(&self, reply: Reply) -> &Self {
+        self.state.lock().unwrap().replies.push_back(reply);
+        self
+    }
+
+    /// eroare pentru următoarea cerere, indiferent de rută
+    pub fn fail_next(&self, fault: Fault) -> &Self {
+        self.state.lock().unwrap().faults.push_back((None, fault));
+        self
+    }
+
+    /// eroare pentru următoarea cerere al cărei path începe cu `path`
+    pub fn fail_next_on(&self, path: &str, fault: Fault) -> &Self {
+        self.state.lock().unwrap().faults.push_back((Some(path.into()), fault));
+        self
+    }
+
+    /// fără apel, orice `Bearer …` e acceptat (şi cheia goală din build-urile de test)
+    pub fn require_openai_key(&self, key: &str) -> &Self {
+        self.state.lock().unwrap().openai_key = Some(key.into());
+        self
+    }
+
+    pub fn require_azure_key(&self, key: &str) -> &Self {
+        self.state.lock().unwrap().azure_key = Some(key.into());
+        self
+    }
+
+    /// tokenurile Azure emise până acum devin invalide (expirate, cheie rotită)
+    pub fn revoke_azure_tokens(&self) -> &Self {
+        self.state.lock().unwrap().azure_tokens.clear();
+        self
+    }
+
+    /// durata audio generată per caracter de text (implicit 60 ms)
+    pub fn tts_ms_per_char(&self, ms: u32) -> &Self {
+        self.state.lock().unwrap().ms_per_char = ms;
+        self
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+/* ------------ HTTP --------------------------------------------------- */
+
+struct Out {
+    status:  u16,
+    headers: Vec<(String, String)>,
+    body:    Body,
+}
+
+enum Body {
+    Full(Vec<u8>),
+    /// SSE – fiecare element devine un chunk
+    Chunks(Vec<Vec<u8>>),
+}
+
+impl Out {
+    fn new(status: u16, ctype: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), ctype.into())],
+            body: Body::Full(body.into()),
+        }
+    }
+
+    fn json(status: u16, v: &Value) -> Self {
+        Self::new(status, "application/json", v.to_string())
+    }
+
+    fn openai_error(status: u16, kind: &str, code: Option<&str>, msg: &str) -> Self {
+        Self::json(status, &json!({
+            "error": { "message": msg, "type": kind, "param": null, "code": code }
+        }))
+    }
+
+    fn empty(status: u16) -> Self {
+        Self::new(status, "text/plain", Vec::new())
+    }
+
+    fn header(mut self, k: &str, v: &str) -> Self {
+        self.headers.push((k.into(), v.into()));
+        self
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        415 => "Unsupported Media Type",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _   => "",
+    }
+}
+
+fn read_request(stream: &TcpStream) -> std::io::Result<Recorded> {
+    let mut reader = BufReader::new(stream);
+    let mut first = String::new();
+    reader.read_line(&mut first)?;
+    let mut parts = first.split_whitespace();
+    let method = parts.next().unwrap_or("").to_owned();
+    let path = parts.next().unwrap_or("").to_owned();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 { break; }
+        let line = line.trim_end();
+        if line.is_empty() { break; }
+        if let Some((k, v)) = line.split_once(':') {
+            headers.push((k.trim().to_owned(), v.trim().to_owned()));
+        }
+    }
+
+    let mut rec = Recorded { method, path, headers, body: Vec::new() };
+    let len: usize = rec.header("Content-Length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    rec.body = vec![0; len];
+    reader.read_exact(&mut rec.body)?;
+    Ok(rec)
+}
+
+fn handle(mut stream: TcpStream, state: &Mutex<State>) -> std::io::Result<()> {
+    let req = read_request(&stream)?;
+    let fault = {
+        let mut st = state.lock().unwrap();
+        st.requests.push(req.clone());
+        st.take_fault(&req.path)
+    };
+
+    let mut truncate = None;
+    let out = match fault {
+        Some(Fault::Drop) => return Ok(()),
+        Some(Fault::Status { status, retry_after }) => {
+            let mut out = fault_response(&req.path, status);
+            if let Some(s) = retry_after {
+                out = out.header("Retry-After", &s.to_string());
+            }
+            out
+        }
+        Some(Fault::QuotaExceeded) => quota_response(&req.path),
+        Some(Fault::Body { status, body }) => Out::new(status, "application/json", body),
+        Some(Fault::Delay(d)) => {
+            thread::sleep(d);
+            route(&req, state)
+        }
+        Some(Fault::Truncate(n)) => {
+            truncate = Some(n);
+            route(&req, state)
+        }
+        None => route(&req, state),
+    };
+
+    write_response(&mut stream, out, truncate)
+}
+
+fn write_response(stream: &mut TcpStream, out: Out, truncate: Option<usize>) -> std::io::Result<()> {
+    let mut head = format!("HTTP/1.1 {} {}\r\n", out.status, reason(out.status));
+    for (k, v) in &out.headers {
+        head.push_str(&format!("{k}: {v}\r\n"));
+    }
+    match out.body {
+        Body::Full(body) => {
+            head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", body.len()));
+            stream.write_all(head.as_bytes())?;
+            let n = truncate.unwrap_or(body.len()).min(body.len());
+            stream.write_all(&body[..n])?;
+        }
+        Body::Chunks(chunks) => {
+            head.push_str("Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n");
+            stream.write_all(head.as_bytes())?;
+            let mut sent = 0usize;
+            for c in chunks {
+                if truncate.is_some_and(|n| sent + c.len() > n) {
+                    return stream.flush();
+                }
+                stream.write_all(format!("{:x}\r\n", c.len()).as_bytes())?;
+                stream.write_all(&c)?;
+                stream.write_all(b"\r\n")?;
+                sent += c.len();
+            }
+            stream.write_all(b"0\r\n\r\n")?;
+        }
+    }
+    stream.flush()
+}
+
+fn fault_response(path: &str, status: u16) -> Out {
+    if path.starts_with("/v1/") {
+        match status {
+            401 => Out::openai_error(401, "invalid_request_error", Some("invalid_api_key"), "Incorrect API key provided"),
+            429 => Out::openai_error(429, "requests", Some("rate_limit_exceeded"), "Rate limit reached for requests"),
+            s if s >= 500 => Out::openai_error(s, "server_error", None, "The server had an error while processing your request"),
+            s => Out::openai_error(s, "invalid_request_error", None, "Injected error"),
+        }
+    } else {
+        // Azure răspunde de obicei cu corp gol
+        Out::empty(status)
+    }
+}
+
+fn quota_response(path: &str) -> Out {
+    if path.starts_with("/v1/") {
+        Out::openai_error(429, "insufficient_quota", Some("insufficient_quota"),
+            "You exceeded your current quota, please check your plan and billing details.")
+    } else {
+        Out::json(403, &json!({ "error": {
+            "code": "403",
+            "message": "Out of call volume quota. Quota will be replenished in 2.12:00:00."
+        }}))
+    }
+}
+
+fn route(req: &Recorded, state: &Mutex<State>) -> Out {
+    let path = req.path.split('?').next().unwrap_or("");
+    match (req.method.as_str(), path) {
+        ("POST", "/v1/audio/transcriptions") => transcriptions(req, state),
+        ("POST", "/v1/chat/completions")     => chat(req, state),
+        ("POST", "/cognitiveservices/v1")    => azure_tts(req, state),
+        ("GET", "/cognitiveservices/voices/list") => azure_voices(req, state),
+        ("POST", "/sts/v1.0/issueToken")     => azure_token(req, state),
+        (_, "/v1/audio/transcriptions" | "/v1/chat/completions" | "/cognitiveservices/v1"
+            | "/cognitiveservices/voices/list" | "/sts/v1.0/issueToken") => {
+            Out::empty(405)
+        }
+        _ => Out::new(404, "text/plain", "Not Found"),
+    }
+}
+
+fn check_openai_auth(req: &Recorded, state: &Mutex<State>) -> Option<Out> {
+    let expected = state.lock().unwrap().openai_key.clone();
+    // headerele sunt tăiate la citire: `Bearer ` (cheie goală) devine `Bearer`
+    let token = req.header("Authorization").and_then(|v| v.strip_prefix("Bearer")).map(str::trim);
+    match (token, expected) {
+        (None, _) => Some(Out::openai_error(401, "invalid_request_error", None,
+            "You didn't provide an API key.")),
+        (Some(t), Some(k)) if t != k => Some(fault_response("/v1/", 401)),
+        _ => None,
+    }
+}
+
+/* ------------ Whisper ------------------------------------------------ */
+
+struct Part {
+    name: String,
+    data: Vec<u8>,
+}
+
+fn find(hay: &[u8], needle: &[u8]) -> Option<usize> {
+    hay.windows(needle.len()).position(|w| w == needle)
+}
+
+/// `multipart/form-data` → părţi (nume, conţinut)
+fn parse_multipart(ctype: &str, body: &[u8]) -> Result<Vec<Part>, String> {
+    let boundary = ctype
+        .split(';')
+        .filter_map(|p| p.trim().strip_prefix("boundary="))
+        .next()
+        .ok_or("lipseşte boundary")?
+        .trim_matches('"');
+    let delim = format!("--{boundary}").into_bytes();
+
+    let mut parts = Vec::new();
+    let mut rest = &body[find(body, &delim).ok_or("boundary absent din corp")? + delim.len()..];
+    loop {
+        if rest.starts_with(b"--") {
+            return Ok(parts);
+        }
+        rest = rest.strip_prefix(b"\r\n").ok_or("lipseşte CRLF după boundary")?;
+        let hdr_end = find(rest, b"\r\n\r\n").ok_or("headere de parte neterminate")?;
+        let hdrs = String::from_utf8_lossy(&rest[..hdr_end]).into_owned();
+        let data_start = hdr_end + 4;
+        let end = find(&rest[data_start..], &delim).ok_or("parte neterminată")? + data_start;
+        let data = rest[data_start..end]
+            .strip_suffix(b"\r\n")
+            .ok_or("lipseşte CRLF înainte de boundary")?;
+
+        let name = hdrs
+            .lines()
+            .find(|l| l.to_ascii_lowercase().starts_with("content-disposition"))
+            .and_then(|l| l.split("name=\"").nth(1))
+            .and_then(|s| s.split('"').next())
+            .ok_or("parte fără nume")?;
+        parts.push(Part { name: name.into(), data: data.to_vec() });
+        rest = &rest[end + delim.len()..];
+    }
+}
+
+fn transcriptions(req: &Recorded, state: &Mutex<State>) -> Out {
+    if let Some(e) = check_openai_auth(req, state) { return e; }
+    let bad = |msg: &str| Out::openai_error(400, "invalid_request_error", None, msg);
+
+    let ctype = req.header("Content-Type").unwrap_or("");
+    if !ctype.starts_with("multipart/form-data") {
+        return bad("Content-Type must be multipart/form-data");
+    }
+    let parts = match parse_multipart(ctype, &req.body) {
+        Ok(p) => p,
+        Err(e) => return bad(&format!("Malformed multipart: {e}")),
+    };
+    let field = |n: &str| parts.iter().find(|p| p.name == n).map(|p| &p.data[..]);
+
+    match field("model") {
+        Some(b"whisper-1") => {}
+        Some(m) => return bad(&format!("Invalid model: {}", String::from_utf8_lossy(m))),
+        None    => return bad("you must provide a model parameter"),
+    }
+    let Some(file) = field("file") else {
+        return bad("you must provide a file parameter");
+    };
+    if file.len() < 44 || &file[..4] != b"RIFF" || &file[8..12] != b"WAVE" {
+        return bad("Invalid file format. Supported formats: ['wav', …]");
+    }
+    let language = field("language").map(|l| String::from_utf8_lossy(l).into_owned());
+
+    let text = state
+        .lock()
+        .unwrap()
+        .transcripts
+        .pop_front()
+        .unwrap_or_else(|| DEFAULT_TRANSCRIPT.into());
+
+    match field("response_format").unwrap_or(b"json") {
+        b"text" => Out::new(200, "text/plain; charset=utf-8", format!("{text}\n")),
+        b"json" => Out::json(200, &json!({ "text": text })),
+        b"verbose_json" => {
+            let secs = (file.len() - 44) as f64 / (2.0 * SAMPLE_RATE as f64);
+            Out::json(200, &json!({
+                "task": "transcribe", "language": language, "duration": secs, "text": text
+            }))
+        }
+        f => bad(&format!("Invalid response_format: {}", String::from_utf8_lossy(f))),
+    }
+}
+
+/* ------------ Chat --------------------------------------------------- */
+
+fn chat(req: &Recorded, state: &Mutex<State>) -> Out {
+    if let Some(e) = check_openai_auth(req, state) { return e; }
+    let bad = |msg: &str| Out::openai_error(400, "invalid_request_error", None, msg);
+
+    let Some(v) = req.json() else {
+        return bad("We could not parse the JSON body of your request.");
+    };
+    let Some(model) = v["model"].as_str() else {
+        return bad("you must provide a model parameter");
+    };
+    let Some(messages) = v["messages"].as_array().filter(|m| !m.is_empty()) else {
+        return bad("'messages' must be a non-empty array");
+    };
+    if messages.iter().any(|m| m["role"].as_str().is_none()) {
+        return bad("each message must have a 'role'");
+    }
+    let stream = v["stream"].as_bool().unwrap_or(false);
+
+    let (reply, id) = {
+        let mut st = state.lock().unwrap();
+        st.counter += 1;
+        (st.replies.pop_front(), st.counter)
+    };
+    let reply = reply.unwrap_or_else(|| {
+        let last = messages
+            .iter()
+            .rev()
+            .find(|m| m["role"] == "user")
+            .and_then(|m| m["content"].as_str())
+            .unwrap_or("");
+        Reply::Text(format!("Ai spus: {last}"))
+    });
+
+    if let Reply::ToolCall { name, .. } = &reply {
+        let offered = v["tools"]
+            .as_array()
+            .is_some_and(|t| t.iter().any(|t| t["function"]["name"] == name.as_str()));
+        if !offered {
+            return bad(&format!("tool '{name}' was not offered in 'tools'"));
+        }
+    }
+
+    let id = format!("chatcmpl-mock-{id}");
+    if stream {
+        chat_stream(&id, model, &reply)
+    } else {
+        let (message, finish) = match &reply {
+            Reply::Text(t) => (json!({ "role": "assistant", "content": t }), "stop"),
+            Reply::ToolCall { name, arguments } => (json!({
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": format!("call_{id}"),
+                    "type": "function",
+                    "function": { "name": name, "arguments": arguments.to_string() }
+                }]
+            }), "tool_calls"),
+        };
+        Out::json(200, &json!({
+            "id": id,
+            "object": "chat.completion",
+            "created": 0,
+            "model": model,
+            "choices": [{ "index": 0, "message": message, "finish_reason": finish }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+        }))
+    }
+}
+
+fn chat_stream(id: &str, model: &str, reply: &Reply) -> Out {
+    let chunk = |delta: Value, finish: Option<&str>| {
+        let v = json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": 0,
+            "model": model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish }]
+        });
+        format!("data: {v}\n\n").into_bytes()
+    };
+
+    let mut chunks = vec![chunk(json!({ "role": "assistant", "content": "" }), None)];
+    let finish = match reply {
+        Reply::Text(t) => {
+            for (i, word) in t.split(' ').enumerate() {
+                let piece = if i == 0 { word.to_owned() } else { format!(" {word}") };
+                chunks.push(chunk(json!({ "content": piece }), None));
+            }
+            "stop"
+        }
+        Reply::ToolCall { name, arguments } => {
+            chunks.push(chunk(json!({ "tool_calls": [{
+                "index": 0,
+                "id": format!("call_{id}"),
+                "type": "function",
+                "function": { "name": name, "arguments": arguments.to_string() }
+            }]}), None));
+            "tool_calls"
+        }
+    };
+    chunks.push(chunk(json!({}), Some(finish)));
+    chunks.push(b"data: [DONE]\n\n".to_vec());
+
+    Out {
+        status: 200,
+        headers: vec![("Content-Type".into(), "text/event-stream".into())],
+        body: Body::Chunks(chunks),
+    }
+}
+
+/* ------------ Azure TTS ---------------------------------------------- */
+
+/// Cheia (doar cea cerută cu `require_azure_key`, dacă e setată).
+fn azure_key_ok(req: &Recorded, state: &Mutex<State>) -> bool {
+    let expected = state.lock().unwrap().azure_key.clone();
+    match (req.header("Ocp-Apim-Subscription-Key"), expected) {
+        (None, _)            => false,
+        (Some(k), Some(exp)) => k == exp,
+        (Some(_), None)      => true,
+    }
+}
+
+/// Cheia sau un token emis de `issueToken` şi nerevocat.
+fn azure_authorized(req: &Recorded, state: &Mutex<State>) -> bool {
+    match req.header("Authorization").and_then(|v| v.strip_prefix("Bearer ")) {
+        Some(token) => state.lock().unwrap().azure_tokens.iter().any(|t| t == token),
+        None        => azure_key_ok(req, state),
+    }
+}
+
+/// Un „JWT” opac; clientul doar îl trimite înapoi.
+fn azure_token(req: &Recorded, state: &Mutex<State>) -> Out {
+    if !azure_key_ok(req, state) {
+        return Out::json(401, &json!({ "error": {
+            "code": "401",
+            "message": "Access denied due to invalid subscription key or wrong API endpoint."
+        }}));
+    }
+    let mut st = state.lock().unwrap();
+    st.counter += 1;
+    let token = format!("eyJhbGciOiJub25lIn0.eyJtb2NrIjp7fX0.{}", st.counter);
+    st.azure_tokens.push(token.clone());
+    Out::new(200, "application/jwt; charset=us-ascii", token)
+}
+
+fn azure_tts(req: &Recorded, state: &Mutex<State>) -> Out {
+    let ms_per_char = state.lock().unwrap().ms_per_char;
+    if !azure_authorized(req, state) {
+        return Out::empty(401);
+    }
+
+    if !req
+        .header("Content-Type")
+        .is_some_and(|c| c.starts_with("application/ssml+xml"))
+    {
+        return Out::empty(415);
+    }
+    let riff = match req.header("X-Microsoft-OutputFormat") {
+        Some("raw-16khz-16bit-mono-pcm")  => false,
+        Some("riff-16khz-16bit-mono-pcm") => true,
+        _ => return Out::empty(400),
+    };
+
+    let ssml = String::from_utf8_lossy(&req.body);
+    let text = match validate_ssml(&ssml) {
+        Ok(t) => t,
+        Err(e) => return Out::new(400, "text/plain", e),
+    };
+
+    let ms = (text.chars().count() as u32 * ms_per_char).min(30_000);
+    let pcm = tone(ms);
+    if riff {
+        Out::new(200, "audio/x-wav", wav(&pcm))
+    } else {
+        Out::new(200, "audio/x-pcm", pcm)
+    }
+}
+
+/// Un eşantion din lista reală, cu toate câmpurile (clientul le ignoră pe
+/// cele de care nu are nevoie).
+fn azure_voices(req: &Recorded, state: &Mutex<State>) -> Out {
+    if !azure_authorized(req, state) {
+        return Out::empty(401);
+    }
+    let voice = |short: &str, local: &str, gender: &str, styles: &[&str]| {
+        let locale = short.splitn(3, '-').take(2).collect::<Vec<_>>().join("-");
+        let mut v = json!({
+            "Name": format!("Microsoft Server Speech Text to Speech Voice ({locale}, {})", &short[locale.len() + 1..]),
+            "DisplayName": local,
+            "LocalName": local,
+            "ShortName": short,
+            "Gender": gender,
+            "Locale": locale,
+            "LocaleName": locale,
+            "SampleRateHertz": "48000",
+            "VoiceType": "Neural",
+            "Status": "GA",
+            "WordsPerMinute": "150",
+        });
+        if !styles.is_empty() {
+            v["StyleList"] = json!(styles);
+        }
+        v
+    };
+    Out::json(200, &json!([
+        voice("ro-RO-AlinaNeural", "Alina", "Female", &[]),
+        voice("ro-RO-EmilNeural", "Emil", "Male", &[]),
+        voice("en-US-JennyNeural", "Jenny", "Female", &["assistant", "chat", "cheerful", "sad"]),
+        voice("en-GB-RyanNeural", "Ryan", "Male", &["cheerful", "chat"]),
+        voice("de-DE-KatjaNeural", "Katja", "Female", &[]),
+        voice("fr-FR-DeniseNeural", "Denise", "Female", &["cheerful", "sad"]),
+    ]))
+}
+
+/// 440 Hz, amplitudine mică – destul ca să se audă în fişierele WAV
+fn tone(ms: u32) -> Vec<u8> {
+    let n = SAMPLE_RATE * ms / 1000;
+    let mut out = Vec::with_capacity(n as usize * 2);
+    for i in 0..n {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let s = ((t * 440.0 * std::f32::consts::TAU).sin() * 4000.0) as i16;
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+    out
+}
+
+fn wav(pcm: &[u8]) -> Vec<u8> {
+    let mut w = Vec::with_capacity(44 + pcm.len());
+    w.extend_from_slice(b"RIFF");
+    w.extend_from_slice(&(36 + pcm.len() as u32).to_le_bytes());
+    w.extend_from_slice(b"WAVEfmt ");
+    w.extend_from_slice(&16u32.to_le_bytes());
+    w.extend_from_slice(&1u16.to_le_bytes());
+    w.extend_from_slice(&1u16.to_le_bytes());
+    w.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    w.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    w.extend_from_slice(&2u16.to_le_bytes());
+    w.extend_from_slice(&16u16.to_le_bytes());
+    w.extend_from_slice(b"data");
+    w.extend_from_slice(&(pcm.len() as u32).to_le_bytes());
+    w.extend_from_slice(pcm);
+    w
+}
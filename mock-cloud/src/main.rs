@@ -0,0 +1,24 @@
+//! `cargo +stable run -p mock-cloud --target x86_64-unknown-linux-gnu -- 9000`
+//! apoi `simulator --openai http://127.0.0.1:9000 --azure http://127.0.0.1:9000`.
+
+fn main() {
+    let port = std::env::args().nth(1).unwrap_or_else(|| "9000".into());
+    let cloud = match mock_cloud::MockCloud::bind(format!("127.0.0.1:{port}")) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("mock-cloud: {e}");
+            std::process::exit(1);
+        }
+    };
+    eprintln!("mock-cloud la {} (OpenAI + Azure TTS)", cloud.url());
+
+    let mut seen = 0;
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let reqs = cloud.requests();
+        for r in &reqs[seen..] {
+            eprintln!("{} {} ({} B)", r.method, r.path, r.body.len());
+        }
+        seen = reqs.len();
+    }
+}
@@ -0,0 +1,152 @@
+//! Validare SSML aproape ca Azure: XML bine format, `<speak version xml:lang>`
+//! ca rădăcină şi cel puţin un `<voice name>`.
+
+/// Întoarce textul rostit (entităţi decodate) sau motivul respingerii.
+pub fn validate_ssml(src: &str) -> Result<String, String> {
+    let mut stack: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut voices = 0usize;
+    let mut root_seen = false;
+    let mut rest = src.trim_start();
+
+    if let Some(r) = rest.strip_prefix("<?xml") {
+        let end = r.find("?>").ok_or("declaraţie XML neterminată")?;
+        rest = r[end + 2..].trim_start();
+    }
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            if !rest.trim().is_empty() {
+                return Err("text în afara elementului rădăcină".into());
+            }
+            break;
+        };
+
+        let chunk = &rest[..lt];
+        if stack.is_empty() && !chunk.trim().is_empty() {
+            return Err("text în afara elementului rădăcină".into());
+        }
+        if stack.iter().any(|t| t == "voice") {
+            text.push_str(&decode(chunk)?);
+        } else if chunk.contains('&') {
+            decode(chunk)?;
+        }
+        rest = &rest[lt..];
+
+        if let Some(r) = rest.strip_prefix("<!--") {
+            let end = r.find("-->").ok_or("comentariu neterminat")?;
+            rest = &r[end + 3..];
+            continue;
+        }
+
+        let gt = rest.find('>').ok_or("tag neterminat")?;
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            match stack.pop() {
+                Some(open) if open == name => {}
+                Some(open) => return Err(format!("</{name}> închide <{open}>")),
+                None => return Err(format!("</{name}> fără deschidere")),
+            }
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        if name.is_empty() || name.contains(['<', '&', '"']) {
+            return Err(format!("nume de element invalid: {name:?}"));
+        }
+        let attrs = parse_attrs(attrs)?;
+        let attr = |k: &str| attrs.iter().find(|(n, _)| n == k).map(|(_, v)| v.as_str());
+
+        if stack.is_empty() {
+            if root_seen {
+                return Err("mai multe elemente rădăcină".into());
+            }
+            root_seen = true;
+            if name != "speak" {
+                return Err(format!("rădăcina trebuie să fie <speak>, nu <{name}>"));
+            }
+            if attr("version").is_none() || attr("xml:lang").is_none() {
+                return Err("<speak> cere version şi xml:lang".into());
+            }
+        }
+        if name == "voice" {
+            if attr("name").unwrap_or("").is_empty() {
+                return Err("<voice> fără name".into());
+            }
+            voices += 1;
+        }
+        if !self_closing {
+            stack.push(name.into());
+        }
+    }
+
+    if let Some(open) = stack.pop() {
+        return Err(format!("<{open}> neînchis"));
+    }
+    if !root_seen {
+        return Err("document gol".into());
+    }
+    if voices == 0 {
+        return Err("lipseşte <voice>".into());
+    }
+    Ok(text)
+}
+
+fn parse_attrs(mut s: &str) -> Result<Vec<(String, String)>, String> {
+    let mut out = Vec::new();
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            return Ok(out);
+        }
+        let eq = s.find('=').ok_or_else(|| format!("atribut fără valoare: {s:?}"))?;
+        let name = s[..eq].trim();
+        let after = s[eq + 1..].trim_start();
+        let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("valoare fără ghilimele pentru {name}"))?;
+        let end = after[1..].find(quote).ok_or("ghilimele neînchise")?;
+        let value = decode(&after[1..1 + end])?;
+        if value.contains('<') {
+            return Err(format!("'<' în atributul {name}"));
+        }
+        out.push((name.into(), value));
+        s = &after[end + 2..];
+    }
+}
+
+/// entităţile XML predefinite + referinţe numerice; `&` liber e o eroare
+fn decode(s: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let end = after.find(';').ok_or("'&' neescapat")?;
+        let ent = &after[..end];
+        let c = match ent {
+            "amp"  => '&',
+            "lt"   => '<',
+            "gt"   => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(h) = ent.strip_prefix("#x") {
+                    u32::from_str_radix(h, 16).ok()
+                } else {
+                    ent.strip_prefix('#').and_then(|d| d.parse().ok())
+                };
+                code.and_then(char::from_u32)
+                    .ok_or_else(|| format!("entitate necunoscută: &{ent};"))?
+            }
+        };
+        out.push(c);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
@@ -0,0 +1,75 @@
+//! Fişierele din `static/`, pregătite de `build.rs`: comprimate gzip
+//! (dacă merită) şi cu ETag calculat la build.
+
+use std::borrow::Cow;
+
+pub struct Asset {
+    pub path: &'static str,
+    /// fără ghilimele; varianta gzip are sufixul `-gz`
+    pub etag: &'static str,
+    /// lungimea necomprimată
+    pub len:  usize,
+    pub gzip: bool,
+    pub data: &'static [u8],
+}
+
+include!(concat!(env!("OUT_DIR"), "/static_assets.rs"));
+
+pub fn find(path: &str) -> Option<&'static Asset> {
+    ASSETS.iter().find(|a| a.path == path)
+}
+
+impl Asset {
+    /// ETag-ul reprezentării trimise (RFC 9110: gzip ≠ identity)
+    pub fn etag(&self, gzip: bool) -> String {
+        if gzip && self.gzip {
+            format!("\"{}-gz\"", self.etag)
+        } else {
+            format!("\"{}\"", self.etag)
+        }
+    }
+
+    /// Conţinutul brut; pentru clienţii fără gzip se dezarhivează în RAM.
+    pub fn decoded(&self) -> Cow<'static, [u8]> {
+        if !self.gzip {
+            return Cow::Borrowed(self.data);
+        }
+        // header gzip fix de 10 B (fără nume de fişier) + CRC32/ISIZE la final
+        let deflate = &self.data[10..self.data.len() - 8];
+        let raw = miniz_oxide::inflate::decompress_to_vec_with_limit(deflate, self.len)
+            .expect("gzip generat de build.rs");
+        Cow::Owned(raw)
+    }
+
+    /// `no-cache` pentru HTML (se revalidează cu ETag), o oră pentru restul
+    pub fn cache_control(&self) -> &'static str {
+        if self.path.ends_with(".html") {
+            "no-cache"
+        } else {
+            "public, max-age=3600"
+        }
+    }
+}
+
+/// `Accept-Encoding` permite gzip? (`gzip;q=0` înseamnă nu)
+pub fn accepts_gzip(accept_encoding: Option<&str>) -> bool {
+    accept_encoding.unwrap_or("").split(',').any(|enc| {
+        let mut parts = enc.split(';');
+        let name = parts.next().unwrap_or("").trim();
+        let q = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .and_then(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        (name.eq_ignore_ascii_case("gzip") || name == "*") && q > 0.0
+    })
+}
+
+/// `If-None-Match` conţine ETag-ul curent (comparaţie slabă, ca la GET)?
+pub fn etag_matches(if_none_match: Option<&str>, asset: &Asset) -> bool {
+    let Some(inm) = if_none_match else { return false };
+    inm.split(',').any(|tag| {
+        let tag = tag.trim();
+        let tag = tag.strip_prefix("W/").unwrap_or(tag).trim_matches('"');
+        tag == "*" || tag == asset.etag || tag.strip_suffix("-gz") == Some(asset.etag)
+    })
+}
@@ -1,37 +1,90 @@
 use anyhow::{anyhow, Result};
 use log::{error, info};
 use std::{
-    sync::mpsc::{Receiver, Sender}
+    sync::mpsc::Sender
 };
 
-use crate::openai;
+use crate::cloud_error::CloudError;
+use crate::config;
+use crate::hal::HttpClient;
+use crate::openai::{self, Conversation};
+use crate::queue::Rx;
 
-pub fn transcribe_and_chat(wav: &[u8]) -> Result<(String, String)> {
-    let text = openai::whisper_wav(wav, "ro")?;
+/// (transcriere, replică) sau motivul pentru care cloud-ul n-a răspuns
+pub type Exchange = Result<(String, String), CloudError>;
+
+/// Ce primeşte `audio_task` de la serverul HTTP.
+pub enum Job {
+    /// `/transcribe` – WAV → Whisper → ChatGPT
+    Wav(Vec<u8>),
+    /// `/api/chat` – text → ChatGPT
+    Text(String),
+    /// uită conversaţia şi reia prompt-ul din `config` (fără răspuns pe canal)
+    Reset,
+}
+
+/// `connect` deschide câte un client HTTP nou – conexiunile ESP nu sunt
+/// `Send`, deci fiecare thread îşi face propriul client.
+pub fn transcribe_and_chat<H, F>(
+    connect: &F,
+    conv: &mut Conversation,
+    wav: &[u8],
+) -> Result<(String, String)>
+where
+    H: HttpClient,
+    F: Fn() -> Result<H> + Sync,
+{
+    let text = openai::whisper_wav(&mut connect()?, wav, &config::get().openai.language)?;
     info!("📜 Whisper: {}", text);
-    
-    let text_for_chat = text.clone();
-    let reply = std::thread::spawn(move || openai::chat(&text_for_chat))
-        .join()
-        .map_err(|e| anyhow!("Eroare thread: {:?}", e))??;
-    
-    info!("🤖 ChatGPT: {}", reply);
+
+    let reply = chat_text(connect, conv, &text)?;
     Ok((text, reply))
 }
 
+pub fn chat_text<H, F>(connect: &F, conv: &mut Conversation, text: &str) -> Result<String>
+where
+    H: HttpClient,
+    F: Fn() -> Result<H> + Sync,
+{
+    let reply = std::thread::scope(|s| {
+        s.spawn(|| openai::chat_in(&mut connect()?, conv, text)).join()
+    })
+    .map_err(|e| anyhow!("Eroare thread: {:?}", e))??;
 
-pub fn audio_task(rx: Receiver<Vec<u8>>, tx: Sender<(String,String)>) {
-    while let Ok(wav) = rx.recv() {
-        info!("audio_task: {} B", wav.len());
+    info!("🤖 ChatGPT: {}", reply);
+    Ok(reply)
+}
+
+
+pub fn audio_task<H, F>(rx: Rx<Job>, tx: Sender<Exchange>, connect: F)
+where
+    H: HttpClient,
+    F: Fn() -> Result<H> + Sync,
+{
+    let mut conv = Conversation::default();
+
+    while let Ok(job) = rx.recv() {
+        let res = match job {
+            Job::Wav(wav) => {
+                info!("audio_task: {} B", wav.len());
+                transcribe_and_chat(&connect, &mut conv, &wav)
+            }
+            Job::Text(text) => {
+                info!("audio_task: \"{text}\"");
+                chat_text(&connect, &mut conv, &text).map(|reply| (text, reply))
+            }
+            Job::Reset => {
+                info!("audio_task: conversaţie nouă");
+                conv = Conversation::default();
+                continue;
+            }
+        };
 
-        match transcribe_and_chat(&wav) {
-            Ok(pair)   => { let _ = tx.send(pair); }
+        match res {
+            Ok(pair)   => { let _ = tx.send(Ok(pair)); }
             Err(error) => {
                 error!("OpenAI: {error:?}");
-                let _ = tx.send((
-                    "Eroare".into(),
-                    format!("Nu pot contacta OpenAI: {error}")
-                ));
+                let _ = tx.send(Err(CloudError::classify("OpenAI", error)));
             }
         }
     }
@@ -0,0 +1,229 @@
+//! Autentificare pentru API: token bearer generat la prima pornire şi
+//! păstrat în NVS, asociere (pairing) cu un PIN rostit de robot şi
+//! allowlist CORS pentru paginile servite de pe alt host.
+
+use anyhow::Result;
+use log::{info, warn};
+use std::{
+    sync::{Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::hal::KeyValueStore;
+use crate::util;
+
+const KEY_TOKEN:   &str = "api_token";
+const KEY_ORIGINS: &str = "cors_origins";
+
+/// cât e valabil un PIN de asociere
+pub const PAIR_TTL: Duration = Duration::from_secs(120);
+/// PIN-uri greşite până la blocare – numărate peste toate PIN-urile cerute,
+/// altfel fiecare `/api/pair/start` ar da încă 5 încercări
+pub const PAIR_ATTEMPTS: u8 = 5;
+/// prima blocare; fiecare blocare următoare e de două ori mai lungă
+pub const PAIR_LOCKOUT: Duration = Duration::from_secs(30);
+pub const PAIR_LOCKOUT_MAX: Duration = Duration::from_secs(60 * 60);
+
+/// Răspunsul pentru o cerere care modifică ceva.
+#[derive(Debug, PartialEq)]
+pub enum Access {
+    Allowed,
+    /// 401 – lipseşte tokenul sau e greşit
+    Unauthorized,
+    /// 403 – pagina vine de pe o origine care nu e în allowlist
+    Forbidden,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PairError {
+    /// nicio asociere pornită sau PIN expirat
+    NotStarted,
+    WrongPin,
+    /// încercarea care a declanşat blocarea
+    TooManyAttempts,
+    /// blocat după prea multe PIN-uri greşite; mai durează atât
+    Locked(Duration),
+}
+
+struct Pairing {
+    pin:     String,
+    expires: Instant,
+}
+
+/// Starea asocierii, comună tuturor PIN-urilor – se resetează doar la o
+/// asociere reuşită (sau la repornire).
+#[derive(Default)]
+struct PairState {
+    current:  Option<Pairing>,
+    wrong:    u8,
+    lockouts: u32,
+    until:    Option<Instant>,
+}
+
+impl PairState {
+    fn locked(&self) -> Option<Duration> {
+        self.until.and_then(|t| t.checked_duration_since(Instant::now())).filter(|d| !d.is_zero())
+    }
+}
+
+pub struct Auth {
+    kv:      Mutex<Box<dyn KeyValueStore + Send>>,
+    token:   RwLock<String>,
+    origins: RwLock<Vec<String>>,
+    pairing: Mutex<PairState>,
+    lockout: (Duration, Duration),
+}
+
+impl Auth {
+    /// Citeşte tokenul din NVS sau îl generează (prima pornire).
+    pub fn load(kv: impl KeyValueStore + Send + 'static) -> Result<Self> {
+        let mut kv: Box<dyn KeyValueStore + Send> = Box::new(kv);
+        let token = match kv.get_str(KEY_TOKEN)? {
+            Some(t) if !t.is_empty() => t,
+            _ => {
+                info!("🔑 token API nou (prima pornire)");
+                let t = new_token();
+                kv.set_str(KEY_TOKEN, &t)?;
+                t
+            }
+        };
+        let origins = kv
+            .get_str(KEY_ORIGINS)?
+            .map(|s| parse_origins(&s))
+            .unwrap_or_default();
+
+        Ok(Self {
+            kv:      Mutex::new(kv),
+            token:   RwLock::new(token),
+            origins: RwLock::new(origins),
+            pairing: Mutex::default(),
+            lockout: (PAIR_LOCKOUT, PAIR_LOCKOUT_MAX),
+        })
+    }
+
+    /// blocări scurte, pentru teste
+    pub fn with_pair_lockout(mut self, first: Duration, max: Duration) -> Self {
+        self.lockout = (first, max);
+        self
+    }
+
+    pub fn token(&self) -> String {
+        self.token.read().unwrap().clone()
+    }
+
+    /// `Authorization: Bearer <token>` corect?
+    pub fn check_token(&self, authorization: Option<&str>) -> bool {
+        let Some(given) = authorization.and_then(|h| h.strip_prefix("Bearer ")) else {
+            return false;
+        };
+        util::ct_eq(given.trim().as_bytes(), self.token.read().unwrap().as_bytes())
+    }
+
+    /// Fără `Origin` (curl, aplicaţii) sau de pe acelaşi host → da;
+    /// altfel doar originile din allowlist.
+    pub fn origin_allowed(&self, origin: Option<&str>, host: Option<&str>) -> bool {
+        let Some(origin) = origin else { return true };
+        same_origin(origin, host) || self.origins.read().unwrap().iter().any(|o| o == origin)
+    }
+
+    /// Valoarea pentru `Access-Control-Allow-Origin` (doar cereri cross-origin
+    /// din allowlist – paginile proprii nu au nevoie de CORS).
+    pub fn cors_origin(&self, origin: Option<&str>, host: Option<&str>) -> Option<String> {
+        let origin = origin?;
+        (!same_origin(origin, host) && self.origin_allowed(Some(origin), host)).then(|| origin.to_owned())
+    }
+
+    pub fn check(&self, authorization: Option<&str>, origin: Option<&str>, host: Option<&str>) -> Access {
+        if !self.origin_allowed(origin, host) {
+            Access::Forbidden
+        } else if !self.check_token(authorization) {
+            Access::Unauthorized
+        } else {
+            Access::Allowed
+        }
+    }
+
+    pub fn origins(&self) -> Vec<String> {
+        self.origins.read().unwrap().clone()
+    }
+
+    /// Allowlist nou, ex. `["http://192.168.1.10:8080"]`; salvat în NVS.
+    pub fn set_origins(&self, origins: &[String]) -> Result<()> {
+        let joined = origins.iter().map(|o| o.trim().trim_end_matches('/')).collect::<Vec<_>>().join(",");
+        self.kv.lock().unwrap().set_str(KEY_ORIGINS, &joined)?;
+        *self.origins.write().unwrap() = parse_origins(&joined);
+        Ok(())
+    }
+
+    /// PIN nou de 6 cifre (îl anulează pe cel vechi); robotul îl rosteşte.
+    /// Încercările greşite de la PIN-urile anterioare rămân numărate.
+    pub fn start_pairing(&self) -> Result<String, PairError> {
+        let mut state = self.pairing.lock().unwrap();
+        if let Some(left) = state.locked() {
+            return Err(PairError::Locked(left));
+        }
+        let mut b = [0u8; 4];
+        util::random_bytes(&mut b);
+        let pin = format!("{:06}", u32::from_le_bytes(b) % 1_000_000);
+        state.current = Some(Pairing { pin: pin.clone(), expires: Instant::now() + PAIR_TTL });
+        Ok(pin)
+    }
+
+    /// PIN corect → tokenul curent; PIN-ul nu mai poate fi folosit. După
+    /// `PAIR_ATTEMPTS` greşeli asocierea e blocată `PAIR_LOCKOUT`, apoi
+    /// dublu la fiecare nouă serie de greşeli.
+    pub fn pair(&self, pin: &str) -> Result<String, PairError> {
+        let mut state = self.pairing.lock().unwrap();
+        if let Some(left) = state.locked() {
+            return Err(PairError::Locked(left));
+        }
+        let Some(p) = state.current.as_ref().filter(|p| Instant::now() < p.expires) else {
+            state.current = None;
+            return Err(PairError::NotStarted);
+        };
+        if util::ct_eq(pin.trim().as_bytes(), p.pin.as_bytes()) {
+            *state = PairState::default();
+            info!("🔑 client nou asociat");
+            return Ok(self.token());
+        }
+        state.wrong += 1;
+        if state.wrong < PAIR_ATTEMPTS {
+            return Err(PairError::WrongPin);
+        }
+        let (first, max) = self.lockout;
+        let lockout = first.saturating_mul(1 << state.lockouts.min(16)).min(max);
+        warn!("🔑 {PAIR_ATTEMPTS} PIN-uri greşite – asocierea blocată {} s", lockout.as_secs());
+        state.current = None;
+        state.wrong = 0;
+        state.lockouts += 1;
+        state.until = Some(Instant::now() + lockout);
+        Err(PairError::TooManyAttempts)
+    }
+
+    /// Token nou – toţi clienţii asociaţi până acum trebuie re-asociaţi.
+    pub fn rotate(&self) -> Result<String> {
+        let t = new_token();
+        self.kv.lock().unwrap().set_str(KEY_TOKEN, &t)?;
+        *self.token.write().unwrap() = t.clone();
+        info!("🔑 token API regenerat");
+        Ok(t)
+    }
+}
+
+fn new_token() -> String {
+    let mut b = [0u8; 16];
+    util::random_bytes(&mut b);
+    util::hex(&b)
+}
+
+fn parse_origins(s: &str) -> Vec<String> {
+    s.split(',').map(str::trim).filter(|o| !o.is_empty()).map(str::to_owned).collect()
+}
+
+fn same_origin(origin: &str, host: Option<&str>) -> bool {
+    let Some(host) = host else { return false };
+    origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .is_some_and(|o| o.eq_ignore_ascii_case(host))
+}
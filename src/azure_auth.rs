@@ -0,0 +1,160 @@
+//! Token Azure Speech: cheia pleacă doar spre `issueToken`, o dată la câteva
+//! minute; replicile şi lista vocilor merg cu `Authorization: Bearer …`.
+//!
+//! `issueToken` are conexiunea lui: altfel fiecare reînnoire ar închide
+//! conexiunea keep-alive a workerului TTS (alt host). Workerul reînnoieşte
+//! tokenul cât n-are ce spune (`azure_tts::next_speech`), deci doar prima
+//! replică (sau prima după o cheie nouă) aşteaptă după `issueToken`.
+
+use anyhow::{bail, Result};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::cloud_error::CloudError;
+use crate::config::Azure;
+use crate::hal::HttpClient;
+use crate::retry::AZURE;
+
+/// Azure îl declară valabil 10 minute; un minut marjă
+pub const TOKEN_LIFETIME: Duration = Duration::from_secs(9 * 60);
+/// reînnoit înainte să expire – nicio replică nu-l prinde expirat
+pub const REFRESH_AFTER: Duration = Duration::from_secs(8 * 60);
+
+struct Token {
+    region: String,
+    key:    String,
+    value:  String,
+    issued: Instant,
+}
+
+/// Tokenul curent, comun workerului TTS şi listei de voci.
+pub struct AzureAuth {
+    http:          Mutex<Box<dyn HttpClient + Send>>,
+    refresh_after: Duration,
+    lifetime:      Duration,
+    token:         Mutex<Option<Token>>,
+}
+
+impl AzureAuth {
+    /// `http` – doar pentru `issueToken`
+    pub fn new(http: impl HttpClient + Send + 'static) -> Self {
+        Self::with_lifetime(http, REFRESH_AFTER, TOKEN_LIFETIME)
+    }
+
+    /// durate scurte, pentru teste
+    pub fn with_lifetime(http: impl HttpClient + Send + 'static, refresh_after: Duration, lifetime: Duration) -> Self {
+        Self { http: Mutex::new(Box::new(http)), refresh_after, lifetime, token: Mutex::new(None) }
+    }
+
+    /// Valoarea headerului `Authorization` pentru `azure`. După
+    /// `refresh_after` se cere alt token; dacă `issueToken` nu răspunde,
+    /// cel vechi merge până la `lifetime`. Altă cheie sau regiune → token nou.
+    pub fn bearer(&self, azure: &Azure) -> Result<String> {
+        if let (Some(value), true) = self.cached(azure) {
+            return Ok(format!("Bearer {value}"));
+        }
+        let mut http = self.http.lock().unwrap();
+        // reînnoit de alt thread cât am aşteptat conexiunea
+        let stale = match self.cached(azure) {
+            (Some(value), true) => return Ok(format!("Bearer {value}")),
+            (stale, _) => stale,
+        };
+
+        match issue_token(&mut **http, azure) {
+            Ok(value) => {
+                log::info!("🔑 token Azure nou ({})", azure.region);
+                let bearer = format!("Bearer {value}");
+                *self.token.lock().unwrap() = Some(Token {
+                    region: azure.region.clone(),
+                    key:    azure.key.clone(),
+                    value,
+                    issued: Instant::now(),
+                });
+                Ok(bearer)
+            }
+            Err(e) if is_auth(&e) => {
+                self.invalidate();
+                Err(e)
+            }
+            Err(e) => match stale {
+                Some(value) => {
+                    log::warn!("🔑 token Azure: {e:#} – îl folosesc pe cel vechi");
+                    Ok(format!("Bearer {value}"))
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Reînnoire din timp, apelată cât workerul TTS n-are de lucru: un
+    /// token mai vechi de `refresh_after` e înlocuit acum, nu la replică.
+    /// Fără token (nicio replică încă, altă cheie) nu cere nimic.
+    pub fn refresh_if_due(&self, azure: &Azure) -> Result<()> {
+        match self.cached(azure) {
+            (Some(_), false) => self.bearer(azure).map(drop),
+            _ => Ok(()),
+        }
+    }
+
+    /// cât de des verifică workerul inactiv tokenul
+    pub fn idle_check(&self) -> Duration {
+        self.refresh_after / 8
+    }
+
+    /// Următorul `bearer` cere un token nou.
+    pub fn invalidate(&self) {
+        *self.token.lock().unwrap() = None;
+    }
+
+    /// `(token, proaspăt)` pentru cheia şi regiunea din `azure`; un token
+    /// mai vechi de `lifetime` nu mai e bun nici de rezervă.
+    fn cached(&self, azure: &Azure) -> (Option<String>, bool) {
+        let token = self.token.lock().unwrap();
+        match token.as_ref().filter(|t| t.region == azure.region && t.key == azure.key) {
+            Some(t) if t.issued.elapsed() < self.refresh_after => (Some(t.value.clone()), true),
+            Some(t) if t.issued.elapsed() < self.lifetime => (Some(t.value.clone()), false),
+            _ => (None, false),
+        }
+    }
+
+    /// `f` primeşte `http` şi headerul `Authorization`. Un 401 (token
+    /// revocat, cheie rotită în portal) → token nou şi încă o încercare.
+    pub fn call<T>(
+        &self,
+        http: &mut dyn HttpClient,
+        azure: &Azure,
+        mut f: impl FnMut(&mut dyn HttpClient, &str) -> Result<T>,
+    ) -> Result<T> {
+        let bearer = self.bearer(azure)?;
+        match f(http, &bearer) {
+            Err(e) if is_auth(&e) => {
+                log::warn!("🔑 token Azure respins: {e:#} – cer altul");
+                self.invalidate();
+                let bearer = self.bearer(azure)?;
+                f(http, &bearer)
+            }
+            res => res,
+        }
+    }
+}
+
+fn is_auth(e: &anyhow::Error) -> bool {
+    matches!(e.downcast_ref(), Some(CloudError::Auth { .. }))
+}
+
+/// `POST /sts/v1.0/issueToken` – corpul răspunsului e tokenul (JWT)
+fn issue_token(http: &mut dyn HttpClient, azure: &Azure) -> Result<String> {
+    let url = format!("https://{}.api.cognitive.microsoft.com/sts/v1.0/issueToken", azure.region);
+    AZURE.call_idempotent("Azure token", http, |http| {
+        let mut resp = http.post(&url, &[("Ocp-Apim-Subscription-Key", azure.key.as_str())], &[])?;
+        if resp.status() != 200 {
+            return Err(CloudError::from_response("Azure token", &mut *resp).into());
+        }
+        let token = String::from_utf8(resp.read_to_end()?)?;
+        let token = token.trim();
+        if token.is_empty() || token.contains(char::is_whitespace) {
+            bail!("Azure token: răspuns invalid ({} B)", token.len());
+        }
+        Ok(token.to_owned())
+    })
+}
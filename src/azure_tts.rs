@@ -1,92 +1,434 @@
 use anyhow::{bail, Result};
-use esp_idf_hal::io::Write as _; 
-use embedded_svc::http::{client::Client, Method};
-use esp_idf_svc::{
-    hal::i2s::{I2sDriver, I2sTx},            
-    http::client::{Configuration as HttpCfg, EspHttpConnection},
-    sys::{TickType_t, esp_crt_bundle_attach},
+use embedded_svc::http::Method;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::RecvTimeoutError,
+    Arc, Mutex,
 };
+use std::time::{Duration, Instant};
 
-const KEY:    &str = "6LjHtBn01z3moL6F7CLSOo0l72XWlbQSSB8oD55uBtGfDV528injJQQJ99BEACYeBjFXJ3w3AAAYACOGabqX";
-const REGION: &str = "eastus";
+use crate::azure_auth::AzureAuth;
+use crate::config::{self, Azure};
+use crate::hal::{AudioSink, HttpClient, HttpResponse, SharedHttp};
+use crate::cloud_error::CloudError;
+use crate::lang;
+use crate::queue::Rx;
+use crate::retry::{Abort, AZURE};
+use crate::tts_text;
 
-pub fn tts_and_play(i2s: &mut I2sDriver<'static, I2sTx>, text: &str) -> Result<()> {
-    let ssml = format!(
-        r#"<speak version="1.0" xml:lang="ro-RO">
-               <voice name="ro-RO-AlinaNeural">{}</voice>
-           </speak>"#,
-        text
-    );
-    log::debug!("📤 Construiesc SSML pentru textul: {:?}", text);
+/// un document SSML trimis direct (`/send_text`, ws `say`)
+pub const MAX_SSML: usize = 2048;
 
-    let url = format!(
-        "https://{}.tts.speech.microsoft.com/cognitiveservices/v1",
-        REGION
-    );
-    log::debug!("🔌 Deschid conexiune TLS către {url}");
+/// Ce poate alege o cerere (`/api/chat`, `/send_text`, ws `say`); ce
+/// lipseşte vine din `config.azure`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VoiceOptions {
+    pub voice:  Option<String>,
+    pub locale: Option<String>,
+    pub rate:   Option<String>,
+    pub pitch:  Option<String>,
+    pub style:  Option<String>,
+}
 
-    let mut cfg = HttpCfg::default();
-    cfg.crt_bundle_attach = Some(esp_crt_bundle_attach);
-    cfg.buffer_size = Some(2048);
+impl VoiceOptions {
+    /// `azure` cu alegerile cererii peste el.
+    fn apply(&self, azure: &Azure) -> Azure {
+        let mut out = azure.clone();
+        for (field, value) in [
+            (&mut out.voice, &self.voice),
+            (&mut out.locale, &self.locale),
+            (&mut out.rate, &self.rate),
+            (&mut out.pitch, &self.pitch),
+            (&mut out.style, &self.style),
+        ] {
+            if let Some(v) = value {
+                *field = v.trim().to_owned();
+            }
+        }
+        out
+    }
 
-    cfg.buffer_size_tx = Some(2048);
+    /// Aceleaşi reguli ca în `config` – valorile ajung în SSML.
+    pub fn validate(&self) -> Result<()> {
+        self.apply(&config::get().azure).validate()
+    }
+}
 
-    let conn = EspHttpConnection::new(&cfg)?;
-    let mut hc  = Client::wrap(conn);
+/// Un element din coada TTS.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Speech {
+    pub text:  String,
+    pub voice: VoiceOptions,
+    /// `text` e un document SSML complet, trimis lui Azure neatins – doar
+    /// de la clienţii autentificaţi, niciodată replica modelului
+    pub ssml:  bool,
+}
 
-    log::debug!("➡️  Trimit request POST + headere...");
-    let mut req = hc.request(
-        Method::Post,
-        &url,
-        &[
-            ("Ocp-Apim-Subscription-Key", KEY),
-            ("Content-Type", "application/ssml+xml"),
-            ("X-Microsoft-OutputFormat", "raw-16khz-16bit-mono-pcm"),
-        ],
-    )?;
+impl Speech {
+    /// Un document SSML scris de client; vezi `check_ssml`.
+    pub fn ssml(doc: String) -> Self {
+        Self { text: doc, voice: VoiceOptions::default(), ssml: true }
+    }
 
-    log::debug!("✉️  Scriu corpul SSML ({} B)…", ssml.len());
-    req.write_all(ssml.as_bytes())?;
+    /// Textul pentru `Event::TtsStart`: fără Markdown, iar dintr-un SSML
+    /// doar ce se rosteşte (fără etichete, entităţile decodate).
+    pub fn caption(&self) -> String {
+        if !self.ssml {
+            return tts_text::strip_markdown(&self.text);
+        }
+        let mut plain = String::with_capacity(self.text.len());
+        let mut tag = false;
+        for c in self.text.chars() {
+            match c {
+                '<' => tag = true,
+                '>' if tag => {
+                    tag = false;
+                    plain.push(' ');
+                }
+                c if !tag => plain.push(c),
+                _ => {}
+            }
+        }
+        let plain = plain
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&");
+        plain.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
 
-    log::debug!("⏳ Aştept răspuns Azure…");
-    let mut resp = req.submit()?;
+impl From<String> for Speech {
+    fn from(text: String) -> Self {
+        Self { text, voice: VoiceOptions::default(), ssml: false }
+    }
+}
 
-    // 5️⃣  Verificare status
-    if resp.status() != 200 {
-        bail!("Azure TTS HTTP {}", resp.status());
+impl From<&str> for Speech {
+    fn from(text: &str) -> Self {
+        text.to_owned().into()
     }
+}
 
-    log::debug!("✅ HTTP {} primit – streaming audio începe", resp.status());
-    let mut buf = [0u8; 1024];
-    let mut total = 0usize;    
+/// Vocea folosită efectiv pentru o replică.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Voice {
+    pub name:   String,
+    pub locale: String,
+    pub rate:   String,
+    pub pitch:  String,
+    pub style:  String,
+}
 
+impl Voice {
+    /// Vocea cerută explicit; altfel cea din `azure.voices` pentru limba
+    /// cerută (`locale`) sau detectată în `text`, dacă diferă de limba
+    /// vocii implicite.
+    pub fn select(azure: &Azure, opts: &VoiceOptions, text: &str) -> Voice {
+        let a = opts.apply(azure);
+        let home = lang::language_of(&azure.locale);
+        let (name, locale) = match (&opts.voice, &opts.locale) {
+            (Some(_), Some(_)) => (a.voice, a.locale),
+            (Some(_), None) => {
+                let locale = locale_of(&a.voice).unwrap_or(&a.locale).to_owned();
+                (a.voice, locale)
+            }
+            (None, Some(_)) => {
+                let wanted = lang::language_of(&a.locale);
+                match azure.voices.get(wanted).filter(|_| wanted != home) {
+                    Some(v) => (v.clone(), a.locale.clone()),
+                    None => (a.voice, a.locale.clone()),
+                }
+            }
+            (None, None) => match lang::detect(text).filter(|l| *l != home).and_then(|l| azure.voices.get(l)) {
+                Some(v) => (v.clone(), locale_of(v).unwrap_or(&a.locale).to_owned()),
+                None => (a.voice, a.locale),
+            },
+        };
+        Voice { name, locale, rate: a.rate, pitch: a.pitch, style: a.style }
+    }
+}
+
+/// `"en-US-JennyNeural"` → `"en-US"`
+fn locale_of(voice: &str) -> Option<&str> {
+    let mut dashes = voice.match_indices('-').map(|(i, _)| i);
+    let second = dashes.nth(1)?;
+    Some(&voice[..second])
+}
+
+/// Documentul SSML cu `text` escapat; `express-as` în jurul lui
+/// `prosody`, ambele doar dacă sunt setate.
+pub fn ssml(voice: &Voice, text: &str) -> String {
+    let mut body = tts_text::escape_xml(text);
+    if !voice.rate.is_empty() || !voice.pitch.is_empty() {
+        let mut attrs = String::new();
+        if !voice.rate.is_empty() {
+            attrs += &format!(r#" rate="{}""#, voice.rate);
+        }
+        if !voice.pitch.is_empty() {
+            attrs += &format!(r#" pitch="{}""#, voice.pitch);
+        }
+        body = format!("<prosody{attrs}>{body}</prosody>");
+    }
+    if !voice.style.is_empty() {
+        body = format!(r#"<mstts:express-as style="{}">{body}</mstts:express-as>"#, voice.style);
+    }
+    format!(
+        r#"<speak version="1.0" xmlns="http://www.w3.org/2001/10/synthesis" xmlns:mstts="https://www.w3.org/2001/mstts" xml:lang="{}"><voice name="{}">{body}</voice></speak>"#,
+        voice.locale, voice.name
+    )
+}
+
+/// Următoarea replică din coada workerului TTS; cât coada e goală,
+/// tokenul Azure e reînnoit din timp. `None` – coada a fost închisă.
+pub fn next_speech(rx: &Rx<Speech>, auth: &AzureAuth) -> Option<Speech> {
     loop {
-        let n = resp.read(&mut buf)?;
-        if n == 0 { break; }
-        total += n;
-        log::trace!("🎧 chunk {} B (total {} KB)", n, total / 1024);
+        match rx.recv_timeout(auth.idle_check()) {
+            Ok(speech) => return Some(speech),
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(e) = auth.refresh_if_due(&config::get().azure) {
+                    log::warn!("🔑 reînnoirea tokenului Azure: {e:#}");
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
 
-        amplify_in_place(&mut buf[..n], VOLUME_GAIN);
+/// Cu vocea implicită (sau cea a limbii replicii) – vezi `speak`.
+pub fn tts_and_play(http: &mut dyn HttpClient, auth: &AzureAuth, sink: &mut dyn AudioSink, text: &str) -> Result<()> {
+    speak(http, auth, sink, &Speech::from(text))
+}
 
-        i2s.write(&buf[..n], TickType_t::MAX)?;  
+/// Verificarea unui SSML primit de la client: Azure îl validează complet,
+/// aici doar cât să nu plece la el orice text.
+pub fn check_ssml(doc: &str) -> Result<()> {
+    let doc = doc.trim();
+    if doc.len() > MAX_SSML {
+        bail!("ssml: cel mult {MAX_SSML} de octeţi");
+    }
+    if !doc.starts_with("<speak") || !doc.ends_with("</speak>") {
+        bail!("ssml: se aşteaptă un document <speak …>…</speak>");
     }
+    Ok(())
+}
+
+/// Regiunea, vocea şi amplificarea vin din `config`, tokenul din `auth`;
+/// textul trece prin `tts_text::prepare` (fără Markdown, numerele în litere).
+pub fn speak(http: &mut dyn HttpClient, auth: &AzureAuth, sink: &mut dyn AudioSink, speech: &Speech) -> Result<()> {
+    let cfg = config::get();
+    let ssml = if speech.ssml {
+        log::debug!("📤 SSML de la client ({} B)", speech.text.len());
+        speech.text.trim().to_owned()
+    } else {
+        let voice = Voice::select(&cfg.azure, &speech.voice, &speech.text);
+        let text = tts_text::prepare(&speech.text, &voice.locale);
+        log::debug!("📤 SSML {} ({}) pentru textul: {:?}", voice.name, voice.locale, text);
+        ssml(&voice, &text)
+    };
+
+    let url = format!(
+        "https://{}.tts.speech.microsoft.com/cognitiveservices/v1",
+        cfg.azure.region
+    );
+    log::debug!("➡️  POST {url} + SSML ({} B)…", ssml.len());
+    let total = auth.call(http, &cfg.azure, |http, bearer| AZURE.call("Azure TTS", http, |http| {
+        let mut resp = http.post(
+            &url,
+            &[
+                ("Authorization", bearer),
+                ("Content-Type", "application/ssml+xml"),
+                ("X-Microsoft-OutputFormat", "raw-16khz-16bit-mono-pcm"),
+            ],
+            ssml.as_bytes(),
+        )?;
+
+        // 5️⃣  Verificare status
+        if resp.status() != 200 {
+            return Err(CloudError::from_response("Azure TTS", &mut *resp).into());
+        }
+
+        log::debug!("✅ HTTP {} primit – streaming audio începe", resp.status());
+        let mut buf = [0u8; 1024];
+        let mut total = 0usize;
+
+        loop {
+            // odată ce s-a auzit ceva, nu mai reluăm replica de la capăt
+            let n = match resp.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if total > 0 => return Err(Abort(e).into()),
+                Err(e) => return Err(e),
+            };
+            if n == 0 { break; }
+            total += n;
+            log::trace!("🎧 chunk {} B (total {} KB)", n, total / 1024);
+
+            amplify_in_place(&mut buf[..n], cfg.azure.gain);
+
+            sink.write(&buf[..n]).map_err(Abort)?;
+        }
+        Ok(total)
+    }))?;
+    sink.flush()?;
 
     log::debug!("🏁 Streaming terminat – {} KB redat", total / 1024);
     Ok(())
 }
 
-const VOLUME_GAIN: f32 = 2.0;
-
 fn amplify_in_place(buf: &mut [u8], gain: f32) {
     if gain == 1.0 { return; }    
     for chunk in buf.chunks_exact_mut(2) {   
         let sample = i16::from_le_bytes([chunk[0], chunk[1]]) as f32;
-        let mut amp  = sample * gain;
-        if amp >  32767.0 { amp =  32767.0; } 
-        if amp < -32768.0 { amp = -32768.0; }
+        let amp  = (sample * gain).clamp(-32768.0, 32767.0);
         let out = amp as i16;
         let bytes = out.to_le_bytes();
         chunk[0] = bytes[0];
         chunk[1] = bytes[1];
     }
-}
\ No newline at end of file
+}
+/* ------------ lista vocilor (`GET /api/tts/voices`) ------------------ */
+
+/// o zi – lista se schimbă rar, iar descărcarea e de ~1 MB
+pub const VOICES_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// O voce din `voices/list`, doar câmpurile de care are nevoie pagina.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VoiceInfo {
+    #[serde(rename(deserialize = "ShortName"))]
+    pub name:       String,
+    #[serde(rename(deserialize = "LocalName"))]
+    pub local_name: String,
+    #[serde(rename(deserialize = "Locale"))]
+    pub locale:     String,
+    #[serde(rename(deserialize = "Gender"))]
+    pub gender:     String,
+    #[serde(rename(deserialize = "StyleList"), default)]
+    pub styles:     Vec<String>,
+}
+
+struct Cached {
+    region:  String,
+    fetched: Instant,
+    voices:  Arc<Vec<VoiceInfo>>,
+    /// `?refresh=1` – descărcată din nou la următorul `get`
+    expired: bool,
+}
+
+/// Lista vocilor din regiunea curentă, ţinută `VOICES_TTL`. Descărcarea
+/// (~1 MB prin TLS) rulează pe thread-ul ei: `get` răspunde imediat, cu ce
+/// e în cache, şi nu ţine pe loc task-ul httpd.
+pub struct VoiceCatalog {
+    http:     SharedHttp,
+    auth:     Arc<AzureAuth>,
+    cache:    Mutex<Option<Cached>>,
+    fetching: AtomicBool,
+    /// eroarea ultimei descărcări, arătată o dată dacă nu există listă
+    failed:   Mutex<Option<anyhow::Error>>,
+}
+
+impl VoiceCatalog {
+    /// `http` şi `auth` – aceeaşi conexiune (acelaşi host) şi acelaşi token
+    /// ca workerul TTS
+    pub fn new(http: SharedHttp, auth: Arc<AzureAuth>) -> Self {
+        Self { http, auth, cache: Mutex::new(None), fetching: AtomicBool::new(false), failed: Mutex::new(None) }
+    }
+
+    /// Lista şi vârsta ei. Expirată → descărcată din nou în fundal, iar
+    /// până atunci rămâne cea veche. `None` – nicio listă pentru regiunea
+    /// curentă încă, descărcarea e în curs.
+    pub fn get(self: &Arc<Self>) -> Result<Option<(Arc<Vec<VoiceInfo>>, Duration)>> {
+        let region = config::get().azure.region.clone();
+        let cached = self
+            .cache
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|c| c.region == region)
+            .map(|c| (c.voices.clone(), c.fetched.elapsed(), c.expired));
+        if let Some((voices, age, false)) = &cached {
+            if *age < VOICES_TTL {
+                return Ok(Some((voices.clone(), *age)));
+            }
+        }
+
+        let failed = self.failed.lock().unwrap().take();
+        self.refresh();
+        match (cached, failed) {
+            (Some((voices, age, _)), _) => Ok(Some((voices, age))),
+            (None, Some(e)) => Err(e),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Următorul `get` porneşte o descărcare nouă.
+    pub fn invalidate(&self) {
+        if let Some(c) = self.cache.lock().unwrap().as_mut() {
+            c.expired = true;
+        }
+    }
+
+    /// Descarcă lista pe un thread separat (TLS + parsare în flux, stivă
+    /// proprie); o singură descărcare odată.
+    fn refresh(self: &Arc<Self>) {
+        if self.fetching.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let this = self.clone();
+        let spawned = std::thread::Builder::new()
+            .name("voices".into())
+            .stack_size(24 * 1024)
+            .spawn(move || {
+                let azure = config::get().azure.clone();
+                let fetched = fetch_voices(&mut *this.http.lock().unwrap(), &this.auth, &azure);
+                match fetched {
+                    Ok(voices) => {
+                        log::info!("🗣️ {} voci Azure în {}", voices.len(), azure.region);
+                        *this.cache.lock().unwrap() = Some(Cached {
+                            region:  azure.region,
+                            fetched: Instant::now(),
+                            voices:  Arc::new(voices),
+                            expired: false,
+                        });
+                    }
+                    Err(e) => {
+                        log::warn!("🗣️ lista vocilor: {e:#}");
+                        *this.failed.lock().unwrap() = Some(e);
+                    }
+                }
+                this.fetching.store(false, Ordering::Release);
+            });
+        if let Err(e) = spawned {
+            self.fetching.store(false, Ordering::Release);
+            *self.failed.lock().unwrap() = Some(anyhow::anyhow!("Eroare thread: {e}"));
+        }
+    }
+}
+
+fn fetch_voices(http: &mut dyn HttpClient, auth: &AzureAuth, azure: &Azure) -> Result<Vec<VoiceInfo>> {
+    let url = format!("https://{}.tts.speech.microsoft.com/cognitiveservices/voices/list", azure.region);
+    auth.call(http, azure, |http, bearer| AZURE.call_idempotent("Azure voices", http, |http| {
+        let mut resp = http.request(Method::Get, &url, &[("Authorization", bearer)], &[])?;
+        if resp.status() != 200 {
+            return Err(CloudError::from_response("Azure voices", &mut *resp).into());
+        }
+        // ~1 MB de JSON: doar câmpurile din `VoiceInfo` rămân în memorie
+        let reader = std::io::BufReader::with_capacity(1024, BodyReader(&mut *resp));
+        let voices: Vec<VoiceInfo> = serde_json::from_reader(reader)?;
+        if voices.is_empty() {
+            bail!("Azure voices: listă goală");
+        }
+        Ok(voices)
+    }))
+}
+
+/// Corpul unui răspuns ca `std::io::Read`, pentru `serde_json::from_reader`.
+struct BodyReader<'a>(&'a mut dyn HttpResponse);
+
+impl std::io::Read for BodyReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf).map_err(std::io::Error::other)
+    }
+}
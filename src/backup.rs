@@ -0,0 +1,259 @@
+//! Copia de siguranţă a configuraţiei – aceleaşi setări pe mai mulţi
+//! roboţi: `GET /api/config/export` dă un fişier JSON, `POST
+//! /api/config/import` îl verifică şi îl aplică (sau doar arată ce s-ar
+//! schimba, `?dry_run=1`).
+//!
+//! Fără parolă secretele (`config::SECRETS`) lipsesc şi fişierul are doar
+//! o sumă de control (`checksum`, SHA-256) contra corupţiei – oricine îl
+//! poate modifica şi recalcula suma; robotul care îl importă îşi păstrează
+//! cheile. Cu parolă (`X-Passphrase`) secretele sunt criptate cu
+//! ChaCha20-Poly1305 (cheia din PBKDF2-HMAC-SHA256), iar restul fişierului
+//! intră ca date asociate: o setare schimbată strică tag-ul AEAD.
+
+use anyhow::Result;
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    ChaCha20Poly1305, KeyInit,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use std::fmt;
+
+use crate::clock;
+use crate::config::{self, Config, Section, Store, SCHEMA, SECRETS};
+use crate::util::{ct_eq, hex, random_bytes, sha256, unhex};
+
+pub const FORMAT: &str = "myrobo-config";
+/// corpul lui `POST /api/config/import`
+pub const MAX_BACKUP: usize = 8192;
+/// PBKDF2 – sub o secundă pe ESP32, doar la export/import
+const ITERATIONS: u32 = 4096;
+/// un fişier nu are voie să ţină httpd-ul ocupat minute întregi
+const MAX_ITERATIONS: u32 = 100_000;
+/// singurul algoritm pentru `secrets.data`
+const CIPHER: &str = "chacha20poly1305";
+
+#[derive(Debug)]
+pub enum BackupError {
+    /// nu e o copie de siguranţă sau configuraţia din ea e invalidă
+    Invalid(String),
+    /// schemă mai nouă decât cea a firmware-ului
+    Newer(u32),
+    /// fişierul are secrete criptate – fără parolă nu poate fi verificat
+    NeedsPassphrase,
+    /// parolă greşită sau fişier modificat (tag-ul AEAD nu se potriveşte)
+    WrongPassphrase,
+    /// salvarea în NVS
+    Failed(anyhow::Error),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(why)    => write!(f, "copie de siguranţă invalidă: {why}"),
+            Self::Newer(schema)   => write!(f, "copia e de la un firmware mai nou (schema v{schema}, aici v{SCHEMA}) – actualizează întâi firmware-ul"),
+            Self::NeedsPassphrase => write!(f, "copia e protejată cu parolă (X-Passphrase)"),
+            Self::WrongPassphrase => write!(f, "parolă greşită sau fişier modificat"),
+            Self::Failed(e)       => write!(f, "{e:#}"),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+/// O valoare schimbată; secretele apar ca `config::MASK`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Change {
+    pub path: String,
+    pub from: Value,
+    pub to:   Value,
+}
+
+/// Răspunsul la import.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub dry_run: bool,
+    /// schema fişierului (adus la cea curentă, dacă era mai vechi)
+    pub schema:  u32,
+    /// fişierul avea secretele (criptate)
+    pub secrets: bool,
+    pub changed: Vec<Section>,
+    pub diff:    Vec<Change>,
+    /// pinii noi cer repornire
+    pub restart: bool,
+}
+
+/// Fişierul pentru `config`; cu `passphrase` include secretele.
+pub fn export(config: &Config, passphrase: Option<&str>) -> Result<Value> {
+    let mut plain = config.clone();
+    let secrets: Map<String, Value> = SECRETS
+        .iter()
+        .zip(plain.secrets_mut())
+        .map(|(path, secret)| (path.to_string(), std::mem::take(secret).into()))
+        .collect();
+    let mut cfg = serde_json::to_value(&plain)?;
+    for path in SECRETS {
+        let (section, key) = path.split_once('.').unwrap();
+        cfg[section].as_object_mut().map(|s| s.remove(key));
+    }
+
+    let mut doc = json!({
+        "format":   FORMAT,
+        "schema":   SCHEMA,
+        "firmware": crate::VERSION,
+        "exported": clock::now().map(|t| t.iso8601()),
+        "config":   cfg,
+    });
+    match passphrase.filter(|p| !p.is_empty()) {
+        None => {
+            doc["checksum"] = hex(&sha256(&serde_json::to_vec(&doc)?)).into();
+        }
+        Some(pass) => {
+            let (mut salt, mut nonce) = ([0u8; 16], [0u8; 12]);
+            random_bytes(&mut salt);
+            random_bytes(&mut nonce);
+            doc["secrets"] = json!({
+                "kdf":        "pbkdf2-sha256",
+                "iterations": ITERATIONS,
+                "salt":       hex(&salt),
+                "cipher":     CIPHER,
+                "nonce":      hex(&nonce),
+            });
+            let msg = serde_json::to_vec(&secrets)?;
+            let data = cipher(pass, &salt, ITERATIONS)
+                .encrypt((&nonce).into(), Payload { msg: &msg, aad: &serde_json::to_vec(&doc)? })
+                .map_err(|_| anyhow::anyhow!("criptarea secretelor"))?;
+            doc["secrets"]["data"] = hex(&data).into();
+        }
+    }
+    Ok(doc)
+}
+
+/// Configuraţia din fişier, verificată şi validată; fără secrete în
+/// fişier rămân cele din `current`. Întoarce şi schema fişierului.
+pub fn restore(current: &Config, backup: &Value, passphrase: Option<&str>) -> Result<(Config, u32, bool), BackupError> {
+    let invalid = |why: &str| BackupError::Invalid(why.into());
+    if backup["format"] != FORMAT {
+        return Err(invalid("nu e un fişier exportat de /api/config/export"));
+    }
+    let schema = backup["schema"].as_u64().and_then(|v| u32::try_from(v).ok()).ok_or_else(|| invalid("\"schema\" lipsă"))?;
+    if schema > SCHEMA {
+        return Err(BackupError::Newer(schema));
+    }
+
+    // integritate: AEAD cu parola sau doar suma de control
+    let mut aad = backup.clone();
+    let map = aad.as_object_mut().ok_or_else(|| invalid("nu e un obiect JSON"))?;
+    let mut secrets = None;
+    if let Some(block) = map.get_mut("secrets").and_then(Value::as_object_mut) {
+        let pass = passphrase.filter(|p| !p.is_empty()).ok_or(BackupError::NeedsPassphrase)?;
+        let data = block.remove("data").and_then(|d| d.as_str().and_then(unhex)).ok_or_else(|| invalid("\"secrets.data\" lipsă"))?;
+        if block.get("cipher").and_then(Value::as_str) != Some(CIPHER) {
+            return Err(invalid("\"secrets.cipher\" necunoscut"));
+        }
+        let hex_field = |name: &str| block.get(name).and_then(Value::as_str).and_then(unhex);
+        let salt = hex_field("salt").ok_or_else(|| invalid("\"secrets.salt\" lipsă"))?;
+        let nonce: [u8; 12] = hex_field("nonce")
+            .and_then(|n| n.try_into().ok())
+            .ok_or_else(|| invalid("\"secrets.nonce\" invalid"))?;
+        let iterations = block
+            .get("iterations")
+            .and_then(Value::as_u64)
+            .filter(|n| (1..=MAX_ITERATIONS as u64).contains(n))
+            .ok_or_else(|| invalid("\"secrets.iterations\" invalid"))? as u32;
+        let aad = serde_json::to_vec(&aad).map_err(|e| invalid(&e.to_string()))?;
+        let plain = cipher(pass, &salt, iterations)
+            .decrypt((&nonce).into(), Payload { msg: &data, aad: &aad })
+            .map_err(|_| BackupError::WrongPassphrase)?;
+        let plain: Map<String, Value> = serde_json::from_slice(&plain).map_err(|e| invalid(&format!("secrete: {e}")))?;
+        secrets = Some(plain);
+    } else {
+        let sum = map.remove("checksum").and_then(|s| s.as_str().and_then(unhex)).ok_or_else(|| invalid("lipseşte \"checksum\""))?;
+        let actual = sha256(&serde_json::to_vec(&aad).map_err(|e| invalid(&e.to_string()))?);
+        if !ct_eq(&sum, &actual) {
+            return Err(invalid("fişier corupt (suma de control SHA-256)"));
+        }
+    }
+
+    let mut doc = backup["config"].clone();
+    if !doc.is_object() {
+        return Err(invalid("\"config\" lipsă"));
+    }
+    doc["version"] = schema.into();
+    config::migrate(&mut doc).map_err(|e| invalid(&e.to_string()))?;
+    let mut new: Config = serde_json::from_value(doc).map_err(|e| invalid(&e.to_string()))?;
+    new.version = SCHEMA;
+
+    let mut old = current.clone();
+    for ((path, secret), old) in SECRETS.iter().zip(new.secrets_mut()).zip(old.secrets_mut()) {
+        *secret = match secrets.as_ref().and_then(|s| s.get(*path)).and_then(Value::as_str) {
+            Some(value) => value.to_owned(),
+            None => std::mem::take(old),
+        };
+    }
+    new.validate().map_err(|e| invalid(&format!("{e:#}")))?;
+    Ok((new, schema, secrets.is_some()))
+}
+
+/// Verifică fişierul şi, dacă nu e `dry_run`, îl aplică prin `store`
+/// (abonaţii sunt anunţaţi ca la `PATCH /api/config`).
+pub fn import(store: &Store, backup: &Value, passphrase: Option<&str>, dry_run: bool) -> Result<Report, BackupError> {
+    let current = store.get();
+    let (new, schema, secrets) = restore(&current, backup, passphrase)?;
+    let changed = current.changed(&new);
+    let report = Report {
+        dry_run,
+        schema,
+        secrets,
+        restart: changed.iter().any(|s| s.needs_restart()),
+        diff: diff(&current, &new),
+        changed,
+    };
+    if !dry_run {
+        store.set(new).map_err(BackupError::Failed)?;
+    }
+    Ok(report)
+}
+
+/// Valorile care diferă, cu calea completă (`azure.voice`).
+pub fn diff(old: &Config, new: &Config) -> Vec<Change> {
+    fn walk(path: &str, old: &Value, new: &Value, out: &mut Vec<Change>) {
+        match (old, new) {
+            (Value::Object(a), Value::Object(b)) => {
+                for (key, value) in a {
+                    let full = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+                    walk(&full, value, b.get(key).unwrap_or(&Value::Null), out);
+                }
+            }
+            _ if old != new => {
+                let (from, to) = if SECRETS.contains(&path) {
+                    (mask(old), mask(new))
+                } else {
+                    (old.clone(), new.clone())
+                };
+                out.push(Change { path: path.into(), from, to });
+            }
+            _ => {}
+        }
+    }
+    let (old, new) = (serde_json::to_value(old).unwrap_or_default(), serde_json::to_value(new).unwrap_or_default());
+    let mut out = Vec::new();
+    walk("", &old, &new, &mut out);
+    out
+}
+
+fn mask(v: &Value) -> Value {
+    if v.as_str().is_some_and(|s| !s.is_empty()) {
+        config::MASK.into()
+    } else {
+        v.clone()
+    }
+}
+
+/// cheia AEAD din parolă
+fn cipher(pass: &str, salt: &[u8], iterations: u32) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(pass.as_bytes(), salt, iterations, &mut key);
+    ChaCha20Poly1305::new(&key.into())
+}
@@ -0,0 +1,281 @@
+// ===================== simulator.rs =====================
+//! Asistentul întreg pe PC: aceleaşi rute HTTP, pipeline audio şi
+//! gesturi, cu hardware simulat.
+//!
+//! ```text
+//! cargo +stable run --target x86_64-unknown-linux-gnu --bin simulator -- \
+//!     --port 8080 --openai http://127.0.0.1:9000 --azure http://127.0.0.1:9000 --out sim_out
+//! ```
+//!
+//! * motoare/servo → `<out>/motion.csv` (t_ms, stânga %, dreapta %, servo °, servo °)
+//! * TTS           → `<out>/tts_NNN.wav`
+//! * fără `--openai` / `--azure` cererile cloud eşuează (nu avem TLS pe PC);
+//!   stand-in local: `cargo +stable run -p mock-cloud --target x86_64-unknown-linux-gnu -- 9000`
+
+#[cfg(target_os = "espidf")]
+fn main() {}
+
+#[cfg(not(target_os = "espidf"))]
+fn main() -> anyhow::Result<()> {
+    sim::run()
+}
+
+#[cfg(not(target_os = "espidf"))]
+mod sim {
+    use anyhow::{bail, Context, Result};
+    use std::{
+        fs::{self, File},
+        io::Write as _,
+        path::PathBuf,
+        sync::{mpsc, Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use esp32_hello_world::{
+        audio, auth::Auth, azure_auth::AzureAuth, azure_tts::{self, VoiceCatalog}, certs::CertStore, clock::Clock, config::{Section, Store}, earcon,
+        hal::{
+            HostHttp, HostHttpServer, HostTimeSync, MemoryFirmware, MemoryKv, MotorDriver, RecordingAdvertiser,
+            ScriptedRadio, ServoBank, WavFileSink,
+        },
+        http,
+        mdns::Mdns,
+        motion::Motion,
+        ota::Ota,
+        queue, status,
+        motors::MotorId,
+        retry,
+        servo::ServoId,
+        updater::Updater,
+        wifi::{Backoff, WifiManager},
+        ws::{Event, Hub},
+    };
+
+    const OPENAI_HOST: &str = "https://api.openai.com";
+
+    struct Args {
+        port:   u16,
+        openai: Option<String>,
+        azure:  Option<String>,
+        out:    PathBuf,
+    }
+
+    fn parse_args() -> Result<Args> {
+        let mut args = Args {
+            port:   8080,
+            openai: std::env::var("MYROBO_OPENAI").ok(),
+            azure:  std::env::var("MYROBO_AZURE").ok(),
+            out:    PathBuf::from("sim_out"),
+        };
+        let mut it = std::env::args().skip(1);
+        while let Some(a) = it.next() {
+            let mut val = || it.next().with_context(|| format!("{a} cere o valoare"));
+            match a.as_str() {
+                "--port"   => args.port = val()?.parse()?,
+                "--openai" => args.openai = Some(val()?),
+                "--azure"  => args.azure = Some(val()?),
+                "--out"    => args.out = PathBuf::from(val()?),
+                _          => bail!("argument necunoscut: {a}"),
+            }
+        }
+        Ok(args)
+    }
+
+    /* ------------ hardware simulat -------------------------------------- */
+
+    /// Starea robotului, scrisă ca rând CSV la fiecare schimbare.
+    struct SimState {
+        start:  Instant,
+        csv:    File,
+        motors: [i8; 2],
+        servos: [f32; 2],
+    }
+
+    impl SimState {
+        fn record(&mut self) -> Result<()> {
+            let t = self.start.elapsed().as_millis();
+            let [l, r] = self.motors;
+            let [sl, sr] = self.servos;
+            writeln!(self.csv, "{t},{l},{r},{sl:.0},{sr:.0}")?;
+            log::info!(
+                "🤖 motoare {:>4}% {:<20}|{:>4}% {:<20}  servo {sl:>3.0}° {sr:>3.0}°",
+                l, bar(l), r, bar(r),
+            );
+            Ok(())
+        }
+    }
+
+    fn bar(speed: i8) -> String {
+        let n = (speed.unsigned_abs() as usize) / 5;
+        let c = if speed < 0 { "◀" } else { "▶" };
+        c.repeat(n)
+    }
+
+    struct SimMotors(Arc<Mutex<SimState>>);
+
+    impl MotorDriver for SimMotors {
+        fn drive(&mut self, id: MotorId, speed: i8) -> Result<()> {
+            let mut st = self.0.lock().unwrap();
+            st.motors[id as usize] = speed.clamp(-100, 100);
+            st.record()
+        }
+    }
+
+    struct SimServos(Arc<Mutex<SimState>>);
+
+    impl ServoBank for SimServos {
+        fn set_angle(&mut self, id: ServoId, deg: f32) -> Result<()> {
+            let mut st = self.0.lock().unwrap();
+            st.servos[id as usize] = deg.clamp(0.0, 180.0);
+            st.record()
+        }
+    }
+
+    /* ------------ log pe stderr ----------------------------------------- */
+
+    struct StderrLogger;
+
+    impl log::Log for StderrLogger {
+        fn enabled(&self, m: &log::Metadata) -> bool {
+            m.level() <= log::max_level()
+        }
+
+        fn log(&self, r: &log::Record) {
+            if self.enabled(r.metadata()) {
+                eprintln!("{:<5} {}: {}", r.level(), r.target(), r.args());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: StderrLogger = StderrLogger;
+
+    /* -------------------------------------------------------------------- */
+
+    pub fn run() -> Result<()> {
+        log::set_logger(&LOGGER).map_err(|e| anyhow::anyhow!("{e}"))?;
+        log::set_max_level(log::LevelFilter::Info);
+
+        let args = parse_args()?;
+        fs::create_dir_all(&args.out)?;
+
+        // setările doar în memorie; redirecţionările de mai jos folosesc regiunea de la pornire
+        let config = Arc::new(Store::load(MemoryKv::default())?);
+
+        let mut http_base = HostHttp::new().timeout(Duration::from_secs(30));
+        if let Some(url) = &args.openai {
+            http_base = http_base.redirect(OPENAI_HOST, url);
+        }
+        if let Some(url) = &args.azure {
+            let region = &config.get().azure.region;
+            http_base = http_base
+                .redirect(&format!("https://{region}.tts.speech.microsoft.com"), url)
+                .redirect(&format!("https://{region}.api.cognitive.microsoft.com"), url);
+        }
+
+        // hardware simulat
+        let mut csv = File::create(args.out.join("motion.csv"))?;
+        writeln!(csv, "t_ms,left,right,servo_left,servo_right")?;
+        let state = Arc::new(Mutex::new(SimState {
+            start:  Instant::now(),
+            csv,
+            motors: [0; 2],
+            servos: [90.0; 2],
+        }));
+        let motion = Arc::new(Mutex::new(Motion::new(
+            SimMotors(state.clone()),
+            SimServos(state.clone()),
+        )));
+
+        // canale – identic cu firmware-ul
+        let (tx_http2audio, rx_http2audio) = queue::channel::<audio::Job>("audio");
+        let (tx_audio2http, rx_audio2http) = mpsc::channel::<audio::Exchange>();
+        let rx_audio2http = Arc::new(Mutex::new(rx_audio2http));
+        let (tx_tts, rx_tts) = queue::channel::<azure_tts::Speech>("tts");
+        let hub = Hub::start();
+
+        {
+            let (tx_audio, hub) = (tx_http2audio.clone(), hub.clone());
+            config.subscribe(move |_, changed| {
+                if changed.contains(&Section::Openai) {
+                    let _ = tx_audio.send(audio::Job::Reset);
+                }
+                hub.publish(&Event::Config { changed: changed.to_vec() });
+            });
+        }
+
+        let azure_auth = Arc::new(AzureAuth::new(http_base.clone()));
+        let tts_http = Arc::new(Mutex::new(http_base.clone()));
+        {
+            let out = args.out.clone();
+            let (hub, azure_auth, tts_http) = (hub.clone(), azure_auth.clone(), tts_http.clone());
+            thread::Builder::new().name("tts_worker".into()).spawn(move || {
+                status::watch_stack("tts_worker");
+                let mut n = 0u32;
+                while let Some(speech) = azure_tts::next_speech(&rx_tts, &azure_auth) {
+                    n += 1;
+                    let path = out.join(format!("tts_{n:03}.wav"));
+                    log::info!("🔊 TTS worker: \"{}\" → {}", speech.text, path.display());
+                    hub.publish(&Event::TtsStart { text: speech.caption() });
+                    let res = WavFileSink::create(&path, 16_000).and_then(|mut wav| {
+                        azure_tts::speak(&mut *tts_http.lock().unwrap(), &azure_auth, &mut wav, &speech).inspect_err(|_| {
+                            if retry::AZURE.is_open() {
+                                let _ = earcon::play_offline(&mut wav);
+                            }
+                        })
+                    });
+                    if let Err(e) = res {
+                        log::error!("tts_and_play error: {:?}", e);
+                    }
+                    hub.publish(&Event::TtsStop);
+                }
+            })?;
+        }
+
+        {
+            let http = http_base.clone();
+            thread::Builder::new().name("audio".into()).spawn(move || {
+                status::watch_stack("audio");
+                audio::audio_task(rx_http2audio, tx_audio2http, || Ok(http.clone()));
+            })?;
+        }
+
+        let mut server = HostHttpServer::new();
+        // token nou la fiecare pornire – simulatorul nu are NVS
+        let auth = Arc::new(Auth::load(MemoryKv::default())?);
+        log::info!("🔑 token API: {}", auth.token());
+        http::register_handlers(&mut server, tx_http2audio, rx_audio2http, motion, tx_tts, hub.clone(), auth.clone())?;
+        // `/api/tls` merge, dar simulatorul rămâne pe HTTP
+        http::register_tls(&mut server, auth.clone(), Arc::new(CertStore::new(MemoryKv::default())), Vec::new)?;
+        // mDNS doar înregistrat – pe PC robotul e la 127.0.0.1
+        let mdns = Mdns::start(RecordingAdvertiser::default(), MemoryKv::default(), args.port, false)?;
+        http::register_mdns(&mut server, auth.clone(), Arc::new(Mutex::new(mdns)))?;
+        // imaginea încărcată rămâne în memorie; „repornirea” doar se numără
+        let ota = Arc::new(Ota::new(MemoryFirmware::new(0x1E_0000), None));
+        http::register_ota(&mut server, auth.clone(), ota.clone(), hub)?;
+        // manifestul poate fi servit local, ex. `python3 -m http.server`
+        let updater = Updater::new(ota, HostHttp::new(), MemoryKv::default())?.start()?;
+        http::register_updater(&mut server, auth.clone(), updater)?;
+        // o reţea „simulator” mereu vizibilă; `/api/wifi` doar editează lista
+        let radio = ScriptedRadio::new();
+        radio.network("simulator", "", -50);
+        let wifi = WifiManager::new(radio, MemoryKv::default(), Backoff::DEFAULT)?;
+        wifi.add_network("simulator", "", 0)?;
+        http::register_wifi(&mut server, auth.clone(), wifi.start()?)?;
+        // ceasul PC-ului; fusul orar şi serverele doar în memorie
+        let clock = Clock::new(HostTimeSync::default(), MemoryKv::default())?;
+        clock.start()?;
+        http::register_clock(&mut server, auth.clone(), Arc::new(clock))?;
+        http::register_config(&mut server, auth.clone(), config.clone())?;
+        http::register_backup(&mut server, auth.clone(), config)?;
+        http::register_tts(&mut server, auth, Arc::new(VoiceCatalog::new(tts_http, azure_auth)))?;
+        http::register_static(&mut server)?;
+        let addr = server.start(("127.0.0.1", args.port))?;
+        log::info!("HTTP ready – http://{addr}/  (ieşiri în {})", args.out.display());
+
+        loop {
+            thread::sleep(Duration::from_secs(3600));
+        }
+    }
+}
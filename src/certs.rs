@@ -0,0 +1,256 @@
+//! Certificatul serverului HTTPS: cel din repo (`server.crt`/`server.key`,
+//! inclus în firmware), unul încărcat din pagină sau unul auto-semnat
+//! generat pe robot – ultimele două păstrate în NVS.
+//!
+//! Browserele dau acces la microfon (`getUserMedia`) doar în context
+//! securizat, deci chat-ul cu voce are nevoie de HTTPS.
+
+use anyhow::{bail, Result};
+use log::info;
+use serde::Serialize;
+use std::{net::IpAddr, sync::Mutex};
+
+use crate::hal::KeyValueStore;
+
+const BUNDLED_CERT: &str = include_str!("../server.crt");
+const BUNDLED_KEY:  &str = include_str!("../server.key");
+
+const KEY_HTTPS:  &str = "https";
+const KEY_CERT:   &str = "tls_cert";
+const KEY_KEY:    &str = "tls_key";
+const KEY_SOURCE: &str = "tls_src";
+
+/// Un lanţ server + intermediar încape. PEM-urile se salvează ca blob NVS
+/// (`EspKv::set` → `nvs_set_blob`), nu ca string – limita de 4000 B a
+/// string-urilor NVS nu se aplică.
+pub const MAX_PEM: usize = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Bundled,
+    Uploaded,
+    SelfSigned,
+}
+
+/// Certificat + cheie PEM, terminate cu NUL (aşa le vrea `esp_https_server`).
+pub struct Pem {
+    pub cert:   String,
+    pub key:    String,
+    pub source: Source,
+}
+
+pub struct CertStore {
+    kv: Mutex<Box<dyn KeyValueStore + Send>>,
+}
+
+impl CertStore {
+    pub fn new(kv: impl KeyValueStore + Send + 'static) -> Self {
+        Self { kv: Mutex::new(Box::new(kv)) }
+    }
+
+    /// HTTPS e opţional – implicit robotul ascultă doar pe HTTP.
+    pub fn https_enabled(&self) -> bool {
+        matches!(self.kv.lock().unwrap().get_str(KEY_HTTPS), Ok(Some(v)) if v == "1")
+    }
+
+    pub fn set_https(&self, on: bool) -> Result<()> {
+        self.kv.lock().unwrap().set_str(KEY_HTTPS, if on { "1" } else { "0" })
+    }
+
+    pub fn source(&self) -> Source {
+        match self.kv.lock().unwrap().get_str(KEY_SOURCE) {
+            Ok(Some(s)) if s == "uploaded"    => Source::Uploaded,
+            Ok(Some(s)) if s == "self_signed" => Source::SelfSigned,
+            _ => Source::Bundled,
+        }
+    }
+
+    /// Certificatul din NVS sau, dacă lipseşte, cel din firmware.
+    pub fn load(&self) -> Result<Pem> {
+        let kv = self.kv.lock().unwrap();
+        let stored = (kv.get_str(KEY_CERT)?, kv.get_str(KEY_KEY)?);
+        drop(kv);
+        let (cert, key, source) = match stored {
+            (Some(c), Some(k)) => (c, k, self.source()),
+            _ => (BUNDLED_CERT.to_owned(), BUNDLED_KEY.to_owned(), Source::Bundled),
+        };
+        Ok(Pem { cert: with_nul(cert), key: with_nul(key), source })
+    }
+
+    /// Salvează o pereche nouă; intră în vigoare la repornire.
+    pub fn install(&self, cert: &str, key: &str, source: Source) -> Result<()> {
+        validate(cert, key)?;
+        let mut kv = self.kv.lock().unwrap();
+        kv.set_str(KEY_CERT, cert.trim())?;
+        kv.set_str(KEY_KEY, key.trim())?;
+        kv.set_str(KEY_SOURCE, if source == Source::SelfSigned { "self_signed" } else { "uploaded" })?;
+        info!("🔒 certificat HTTPS nou ({source:?})");
+        Ok(())
+    }
+
+    /// Înapoi la certificatul din firmware.
+    pub fn reset(&self) -> Result<()> {
+        let mut kv = self.kv.lock().unwrap();
+        for key in [KEY_CERT, KEY_KEY, KEY_SOURCE] {
+            kv.remove(key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Doar o verificare de formă – mbedTLS respinge restul la pornire.
+fn validate(cert: &str, key: &str) -> Result<()> {
+    let (cert, key) = (cert.trim(), key.trim());
+    if cert.len() > MAX_PEM || key.len() > MAX_PEM {
+        bail!("PEM prea mare (max. {MAX_PEM} B)");
+    }
+    if !cert.starts_with("-----BEGIN CERTIFICATE-----") || !cert.ends_with("-----END CERTIFICATE-----") {
+        bail!("certificatul trebuie să fie PEM (BEGIN CERTIFICATE)");
+    }
+    if !key.starts_with("-----BEGIN ") || !key.ends_with("PRIVATE KEY-----") {
+        bail!("cheia trebuie să fie PEM (BEGIN … PRIVATE KEY)");
+    }
+    Ok(())
+}
+
+fn with_nul(mut pem: String) -> String {
+    if !pem.ends_with('\0') {
+        pem.push('\0');
+    }
+    pem
+}
+
+/* ------------ certificat auto-semnat (mbedTLS) ----------------------- */
+
+/// Extensia `subjectAltName` (RFC 5280 §4.2.1.6) în DER: IP-urile ca
+/// `iPAddress`, restul ca `dNSName`. Browserele nu se mai uită la CN – fără
+/// SAN certificatul e respins chiar dacă utilizatorul l-a acceptat.
+pub fn subject_alt_name(names: &[String]) -> Vec<u8> {
+    fn der(tag: u8, body: &[u8], out: &mut Vec<u8>) {
+        out.push(tag);
+        match body.len() {
+            n @ 0..=0x7f => out.push(n as u8),
+            n @ 0x80..=0xff => out.extend_from_slice(&[0x81, n as u8]),
+            n => out.extend_from_slice(&[0x82, (n >> 8) as u8, n as u8]),
+        }
+        out.extend_from_slice(body);
+    }
+
+    let mut general_names = Vec::new();
+    for name in names {
+        match name.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => der(0x87, &ip.octets(), &mut general_names),
+            Ok(IpAddr::V6(ip)) => der(0x87, &ip.octets(), &mut general_names),
+            Err(_) => der(0x82, name.as_bytes(), &mut general_names),
+        }
+    }
+    let mut out = Vec::new();
+    der(0x30, &general_names, &mut out);
+    out
+}
+
+/// Cheie EC P-256 + certificat auto-semnat pentru `cn`, valabil 20 de ani
+/// (robotul nu are încă ceas la pornire), cu `names` (nume şi IP-uri) în
+/// `subjectAltName`. Întoarce (cert, cheie) PEM.
+#[cfg(target_os = "espidf")]
+pub fn self_signed(cn: &str, names: &[String]) -> Result<(String, String)> {
+    use esp_idf_svc::sys::*;
+    use std::ffi::{c_void, CStr, CString};
+
+    fn check(code: i32, what: &str) -> Result<()> {
+        if code != 0 {
+            bail!("mbedTLS {what}: -0x{:04X}", -code);
+        }
+        Ok(())
+    }
+
+    let name = CString::new(format!("CN={cn},O=MyRoboAssistant"))?;
+    let san = subject_alt_name(names);
+    let mut serial = [0u8; 16];
+    crate::util::random_bytes(&mut serial);
+    serial[0] &= 0x7f;                  // număr pozitiv
+
+    let mut cert_pem = vec![0u8; MAX_PEM];
+    let mut key_pem  = vec![0u8; MAX_PEM];
+
+    unsafe {
+        let mut pk:      mbedtls_pk_context        = core::mem::zeroed();
+        let mut crt:     mbedtls_x509write_cert    = core::mem::zeroed();
+        let mut entropy: mbedtls_entropy_context   = core::mem::zeroed();
+        let mut drbg:    mbedtls_ctr_drbg_context  = core::mem::zeroed();
+        mbedtls_pk_init(&mut pk);
+        mbedtls_x509write_crt_init(&mut crt);
+        mbedtls_entropy_init(&mut entropy);
+        mbedtls_ctr_drbg_init(&mut drbg);
+
+        let res = (|| -> Result<()> {
+            let rng = &mut drbg as *mut _ as *mut c_void;
+            check(mbedtls_ctr_drbg_seed(
+                &mut drbg,
+                Some(mbedtls_entropy_func),
+                &mut entropy as *mut _ as *mut c_void,
+                b"myrobo".as_ptr(),
+                6,
+            ), "seed")?;
+            check(mbedtls_pk_setup(&mut pk, mbedtls_pk_info_from_type(mbedtls_pk_type_t_MBEDTLS_PK_ECKEY)), "pk")?;
+            check(mbedtls_ecp_gen_key(
+                mbedtls_ecp_group_id_MBEDTLS_ECP_DP_SECP256R1,
+                pk.private_pk_ctx as *mut mbedtls_ecp_keypair,
+                Some(mbedtls_ctr_drbg_random),
+                rng,
+            ), "cheie")?;
+
+            mbedtls_x509write_crt_set_version(&mut crt, MBEDTLS_X509_CRT_VERSION_3 as i32);
+            mbedtls_x509write_crt_set_md_alg(&mut crt, mbedtls_md_type_t_MBEDTLS_MD_SHA256);
+            mbedtls_x509write_crt_set_subject_key(&mut crt, &mut pk);
+            mbedtls_x509write_crt_set_issuer_key(&mut crt, &mut pk);
+            check(mbedtls_x509write_crt_set_subject_name(&mut crt, name.as_ptr()), "subject")?;
+            check(mbedtls_x509write_crt_set_issuer_name(&mut crt, name.as_ptr()), "issuer")?;
+            check(mbedtls_x509write_crt_set_serial_raw(&mut crt, serial.as_mut_ptr(), serial.len()), "serial")?;
+            check(mbedtls_x509write_crt_set_validity(
+                &mut crt,
+                c"20250101000000".as_ptr(),
+                c"20450101000000".as_ptr(),
+            ), "validity")?;
+            check(mbedtls_x509write_crt_set_basic_constraints(&mut crt, 0, -1), "constraints")?;
+            // MBEDTLS_OID_SUBJECT_ALT_NAME = 2.5.29.17
+            let oid = b"\x55\x1d\x11";
+            check(mbedtls_x509write_crt_set_extension(
+                &mut crt,
+                oid.as_ptr().cast(),
+                oid.len(),
+                0,
+                san.as_ptr(),
+                san.len(),
+            ), "subjectAltName")?;
+
+            check(mbedtls_x509write_crt_pem(
+                &mut crt,
+                cert_pem.as_mut_ptr(),
+                cert_pem.len(),
+                Some(mbedtls_ctr_drbg_random),
+                rng,
+            ), "cert PEM")?;
+            check(mbedtls_pk_write_key_pem(&mut pk, key_pem.as_mut_ptr(), key_pem.len()), "cheie PEM")?;
+            Ok(())
+        })();
+
+        mbedtls_x509write_crt_free(&mut crt);
+        mbedtls_pk_free(&mut pk);
+        mbedtls_ctr_drbg_free(&mut drbg);
+        mbedtls_entropy_free(&mut entropy);
+        res?;
+    }
+
+    let pem = |buf: &[u8]| -> Result<String> {
+        Ok(CStr::from_bytes_until_nul(buf)?.to_str()?.to_owned())
+    };
+    Ok((pem(&cert_pem)?, pem(&key_pem)?))
+}
+
+/// Pe PC nu avem mbedTLS – simulatorul rămâne pe HTTP.
+#[cfg(not(target_os = "espidf"))]
+pub fn self_signed(_cn: &str, _names: &[String]) -> Result<(String, String)> {
+    bail!("certificatul auto-semnat se generează doar pe ESP32")
+}
@@ -0,0 +1,416 @@
+//! Ceasul: SNTP pornit după ce Wi-Fi-ul are IP, fusul orar ca şir POSIX
+//! (`EET-2EEST,M3.5.0/3,M10.5.0/4`) salvat în NVS şi ora locală pentru
+//! jurnal, ferestre de mentenanţă şi prompt-ul de chat.
+//!
+//! Până la primul răspuns SNTP ceasul ESP32 porneşte din 1970 – `now()`
+//! întoarce `None`, nu o dată greşită.
+//!
+//! Pe ESP32 ora locală vine din `localtime_r` (newlib, `TZ` + `tzset`);
+//! aici şirul e doar validat. Calculul orei de vară (`clock/host.rs`) e
+//! pentru PC (simulator, teste), unde `TZ`-ul procesului nu e al nostru.
+
+use anyhow::{bail, Result};
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::hal::{KeyValueStore, TimeSync};
+
+// ora locală pe PC, fără `localtime_r`
+#[cfg(not(target_os = "espidf"))]
+mod host;
+#[cfg(not(target_os = "espidf"))]
+use host::{apply_tz, local};
+
+const KEY_SERVERS: &str = "ntp_servers";
+const KEY_TZ:      &str = "tz";
+
+/// România, cu ora de vară
+pub const DEFAULT_TZ: &str = "EET-2EEST,M3.5.0/3,M10.5.0/4";
+pub const DEFAULT_SERVERS: [&str; 2] = ["pool.ntp.org", "time.google.com"];
+/// `CONFIG_LWIP_SNTP_MAX_SERVERS`
+pub const MAX_SERVERS: usize = 3;
+/// ceasul e considerat setat după 2024-01-01
+pub const CLOCK_VALID: u64 = 1_704_067_200;
+
+const ZILE: [&str; 7] = ["duminică", "luni", "marţi", "miercuri", "joi", "vineri", "sâmbătă"];
+const LUNI: [&str; 12] = [
+    "ianuarie", "februarie", "martie", "aprilie", "mai", "iunie",
+    "iulie", "august", "septembrie", "octombrie", "noiembrie", "decembrie",
+];
+
+/* ------------ starea globală (ca `retry::OPENAI`) --------------------- */
+
+static SYNCED:    AtomicBool              = AtomicBool::new(false);
+static LAST_SYNC: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Apelată de SNTP la fiecare sincronizare reuşită.
+pub fn mark_synced() {
+    let first = !SYNCED.swap(true, Ordering::Relaxed);
+    *LAST_SYNC.lock().unwrap() = Some(crate::status::uptime());
+    if first {
+        info!("🕒 ceas sincronizat – {}", now().map_or_else(|| "?".into(), |t| t.iso8601()));
+    }
+}
+
+/// SNTP a răspuns şi ceasul sistemului arată o dată plauzibilă.
+pub fn synced() -> bool {
+    SYNCED.load(Ordering::Relaxed) && unix_now().is_some()
+}
+
+/// Secunde de la pornire la ultima sincronizare.
+pub fn last_sync() -> Option<Duration> {
+    *LAST_SYNC.lock().unwrap()
+}
+
+fn unix_now() -> Option<i64> {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    (secs >= CLOCK_VALID).then_some(secs as i64)
+}
+
+/// Ora locală, dacă ceasul e sincronizat (fără fus orar setat = UTC).
+pub fn now() -> Option<LocalTime> {
+    if !SYNCED.load(Ordering::Relaxed) {
+        return None;
+    }
+    unix_now().map(local)
+}
+
+/// Fusul orar pentru `localtime_r` din C: `now()` şi marcajele de timp din
+/// log (`CONFIG_LOG_TIMESTAMP_SOURCE_SYSTEM`).
+#[cfg(target_os = "espidf")]
+fn apply_tz(tz: &str) {
+    std::env::set_var("TZ", tz);
+    unsafe { esp_idf_svc::sys::tzset() };
+}
+
+/// `localtime_r`; offset-ul şi abrevierea din `strftime("%z %Z")` –
+/// `struct tm` din newlib nu are `tm_gmtoff`.
+#[cfg(target_os = "espidf")]
+fn local(utc: i64) -> LocalTime {
+    use esp_idf_svc::sys::{localtime_r, strftime, time_t, tm};
+
+    let mut tm: tm = unsafe { std::mem::zeroed() };
+    let mut buf = [0u8; 32];
+    let len = unsafe {
+        localtime_r(&(utc as time_t), &mut tm);
+        strftime(buf.as_mut_ptr().cast(), buf.len() as _, c"%z %Z".as_ptr(), &tm)
+    };
+    // „+0300 EEST”
+    let zone = std::str::from_utf8(&buf[..len as usize]).unwrap_or_default();
+    let (z, abbr) = zone.split_once(' ').unwrap_or(("+0000", "UTC"));
+    let hhmm: i32 = z.get(1..).and_then(|n| n.parse().ok()).unwrap_or(0);
+    let sign = if z.starts_with('-') { -1 } else { 1 };
+    LocalTime {
+        year:     tm.tm_year + 1900,
+        month:    (tm.tm_mon + 1) as u8,
+        day:      tm.tm_mday as u8,
+        hour:     tm.tm_hour as u8,
+        minute:   tm.tm_min as u8,
+        second:   tm.tm_sec as u8,
+        weekday:  tm.tm_wday as u8,
+        offset_s: sign * (hhmm / 100 * 3600 + hhmm % 100 * 60),
+        dst:      tm.tm_isdst > 0,
+        abbr:     abbr.into(),
+    }
+}
+
+/* ------------ ora locală ---------------------------------------------- */
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LocalTime {
+    pub year:     i32,
+    pub month:    u8,
+    pub day:      u8,
+    pub hour:     u8,
+    pub minute:   u8,
+    pub second:   u8,
+    /// 0 = duminică
+    pub weekday:  u8,
+    /// secunde faţă de UTC (+10800 vara în România)
+    pub offset_s: i32,
+    pub dst:      bool,
+    pub abbr:     String,
+}
+
+impl LocalTime {
+    pub fn minute_of_day(&self) -> u16 {
+        self.hour as u16 * 60 + self.minute as u16
+    }
+
+    /// `2026-10-19T14:05:00+03:00`
+    pub fn iso8601(&self) -> String {
+        let sign = if self.offset_s < 0 { '-' } else { '+' };
+        let off = self.offset_s.unsigned_abs();
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{sign}{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second, off / 3600, off % 3600 / 60
+        )
+    }
+
+    /// „luni, 19 octombrie 2026, ora 14:05” – pentru prompt-ul de chat
+    pub fn describe(&self) -> String {
+        format!(
+            "{}, {} {} {}, ora {:02}:{:02}",
+            ZILE[self.weekday as usize], self.day, LUNI[self.month as usize - 1], self.year, self.hour, self.minute
+        )
+    }
+}
+
+/* ------------ fus orar POSIX ------------------------------------------ */
+
+// Pe ESP32 `Tz` doar validează şirul; regulile le aplică newlib.
+
+/// Ziua schimbării orei: `Mm.w.d`, `Jn` (fără 29 feb.) sau `n` (de la 0).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(target_os = "espidf", allow(dead_code))]
+enum Day {
+    Month { month: u8, week: u8, weekday: u8 },
+    Julian(u16),
+    Zero(u16),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(target_os = "espidf", allow(dead_code))]
+struct Rule {
+    day:  Day,
+    /// secunde de la miezul nopţii (ora locală în vigoare)
+    time: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(target_os = "espidf", allow(dead_code))]
+struct Dst {
+    abbr:   String,
+    offset: i32,
+    start:  Rule,
+    end:    Rule,
+}
+
+/// Un şir `TZ` POSIX: `STDoffset[DST[offset][,start[/time],end[/time]]]`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(target_os = "espidf", allow(dead_code))]
+pub struct Tz {
+    abbr:   String,
+    /// secunde faţă de UTC (invers faţă de şir: `EET-2` → +7200)
+    offset: i32,
+    dst:    Option<Dst>,
+}
+
+impl Tz {
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut p = Parser(s.trim());
+        let abbr = p.name()?;
+        let offset = -p.offset()?;
+        if p.0.is_empty() {
+            return Some(Self { abbr, offset, dst: None });
+        }
+        let dst_abbr = p.name()?;
+        let dst_offset = match p.0.chars().next() {
+            Some(',') | None => offset + 3600,
+            _ => -p.offset()?,
+        };
+        // fără reguli: cele din SUA, ca în glibc
+        let (start, end) = if p.0.is_empty() {
+            (Rule { day: Day::Month { month: 3, week: 2, weekday: 0 }, time: 7200 },
+             Rule { day: Day::Month { month: 11, week: 1, weekday: 0 }, time: 7200 })
+        } else {
+            p.eat(',')?;
+            let start = p.rule()?;
+            p.eat(',')?;
+            (start, p.rule()?)
+        };
+        if !p.0.is_empty() {
+            return None;
+        }
+        Some(Self { abbr, offset, dst: Some(Dst { abbr: dst_abbr, offset: dst_offset, start, end }) })
+    }
+}
+
+struct Parser<'a>(&'a str);
+
+impl Parser<'_> {
+    fn eat(&mut self, c: char) -> Option<()> {
+        self.0 = self.0.strip_prefix(c)?;
+        Some(())
+    }
+
+    /// `EET` sau `<+03>`
+    fn name(&mut self) -> Option<String> {
+        let (name, rest) = if let Some(quoted) = self.0.strip_prefix('<') {
+            let end = quoted.find('>')?;
+            (&quoted[..end], &quoted[end + 1..])
+        } else {
+            let end = self.0.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(self.0.len());
+            self.0.split_at(end)
+        };
+        if name.len() < 3 {
+            return None;
+        }
+        self.0 = rest;
+        Some(name.into())
+    }
+
+    fn number(&mut self) -> Option<i32> {
+        let end = self.0.find(|c: char| !c.is_ascii_digit()).unwrap_or(self.0.len());
+        if end == 0 || end > 3 {
+            return None;
+        }
+        let n = self.0[..end].parse().ok()?;
+        self.0 = &self.0[end..];
+        Some(n)
+    }
+
+    /// `[+-]hh[:mm[:ss]]` în secunde
+    fn offset(&mut self) -> Option<i32> {
+        let sign = match self.0.chars().next()? {
+            '-' => { self.0 = &self.0[1..]; -1 }
+            '+' => { self.0 = &self.0[1..]; 1 }
+            _ => 1,
+        };
+        let mut secs = self.number()? * 3600;
+        for unit in [60, 1] {
+            if self.eat(':').is_none() {
+                break;
+            }
+            secs += self.number().filter(|n| *n < 60)? * unit;
+        }
+        (secs <= 167 * 3600).then_some(sign * secs)
+    }
+
+    fn rule(&mut self) -> Option<Rule> {
+        let day = if self.eat('M').is_some() {
+            let month = self.number().filter(|m| (1..=12).contains(m))? as u8;
+            self.eat('.')?;
+            let week = self.number().filter(|w| (1..=5).contains(w))? as u8;
+            self.eat('.')?;
+            let weekday = self.number().filter(|d| (0..=6).contains(d))? as u8;
+            Day::Month { month, week, weekday }
+        } else if self.eat('J').is_some() {
+            Day::Julian(self.number().filter(|n| (1..=365).contains(n))? as u16)
+        } else {
+            Day::Zero(self.number().filter(|n| (0..=365).contains(n))? as u16)
+        };
+        let time = if self.eat('/').is_some() { self.offset()? } else { 7200 };
+        Some(Rule { day, time })
+    }
+}
+
+/* ------------ setări + SNTP ------------------------------------------- */
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Settings {
+    pub servers: Vec<String>,
+    /// şir `TZ` POSIX
+    pub tz:      String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { servers: DEFAULT_SERVERS.map(String::from).to_vec(), tz: DEFAULT_TZ.into() }
+    }
+}
+
+/// Ce arată `/api/clock`.
+#[derive(Clone, Debug, Serialize)]
+pub struct State {
+    pub synced:      bool,
+    /// ora locală ISO 8601
+    pub now:         Option<String>,
+    pub local:       Option<LocalTime>,
+    /// secunde de la pornire la ultima sincronizare
+    pub last_sync_s: Option<u64>,
+    #[serde(flatten)]
+    pub settings:    Settings,
+}
+
+pub struct Clock {
+    sync:     Mutex<Box<dyn TimeSync + Send>>,
+    kv:       Mutex<Box<dyn KeyValueStore + Send>>,
+    settings: RwLock<Settings>,
+    started:  AtomicBool,
+}
+
+impl Clock {
+    /// Setările din NVS; fusul orar e aplicat imediat, SNTP abia la `start`.
+    pub fn new(sync: impl TimeSync + Send + 'static, kv: impl KeyValueStore + Send + 'static) -> Result<Self> {
+        let mut settings = Settings::default();
+        if let Some(list) = kv.get_str(KEY_SERVERS)? {
+            let servers: Vec<String> = list.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect();
+            if !servers.is_empty() {
+                settings.servers = servers;
+            }
+        }
+        match kv.get_str(KEY_TZ)? {
+            Some(tz) if Tz::parse(&tz).is_some() => settings.tz = tz,
+            Some(tz) => warn!("🕒 fus orar invalid în NVS ({tz:?}) – folosesc {DEFAULT_TZ}"),
+            None => {}
+        }
+        apply_tz(&settings.tz);
+        Ok(Self {
+            sync:     Mutex::new(Box::new(sync)),
+            kv:       Mutex::new(Box::new(kv)),
+            settings: RwLock::new(settings),
+            started:  AtomicBool::new(false),
+        })
+    }
+
+    /// Porneşte SNTP – după ce Wi-Fi-ul are IP. A doua oară nu face nimic.
+    pub fn start(&self) -> Result<()> {
+        if self.started.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let servers = self.settings().servers;
+        info!("🕒 SNTP: {}", servers.join(", "));
+        self.sync.lock().unwrap().start(&servers).inspect_err(|_| self.started.store(false, Ordering::Relaxed))
+    }
+
+    pub fn settings(&self) -> Settings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// Salvează în NVS; servere noi → SNTP repornit (dacă rula).
+    pub fn set_settings(&self, new: Settings) -> Result<()> {
+        if Tz::parse(&new.tz).is_none() {
+            bail!("fus orar invalid: {:?} (ex. \"{DEFAULT_TZ}\")", new.tz);
+        }
+        if new.servers.is_empty() || new.servers.len() > MAX_SERVERS {
+            bail!("între 1 şi {MAX_SERVERS} servere NTP");
+        }
+        let host = |s: &str| {
+            !s.is_empty() && s.len() <= 64 && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        };
+        if let Some(bad) = new.servers.iter().find(|s| !host(s)) {
+            bail!("server NTP invalid: {bad:?}");
+        }
+        {
+            let mut kv = self.kv.lock().unwrap();
+            kv.set_str(KEY_SERVERS, &new.servers.join(","))?;
+            kv.set_str(KEY_TZ, &new.tz)?;
+        }
+        let restart = self.settings().servers != new.servers && self.started.load(Ordering::Relaxed);
+        apply_tz(&new.tz);
+        *self.settings.write().unwrap() = new;
+        if restart {
+            self.sync.lock().unwrap().start(&self.settings().servers)?;
+        }
+        Ok(())
+    }
+
+    pub fn state(&self) -> State {
+        let local = now();
+        State {
+            synced: synced(),
+            now: local.as_ref().map(LocalTime::iso8601),
+            local,
+            last_sync_s: last_sync().map(|d| d.as_secs()),
+            settings: self.settings(),
+        }
+    }
+}
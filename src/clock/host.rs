@@ -0,0 +1,126 @@
+//! Ora locală pe PC (simulator, teste): regulile şirului `TZ` aplicate
+//! de mână – `TZ`-ul procesului nu e al nostru. Pe ESP32 le aplică newlib.
+
+use std::sync::RwLock;
+
+use super::{Day, Dst, LocalTime, Tz};
+
+static TZ: RwLock<Option<Tz>> = RwLock::new(None);
+
+pub(super) fn apply_tz(tz: &str) {
+    *TZ.write().unwrap() = Tz::parse(tz);
+}
+
+/// Fără fus orar setat = UTC.
+pub(super) fn local(utc: i64) -> LocalTime {
+    match &*TZ.read().unwrap() {
+        Some(tz) => tz.local(utc),
+        None => Tz::utc().local(utc),
+    }
+}
+
+impl Tz {
+    pub fn utc() -> Self {
+        Self { abbr: "UTC".into(), offset: 0, dst: None }
+    }
+
+    /// Ora locală pentru secunde UNIX.
+    pub fn local(&self, utc: i64) -> LocalTime {
+        let (offset, abbr, dst) = match &self.dst {
+            Some(d) if self.in_dst(d, utc) => (d.offset, &d.abbr, true),
+            _ => (self.offset, &self.abbr, false),
+        };
+        let t = utc + offset as i64;
+        let days = t.div_euclid(86_400);
+        let secs = t.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        LocalTime {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            minute: (secs % 3600 / 60) as u8,
+            second: (secs % 60) as u8,
+            weekday: weekday(days),
+            offset_s: offset,
+            dst,
+            abbr: abbr.clone(),
+        }
+    }
+
+    fn in_dst(&self, dst: &Dst, utc: i64) -> bool {
+        let year = civil_from_days((utc + self.offset as i64).div_euclid(86_400)).0;
+        // începutul se dă în ora standard, sfârşitul în ora de vară
+        let start = rule_day(dst.start.day, year) * 86_400 + dst.start.time as i64 - self.offset as i64;
+        let end = rule_day(dst.end.day, year) * 86_400 + dst.end.time as i64 - dst.offset as i64;
+        if start < end {
+            (start..end).contains(&utc)
+        } else {
+            // emisfera sudică: vara trece peste Anul Nou
+            !(end..start).contains(&utc)
+        }
+    }
+}
+
+/* ------------ calendar ------------------------------------------------ */
+
+fn is_leap(y: i32) -> bool {
+    y % 4 == 0 && (y % 100 != 0 || y % 400 == 0)
+}
+
+fn days_in_month(y: i32, m: u8) -> u8 {
+    match m {
+        2 if is_leap(y) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Zile de la 1970-01-01 (algoritmul lui H. Hinnant).
+fn days_from_civil(y: i32, m: u8, d: u8) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y } as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = m as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(z: i64) -> (i32, u8, u8) {
+    let z = z + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let y = (yoe + era * 400) as i32 + (m <= 2) as i32;
+    (y, m, d)
+}
+
+/// 0 = duminică; 1970-01-01 a fost joi
+fn weekday(days: i64) -> u8 {
+    (days + 4).rem_euclid(7) as u8
+}
+
+fn rule_day(day: Day, year: i32) -> i64 {
+    match day {
+        Day::Month { month, week, weekday: wd } => {
+            let first = days_from_civil(year, month, 1);
+            let mut day = first + (wd as i64 - weekday(first) as i64).rem_euclid(7) + (week as i64 - 1) * 7;
+            // `5` = ultima din lună
+            while day >= first + days_in_month(year, month) as i64 {
+                day -= 7;
+            }
+            day
+        }
+        Day::Julian(n) => {
+            let leap = (is_leap(year) && n > 59) as i64;
+            days_from_civil(year, 1, 1) + n as i64 - 1 + leap
+        }
+        Day::Zero(n) => days_from_civil(year, 1, 1) + n as i64,
+    }
+}
@@ -0,0 +1,278 @@
+//! Erori tipizate pentru OpenAI / Azure TTS: ce a răspuns providerul,
+//! ce status dă API-ul nostru şi ce spune robotul în locul replicii.
+
+use anyhow::Error;
+use serde_json::{json, Value};
+use std::{fmt, io, time::Duration};
+
+use crate::hal::HttpResponse;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CloudError {
+    /// cheie greşită / expirată (401, 403)
+    Auth        { service: &'static str, message: String },
+    /// credit epuizat (`insufficient_quota`, „Out of call volume quota”)
+    Quota       { service: &'static str, message: String },
+    RateLimited { service: &'static str, retry_after: Option<Duration>, message: String },
+    /// cererea a fost respinsă (4xx) – mesajul providerului
+    BadRequest  { service: &'static str, status: u16, message: String },
+    /// 5xx la provider
+    Server      { service: &'static str, status: u16, retry_after: Option<Duration>, message: String },
+    /// DNS, TCP, TLS, conexiune ruptă
+    Network     { service: &'static str, detail: String },
+    Timeout     { service: &'static str },
+    /// 200, dar corpul nu e ce aşteptam
+    Malformed   { service: &'static str, detail: String },
+    /// circuit breaker deschis – nu s-a trimis nimic
+    Offline     { service: &'static str, retry_in: Duration },
+}
+
+use CloudError::*;
+
+impl CloudError {
+    /// Răspuns cu status ≠ 200; corpul e citit (max. 2 KB) pentru mesaj.
+    pub fn from_response(service: &'static str, resp: &mut dyn HttpResponse) -> Self {
+        let retry_after = resp
+            .header("Retry-After")
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs);
+        let status = resp.status();
+
+        let mut body = Vec::new();
+        let mut buf = [0u8; 256];
+        while body.len() < 2048 {
+            match resp.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => body.extend_from_slice(&buf[..n]),
+            }
+        }
+        Self::from_status(service, status, retry_after, &body)
+    }
+
+    /// OpenAI: `{"error":{"message","type","code"}}`; Azure: `{"error":{"code","message"}}`
+    /// sau corp gol / text.
+    pub fn from_status(
+        service: &'static str,
+        status: u16,
+        retry_after: Option<Duration>,
+        body: &[u8],
+    ) -> Self {
+        let v: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+        let err = &v["error"];
+        let code = err["code"].as_str().or(err["type"].as_str()).unwrap_or("");
+        let message = match err["message"].as_str() {
+            Some(m) => m.to_owned(),
+            None => {
+                let text = String::from_utf8_lossy(body).trim().to_owned();
+                if text.is_empty() { reason(status).to_owned() } else { text }
+            }
+        };
+        let quota = code == "insufficient_quota" || message.to_lowercase().contains("quota");
+
+        match status {
+            401           => Auth { service, message },
+            403 if quota  => Quota { service, message },
+            403           => Auth { service, message },
+            429 if quota  => Quota { service, message },
+            429           => RateLimited { service, retry_after, message },
+            408           => Timeout { service },
+            s if s >= 500 => Server { service, status: s, retry_after, message },
+            s             => BadRequest { service, status: s, message },
+        }
+    }
+
+    /// Eroare de transport / parsare → variantă; o `CloudError` trece neschimbată.
+    pub fn classify(service: &'static str, e: Error) -> Self {
+        let e = match e.downcast::<CloudError>() {
+            Ok(c)  => return c,
+            Err(e) => e,
+        };
+        #[cfg(target_os = "espidf")]
+        if let Some(code) = e.chain().find_map(esp_code) {
+            use esp_idf_svc::sys::{ESP_ERR_HTTP_EAGAIN, ESP_ERR_TIMEOUT};
+            return if code == ESP_ERR_HTTP_EAGAIN || code == ESP_ERR_TIMEOUT {
+                Timeout { service }
+            } else {
+                Network { service, detail: format!("{e:#}") }
+            };
+        }
+        if let Some(io) = e.chain().find_map(|c| c.downcast_ref::<io::Error>()) {
+            return match io.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Timeout { service },
+                _ => Network { service, detail: format!("{e:#}") },
+            };
+        }
+        Malformed { service, detail: format!("{e:#}") }
+    }
+
+    pub fn service(&self) -> &'static str {
+        match self {
+            Auth { service, .. } | Quota { service, .. } | RateLimited { service, .. }
+            | BadRequest { service, .. } | Server { service, .. } | Network { service, .. }
+            | Timeout { service } | Malformed { service, .. } | Offline { service, .. } => service,
+        }
+    }
+
+    /// identificator stabil pentru JSON-ul API-ului
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Auth { .. }        => "auth",
+            Quota { .. }       => "quota",
+            RateLimited { .. } => "rate_limited",
+            BadRequest { .. }  => "bad_request",
+            Server { .. }      => "upstream",
+            Network { .. }     => "network",
+            Timeout { .. }     => "timeout",
+            Malformed { .. }   => "malformed_response",
+            Offline { .. }     => "offline",
+        }
+    }
+
+    /// aşteptare cerută de provider (sau rămasă până se închide circuitul)
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            RateLimited { retry_after, .. } | Server { retry_after, .. } => *retry_after,
+            Offline { retry_in, .. } => Some(*retry_in),
+            _ => None,
+        }
+    }
+
+    /// merită reîncercat (la nivel de `retry`)
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RateLimited { .. } | Network { .. } | Timeout { .. } => true,
+            Server { status, .. } => matches!(status, 500 | 502 | 503 | 504),
+            _ => false,
+        }
+    }
+
+    /// statusul cu care răspunde API-ul robotului
+    pub fn http_status(&self) -> u16 {
+        match self {
+            RateLimited { .. }           => 429,
+            BadRequest { .. }            => 422,
+            Quota { .. } | Offline { .. } => 503,
+            Timeout { .. }               => 504,
+            Auth { .. } | Server { .. } | Network { .. } | Malformed { .. } => 502,
+        }
+    }
+
+    /// `{"error":{"kind","service","message","retry_after"}}`
+    pub fn to_json(&self) -> Value {
+        json!({ "error": {
+            "kind":        self.kind(),
+            "service":     self.service(),
+            "message":     self.to_string(),
+            "retry_after": self.retry_after().map(|d| d.as_secs().max(1)),
+        }})
+    }
+
+    /// Ce spune robotul în locul replicii. `lang`: cod ISO („ro”, „en”, „ru”).
+    pub fn spoken(&self, lang: &str) -> &'static str {
+        let i = match lang {
+            "en" => 1,
+            "ru" => 2,
+            _    => 0,
+        };
+        let msgs: [&str; 3] = match self {
+            Auth { .. } => [
+                "Nu mă pot autentifica la serviciul din cloud. Verifică cheia API.",
+                "I can't authenticate with the cloud service. Please check the API key.",
+                "Не могу авторизоваться в облачном сервисе. Проверьте ключ API.",
+            ],
+            Quota { .. } => [
+                "S-a terminat creditul la serviciul din cloud.",
+                "The cloud service quota has been used up.",
+                "Закончилась квота облачного сервиса.",
+            ],
+            RateLimited { .. } => [
+                "Sunt prea multe cereri acum. Mai încearcă peste câteva secunde.",
+                "Too many requests right now. Please try again in a few seconds.",
+                "Слишком много запросов. Попробуйте через несколько секунд.",
+            ],
+            BadRequest { .. } => [
+                "Nu am putut procesa cererea. Mai spune o dată, te rog.",
+                "I couldn't process that request. Please say it again.",
+                "Не удалось обработать запрос. Повторите, пожалуйста.",
+            ],
+            Timeout { .. } => [
+                "Serviciul din cloud răspunde prea încet.",
+                "The cloud service is taking too long to answer.",
+                "Облачный сервис отвечает слишком долго.",
+            ],
+            Server { .. } | Malformed { .. } => [
+                "Serviciul din cloud are probleme acum.",
+                "The cloud service is having problems right now.",
+                "У облачного сервиса сейчас проблемы.",
+            ],
+            Network { .. } | Offline { .. } => [
+                "Nu am conexiune la internet.",
+                "I have no internet connection.",
+                "Нет подключения к интернету.",
+            ],
+        };
+        msgs[i]
+    }
+}
+
+impl fmt::Display for CloudError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Auth { service, message } | Quota { service, message } => {
+                write!(f, "{service} ({}): {message}", self.kind())
+            }
+            RateLimited { service, message, .. } => write!(f, "{service} HTTP 429: {message}"),
+            BadRequest { service, status, message } | Server { service, status, message, .. } => {
+                write!(f, "{service} HTTP {status}: {message}")
+            }
+            Network { service, detail }   => write!(f, "{service}: eroare de reţea – {detail}"),
+            Timeout { service }           => write!(f, "{service}: timeout"),
+            Malformed { service, detail } => write!(f, "{service}: răspuns invalid – {detail}"),
+            Offline { service, retry_in } => {
+                write!(f, "{service} offline – reîncerc în {} s", retry_in.as_secs().max(1))
+            }
+        }
+    }
+}
+
+impl std::error::Error for CloudError {}
+
+/// primul cod suportat din `Accept-Language` („ro-RO,en;q=0.8” → „ro”)
+pub fn lang_from(accept_language: Option<&str>) -> &'static str {
+    accept_language
+        .unwrap_or("")
+        .split(',')
+        .map(|l| l.split(';').next().unwrap_or("").trim())
+        .find_map(|l| match l.get(..2).map(|p| p.to_ascii_lowercase()).as_deref() {
+            Some("ro") => Some("ro"),
+            Some("en") => Some("en"),
+            Some("ru") => Some("ru"),
+            _          => None,
+        })
+        .unwrap_or("ro")
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _   => "eroare",
+    }
+}
+
+#[cfg(target_os = "espidf")]
+fn esp_code(e: &(dyn std::error::Error + 'static)) -> Option<i32> {
+    use esp_idf_svc::{io::EspIOError, sys::EspError};
+    e.downcast_ref::<EspIOError>()
+        .map(|e| e.0.code())
+        .or_else(|| e.downcast_ref::<EspError>().map(|e| e.code()))
+}
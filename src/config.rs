@@ -0,0 +1,575 @@
+//! Setările robotului într-un singur loc: schemă tipizată şi versionată,
+//! valorile din firmware ca implicite, validare, un document JSON în NVS
+//! şi notificări pentru subsistemele care ţin stare.
+//!
+//! Cine citeşte o setare la fiecare folosire (cheile, URL-urile, vocea)
+//! apelează `config::get()`; cine o păstrează (conversaţia, reţeaua de
+//! pornire) se abonează cu `Store::subscribe`. Pinii se aplică doar la
+//! pornire.
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, RwLock,
+};
+
+use crate::hal::KeyValueStore;
+use crate::openai::SYSTEM_PROMPT;
+
+const KEY: &str = "config";
+
+/// versiunea schemei – creşte la orice schimbare care cere un pas în `migrate`
+pub const SCHEMA: u32 = 1;
+/// ce apare în locul cheilor şi parolelor; trimis înapoi = neschimbat
+pub const MASK: &str = "********";
+/// documentul JSON, în NVS şi în `PATCH /api/config`
+pub const MAX_JSON: usize = 4096;
+/// câmpurile mascate / criptate în copiile de siguranţă, în ordinea din `secrets_mut`
+pub const SECRETS: [&str; 3] = ["wifi.pass", "openai.key", "azure.key"];
+/// obiecte cu chei libere – un patch le poate adăuga chei noi
+const OPEN_MAPS: [&str; 1] = ["azure.voices"];
+/// GPIO-urile ESP32 care pot fi ieşiri: 1/3 sunt UART0 (consola), 6–11
+/// flash-ul SPI, 34–39 doar intrări, restul nu există
+const OUTPUT_GPIOS: [u8; 20] = [0, 2, 4, 5, 12, 13, 14, 15, 16, 17, 18, 19, 21, 22, 23, 25, 26, 27, 32, 33];
+/// pini de strapping: citiţi la reset, un motor sau un amplificator pe ei
+/// poate schimba modul de boot sau tensiunea flash-ului
+const STRAPPING_GPIOS: [u8; 5] = [0, 2, 5, 12, 15];
+
+/* ------------ valorile din firmware ----------------------------------- */
+
+/// Nimic secret în sursă: reţeaua de la prima pornire şi cheile vin din
+/// mediul build-ului (`WIFI_SSID`, `WIFI_PASS`, `OPENAI_API_KEY`,
+/// `AZURE_SPEECH_KEY`); lipsă → goale, de completat din pagina de setări.
+/// Reţeaua contează doar la prima pornire; apoi lista din NVS (`/api/wifi`).
+const STA_SSID: &str = env_or_empty(option_env!("WIFI_SSID"));
+const STA_PASS: &str = env_or_empty(option_env!("WIFI_PASS"));
+const OPENAI_KEY: &str = env_or_empty(option_env!("OPENAI_API_KEY"));
+const AZURE_KEY: &str = env_or_empty(option_env!("AZURE_SPEECH_KEY"));
+
+const fn env_or_empty(var: Option<&'static str>) -> &'static str {
+    match var {
+        Some(v) => v,
+        None    => "",
+    }
+}
+
+/* ------------ schema -------------------------------------------------- */
+
+/// Câmpurile lipsă dintr-un document (mai vechi sau scris de mână) iau
+/// valoarea implicită.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub version: u32,
+    pub wifi:    Wifi,
+    pub openai:  OpenAi,
+    pub azure:   Azure,
+    pub pins:    Pins,
+}
+
+/// Reţeaua adăugată în lista managerului Wi-Fi.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Wifi {
+    pub ssid: String,
+    pub pass: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAi {
+    pub key:           String,
+    /// fără `/v1/…` – alt server compatibil sau un proxy
+    pub url:           String,
+    pub chat_model:    String,
+    pub whisper_model: String,
+    /// limba transcrierii (ISO 639-1)
+    pub language:      String,
+    pub system_prompt: String,
+    /// replici păstrate în context
+    pub turns:         u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Azure {
+    pub key:    String,
+    pub region: String,
+    /// vocea replicilor în limba lui `locale`
+    pub voice:  String,
+    /// `xml:lang` al SSML-ului
+    pub locale: String,
+    /// `"+10%"`, `"slow"`, `"1.2"`; gol = viteza vocii
+    pub rate:   String,
+    /// `"-5%"`, `"+2st"`, `"high"`; gol = tonul vocii
+    pub pitch:  String,
+    /// `mstts:express-as`, ex. `"cheerful"` (doar la vocile care îl au); gol = neutru
+    pub style:  String,
+    /// limba detectată a replicii (ISO 639-1) → vocea; lipsă = `voice`
+    pub voices: BTreeMap<String, String>,
+    /// amplificarea PCM-ului primit
+    pub gain:   f32,
+}
+
+/// GPIO-uri; se aplică la repornire.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pins {
+    pub i2s_bclk:    u8,
+    pub i2s_ws:      u8,
+    pub i2s_dout:    u8,
+    /// L9110S: `[A, B]` pentru fiecare motor
+    pub motor_left:  [u8; 2],
+    pub motor_right: [u8; 2],
+    pub servo_left:  u8,
+    pub servo_right: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA,
+            wifi:    Wifi::default(),
+            openai:  OpenAi::default(),
+            azure:   Azure::default(),
+            pins:    Pins::default(),
+        }
+    }
+}
+
+impl Default for Wifi {
+    fn default() -> Self {
+        Self { ssid: STA_SSID.into(), pass: STA_PASS.into() }
+    }
+}
+
+impl Default for OpenAi {
+    fn default() -> Self {
+        Self {
+            key:           OPENAI_KEY.into(),
+            url:           "https://api.openai.com".into(),
+            chat_model:    "gpt-3.5-turbo".into(),
+            whisper_model: "whisper-1".into(),
+            language:      "ro".into(),
+            system_prompt: SYSTEM_PROMPT.into(),
+            turns:         6,
+        }
+    }
+}
+
+impl Default for Azure {
+    fn default() -> Self {
+        Self {
+            key:    AZURE_KEY.into(),
+            region: "eastus".into(),
+            voice:  "ro-RO-AlinaNeural".into(),
+            locale: "ro-RO".into(),
+            rate:   String::new(),
+            pitch:  String::new(),
+            style:  String::new(),
+            voices: [
+                ("de", "de-DE-KatjaNeural"),
+                ("en", "en-US-JennyNeural"),
+                ("es", "es-ES-ElviraNeural"),
+                ("fr", "fr-FR-DeniseNeural"),
+                ("it", "it-IT-ElsaNeural"),
+                ("ru", "ru-RU-SvetlanaNeural"),
+            ]
+            .into_iter()
+            .map(|(lang, voice)| (lang.into(), voice.into()))
+            .collect(),
+            gain:   2.0,
+        }
+    }
+}
+
+impl Default for Pins {
+    fn default() -> Self {
+        Self {
+            i2s_bclk:    27,
+            i2s_ws:      25,
+            i2s_dout:    26,
+            motor_left:  [18, 19],
+            motor_right: [21, 22],
+            servo_left:  16,
+            servo_right: 17,
+        }
+    }
+}
+
+impl Azure {
+    /// Totul ajunge în atribute SSML; folosită şi pentru alegerile unei
+    /// singure cereri (`azure_tts::VoiceOptions`).
+    pub fn validate(&self) -> Result<()> {
+        if !token(&self.region, 32) || self.region.contains(['.', '-']) {
+            bail!("azure.region: regiune invalidă: {:?} (ex. \"westeurope\")", self.region);
+        }
+        if !token(&self.voice, 64) {
+            bail!("azure.voice: voce invalidă: {:?} (ex. \"ro-RO-AlinaNeural\")", self.voice);
+        }
+        if !locale(&self.locale) {
+            bail!("azure.locale: invalid: {:?} (ex. \"ro-RO\")", self.locale);
+        }
+        const RATES: [&str; 6] = ["x-slow", "slow", "medium", "fast", "x-fast", "default"];
+        if !prosody(&self.rate, &RATES, &["%", ""]) {
+            bail!("azure.rate: {:?} – ex. \"+10%\", \"1.2\" sau \"slow\"", self.rate);
+        }
+        const PITCHES: [&str; 6] = ["x-low", "low", "medium", "high", "x-high", "default"];
+        if !prosody(&self.pitch, &PITCHES, &["%", "Hz", "st"]) {
+            bail!("azure.pitch: {:?} – ex. \"-5%\", \"+2st\" sau \"high\"", self.pitch);
+        }
+        if !self.style.is_empty() && !token(&self.style, 32) {
+            bail!("azure.style: stil invalid: {:?} (ex. \"cheerful\")", self.style);
+        }
+        if self.voices.len() > 16 {
+            bail!("azure.voices: cel mult 16 limbi");
+        }
+        for (lang, voice) in &self.voices {
+            if lang.len() != 2 || !lang.bytes().all(|b| b.is_ascii_lowercase()) {
+                bail!("azure.voices: {lang:?} nu e un cod ISO 639-1 (ex. \"en\")");
+            }
+            if !token(voice, 64) {
+                bail!("azure.voices.{lang}: voce invalidă: {voice:?}");
+            }
+        }
+        if !(0.1..=8.0).contains(&self.gain) {
+            bail!("azure.gain: între 0.1 şi 8");
+        }
+        Ok(())
+    }
+}
+
+/// Partea din configuraţie care s-a schimbat – ce primesc abonaţii.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Section {
+    Wifi,
+    Openai,
+    Azure,
+    Pins,
+}
+
+impl Section {
+    pub fn needs_restart(self) -> bool {
+        self == Section::Pins
+    }
+}
+
+impl Config {
+    pub fn validate(&self) -> Result<()> {
+        let w = &self.wifi;
+        if w.ssid.len() > 32 {
+            bail!("wifi.ssid: cel mult 32 de octeţi");
+        }
+        if !w.pass.is_empty() && !(8..=63).contains(&w.pass.len()) {
+            bail!("wifi.pass: între 8 şi 63 de caractere (sau goală pentru reţea deschisă)");
+        }
+
+        let o = &self.openai;
+        if !o.url.starts_with("https://") {
+            bail!("openai.url: trebuie să înceapă cu https://");
+        }
+        for (name, model) in [("openai.chat_model", &o.chat_model), ("openai.whisper_model", &o.whisper_model)] {
+            if !token(model, 64) {
+                bail!("{name}: nume de model invalid: {model:?}");
+            }
+        }
+        if o.language.len() != 2 || !o.language.bytes().all(|b| b.is_ascii_lowercase()) {
+            bail!("openai.language: cod ISO 639-1 din două litere (ex. \"ro\")");
+        }
+        if o.system_prompt.chars().count() > 1000 {
+            bail!("openai.system_prompt: cel mult 1000 de caractere");
+        }
+        if o.turns > 20 {
+            bail!("openai.turns: cel mult 20");
+        }
+
+        self.azure.validate()?;
+
+        let p = &self.pins;
+        let all = [
+            ("i2s_bclk", p.i2s_bclk), ("i2s_ws", p.i2s_ws), ("i2s_dout", p.i2s_dout),
+            ("motor_left", p.motor_left[0]), ("motor_left", p.motor_left[1]),
+            ("motor_right", p.motor_right[0]), ("motor_right", p.motor_right[1]),
+            ("servo_left", p.servo_left), ("servo_right", p.servo_right),
+        ];
+        for (i, &(name, gpio)) in all.iter().enumerate() {
+            if !OUTPUT_GPIOS.contains(&gpio) {
+                bail!("pins.{name}: GPIO{gpio} nu poate fi ieşire");
+            }
+            if let Some((other, _)) = all[..i].iter().find(|(_, g)| *g == gpio) {
+                bail!("pins.{name}: GPIO{gpio} e deja folosit de pins.{other}");
+            }
+            if STRAPPING_GPIOS.contains(&gpio) {
+                warn!("⚠️ pins.{name}: GPIO{gpio} e pin de strapping – poate bloca pornirea");
+            }
+        }
+        Ok(())
+    }
+
+    /// Secţiunile care diferă.
+    pub fn changed(&self, new: &Config) -> Vec<Section> {
+        let mut out = Vec::new();
+        if self.wifi != new.wifi {
+            out.push(Section::Wifi);
+        }
+        if self.openai != new.openai {
+            out.push(Section::Openai);
+        }
+        if self.azure != new.azure {
+            out.push(Section::Azure);
+        }
+        if self.pins != new.pins {
+            out.push(Section::Pins);
+        }
+        out
+    }
+
+    /// Copia pentru `GET /api/config`: cheile şi parolele setate → `MASK`.
+    pub fn masked(&self) -> Config {
+        let mut out = self.clone();
+        for secret in out.secrets_mut() {
+            if !secret.is_empty() {
+                *secret = MASK.into();
+            }
+        }
+        out
+    }
+
+    /// Cheile şi parolele, în ordinea din `SECRETS`.
+    pub fn secrets_mut(&mut self) -> [&mut String; 3] {
+        [&mut self.wifi.pass, &mut self.openai.key, &mut self.azure.key]
+    }
+
+    /// JSON Merge Patch (RFC 7396): cheile lipsă rămân, `null` = valoarea
+    /// implicită, `MASK` = secretul vechi. Cheile necunoscute sunt erori,
+    /// ca o greşeală de tipar să nu fie ignorată în tăcere.
+    pub fn patched(&self, patch: &Value) -> Result<Config> {
+        if !patch.is_object() {
+            bail!("se aşteaptă un obiect JSON");
+        }
+        let mut doc = serde_json::to_value(self)?;
+        check_keys(&doc, patch, "")?;
+        merge(&mut doc, patch);
+        doc["version"] = SCHEMA.into();
+
+        let mut new: Config = serde_json::from_value(doc).map_err(|e| anyhow!("{e}"))?;
+        let mut old = self.clone();
+        for (secret, old) in new.secrets_mut().into_iter().zip(old.secrets_mut()) {
+            if secret == MASK {
+                *secret = std::mem::take(old);
+            }
+        }
+        new.validate()?;
+        Ok(new)
+    }
+}
+
+/// Identificator fără spaţii: litere, cifre, `-`, `_`, `.`.
+fn token(s: &str, max: usize) -> bool {
+    !s.is_empty() && s.len() <= max && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b))
+}
+
+/// `ro-RO`, `en-US`, `zh-Hans-CN`
+fn locale(s: &str) -> bool {
+    let mut parts = s.split('-');
+    let lang = parts.next().unwrap_or("");
+    let rest: Vec<&str> = parts.collect();
+    (2..=3).contains(&lang.len())
+        && lang.bytes().all(|b| b.is_ascii_lowercase())
+        && !rest.is_empty()
+        && rest.iter().all(|p| (2..=4).contains(&p.len()) && p.bytes().all(|b| b.is_ascii_alphanumeric()))
+}
+
+/// Valoare `<prosody>`: gol, un cuvânt din `words` sau un număr (cu semn)
+/// urmat de una din `units` (`""` = fără unitate).
+fn prosody(s: &str, words: &[&str], units: &[&str]) -> bool {
+    if s.is_empty() || words.contains(&s) {
+        return true;
+    }
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    let end = digits.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(digits.len());
+    let (number, unit) = digits.split_at(end);
+    number.len() <= 6 && number.parse::<f32>().is_ok() && units.contains(&unit)
+}
+
+/// Orice cheie din `patch` trebuie să existe în `doc` (în afară de `OPEN_MAPS`).
+fn check_keys(doc: &Value, patch: &Value, path: &str) -> Result<()> {
+    let (Value::Object(doc), Value::Object(patch)) = (doc, patch) else { return Ok(()) };
+    if OPEN_MAPS.contains(&path) {
+        return Ok(());
+    }
+    for (key, value) in patch {
+        let full = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+        match doc.get(key) {
+            Some(inner) => check_keys(inner, value, &full)?,
+            None => bail!("cheie necunoscută: {full}"),
+        }
+    }
+    Ok(())
+}
+
+fn merge(doc: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *doc = patch.clone();
+        return;
+    };
+    if !doc.is_object() {
+        *doc = Value::Object(Default::default());
+    }
+    let Value::Object(doc) = doc else { unreachable!() };
+    for (key, value) in patch {
+        if value.is_null() {
+            doc.remove(key);
+        } else {
+            merge(doc.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Aduce documentul la `SCHEMA`; întoarce versiunea găsită. Unul mai nou
+/// (firmware întors prin rollback) rămâne cum e – câmpurile necunoscute
+/// se ignoră la citire.
+pub fn migrate(doc: &mut Value) -> Result<u32> {
+    let Value::Object(map) = doc else { bail!("configuraţia nu e un obiect JSON") };
+    let found = match map.get("version") {
+        None => 0,
+        Some(v) => v.as_u64().and_then(|v| u32::try_from(v).ok()).context("\"version\" invalid")?,
+    };
+    for from in found..SCHEMA {
+        match from {
+            // fără `version` (scris de mână): aceeaşi formă ca v1
+            0 => {}
+            _ => unreachable!("lipseşte pasul de migrare {from} → {}", from + 1),
+        }
+        map.insert("version".into(), (from + 1).into());
+    }
+    Ok(found)
+}
+
+/* ------------ configuraţia curentă (ca `retry::OPENAI`) --------------- */
+
+static CURRENT: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+
+/// Configuraţia încărcată de `Store`; valorile din firmware până atunci
+/// (şi în testele care nu pornesc un `Store`).
+pub fn get() -> Arc<Config> {
+    CURRENT.read().unwrap().clone().unwrap_or_default()
+}
+
+type Listener = Box<dyn Fn(&Config, &[Section]) + Send>;
+
+pub struct Store {
+    kv:        Mutex<Box<dyn KeyValueStore + Send>>,
+    current:   RwLock<Arc<Config>>,
+    listeners: Mutex<Vec<Listener>>,
+    /// pini schimbaţi de la pornire
+    restart:   AtomicBool,
+}
+
+impl Store {
+    /// Documentul din NVS, adus la schema curentă; unul stricat sau
+    /// invalid → valorile din firmware (documentul rămâne până la prima
+    /// salvare).
+    pub fn load(mut kv: impl KeyValueStore + Send + 'static) -> Result<Self> {
+        let config = match kv.get(KEY)? {
+            None => Config::default(),
+            Some(raw) => match parse(&raw) {
+                Ok((config, found)) if found < SCHEMA => {
+                    info!("⚙️ configuraţia v{found} → v{SCHEMA}");
+                    kv.set(KEY, &serde_json::to_vec(&config)?)?;
+                    config
+                }
+                Ok((config, found)) => {
+                    if found > SCHEMA {
+                        warn!("⚙️ configuraţie v{found} de la un firmware mai nou – citesc doar ce cunosc");
+                    }
+                    config
+                }
+                Err(e) => {
+                    warn!("⚙️ configuraţia din NVS: {e:#} – folosesc valorile din firmware");
+                    Config::default()
+                }
+            },
+        };
+        for (name, key) in [("openai.key", &config.openai.key), ("azure.key", &config.azure.key)] {
+            if key.is_empty() {
+                warn!("⚙️ {name} lipseşte – completaţi-o în settings.html");
+            }
+        }
+        let config = Arc::new(config);
+        *CURRENT.write().unwrap() = Some(config.clone());
+        Ok(Self {
+            kv:        Mutex::new(Box::new(kv)),
+            current:   RwLock::new(config),
+            listeners: Mutex::new(Vec::new()),
+            restart:   AtomicBool::new(false),
+        })
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Au fost schimbaţi pini de la pornire.
+    pub fn restart_pending(&self) -> bool {
+        self.restart.load(Ordering::Relaxed)
+    }
+
+    /// `f` primeşte configuraţia nouă şi ce s-a schimbat, pe thread-ul
+    /// care a salvat-o. Nu are voie să salveze la rândul lui.
+    pub fn subscribe(&self, f: impl Fn(&Config, &[Section]) + Send + 'static) {
+        self.listeners.lock().unwrap().push(Box::new(f));
+    }
+
+    /// Validează, salvează şi anunţă; întoarce secţiunile schimbate.
+    pub fn set(&self, new: Config) -> Result<Vec<Section>> {
+        self.update(|_| Ok(new))
+    }
+
+    /// `PATCH /api/config` – vezi `Config::patched`.
+    pub fn patch(&self, patch: &Value) -> Result<Vec<Section>> {
+        self.update(|old| old.patched(patch))
+    }
+
+    fn update(&self, f: impl FnOnce(&Config) -> Result<Config>) -> Result<Vec<Section>> {
+        let (config, changed) = {
+            let mut kv = self.kv.lock().unwrap();
+            let old = self.get();
+            let mut new = f(&old)?;
+            new.version = SCHEMA;
+            new.validate()?;
+            let changed = old.changed(&new);
+            if changed.is_empty() {
+                return Ok(changed);
+            }
+            kv.set(KEY, &serde_json::to_vec(&new)?)?;
+            let new = Arc::new(new);
+            *self.current.write().unwrap() = new.clone();
+            *CURRENT.write().unwrap() = Some(new.clone());
+            (new, changed)
+        };
+        if changed.iter().any(|s| s.needs_restart()) {
+            self.restart.store(true, Ordering::Relaxed);
+            warn!("⚙️ pini noi – se aplică după repornire");
+        }
+        info!("⚙️ configuraţie salvată: {changed:?}");
+        for f in self.listeners.lock().unwrap().iter() {
+            f(&config, &changed);
+        }
+        Ok(changed)
+    }
+}
+
+fn parse(raw: &[u8]) -> Result<(Config, u32)> {
+    let mut doc: Value = serde_json::from_slice(raw)?;
+    let found = migrate(&mut doc)?;
+    let mut config: Config = serde_json::from_value(doc)?;
+    config.version = SCHEMA;
+    config.validate()?;
+    Ok((config, found))
+}
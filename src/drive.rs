@@ -0,0 +1,147 @@
+//! Mers continuu (joystick): ţinta vine prin `/ws` sau `POST /api/drive`,
+//! motoarele urcă spre ea cu acceleraţie limitată, iar fără heartbeat
+//! robotul se opreşte.
+
+use anyhow::Result;
+use log::warn;
+use std::{
+    sync::{Arc, Mutex, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::motion::{self, Motion};
+use crate::ws::{Event, Hub};
+
+/// Clientul trimite comenzi (sau `ping`) cel puţin la 5 Hz; mai lasă
+/// 50 ms pentru jitter-ul Wi-Fi-ului.
+pub const DEADMAN: Duration = Duration::from_millis(250);
+
+/// „sesiunea” comenzilor venite prin HTTP (cele WebSocket sunt ≥ 0)
+pub const HTTP: i32 = -1;
+
+const TICK: Duration = Duration::from_millis(20);
+/// % pe tick: 0 → 100 % în ~250 ms
+const RAMP_STEP: i16 = 8;
+
+struct State {
+    target: (i8, i8),
+    /// cine trebuie să trimită heartbeat; `None` = oprire în curs/oprit
+    owner:  Option<i32>,
+    last:   Instant,
+    /// bucla de rampă atinge motoarele doar cât timp e activă
+    /// (`/move/*` şi gesturile le folosesc direct)
+    active: bool,
+}
+
+pub struct Drive {
+    motion: Arc<Mutex<Motion>>,
+    hub:    Arc<Hub>,
+    state:  Mutex<State>,
+}
+
+impl Drive {
+    /// Porneşte bucla de rampă; se opreşte singură când `Drive` dispare.
+    pub fn start(motion: Arc<Mutex<Motion>>, hub: Arc<Hub>) -> Result<Arc<Self>> {
+        let drive = Arc::new(Self {
+            motion,
+            hub,
+            state: Mutex::new(State {
+                target: (0, 0),
+                owner:  None,
+                last:   Instant::now(),
+                active: false,
+            }),
+        });
+
+        let weak: Weak<Self> = Arc::downgrade(&drive);
+        thread::Builder::new()
+            .name("drive".into())
+            .stack_size(4 * 1024)
+            .spawn(move || {
+                crate::status::watch_stack("drive");
+                while let Some(drive) = weak.upgrade() {
+                    if let Err(e) = drive.tick() {
+                        warn!("drive: {e:?}");
+                    }
+                    drop(drive);
+                    thread::sleep(TICK);
+                }
+            })?;
+
+        Ok(drive)
+    }
+
+    /// linear/angular ∈ [-1, 1]; contează şi ca heartbeat.
+    pub fn set(&self, owner: i32, linear: f32, angular: f32) -> (i8, i8) {
+        let target = motion::mix(linear, angular);
+        let mut st = self.state.lock().unwrap();
+        st.target = target;
+        st.owner = (target != (0, 0)).then_some(owner);
+        st.last = Instant::now();
+        st.active = true;
+        target
+    }
+
+    pub fn heartbeat(&self, owner: i32) {
+        let mut st = self.state.lock().unwrap();
+        if st.owner == Some(owner) {
+            st.last = Instant::now();
+        }
+    }
+
+    /// Oprire imediată, fără rampă.
+    pub fn stop(&self) -> Result<()> {
+        let mut st = self.state.lock().unwrap();
+        st.target = (0, 0);
+        st.owner = None;
+        st.active = false;
+        self.apply((0, 0), true)
+    }
+
+    /// Clientul `owner` a dispărut – dacă el conducea, oprim.
+    pub fn release(&self, owner: i32) -> Result<()> {
+        if self.state.lock().unwrap().owner == Some(owner) {
+            warn!("🛑 drive: clientul {owner} s-a deconectat – opresc motoarele");
+            return self.stop();
+        }
+        Ok(())
+    }
+
+    fn tick(&self) -> Result<()> {
+        let mut st = self.state.lock().unwrap();
+        if st.owner.is_some() && st.last.elapsed() > DEADMAN {
+            warn!("🛑 drive: fără heartbeat de {DEADMAN:?} – opresc motoarele");
+            drop(st);
+            return self.stop();
+        }
+        if !st.active {
+            return Ok(());
+        }
+
+        let current = self.motion.lock().unwrap().speeds();
+        let next = (ramp(current.0, st.target.0), ramp(current.1, st.target.1));
+        if next == st.target && st.owner.is_none() {
+            st.active = false;
+        }
+        self.apply(next, next == st.target)
+    }
+
+    /// Evenimentul `motors` pleacă doar când rampa a ajuns la ţintă.
+    fn apply(&self, speeds: (i8, i8), settled: bool) -> Result<()> {
+        let mut motion = self.motion.lock().unwrap();
+        if motion.speeds() == speeds {
+            return Ok(());
+        }
+        motion.drive(speeds.0, speeds.1)?;
+        if settled {
+            self.hub.publish(&Event::Motors { left: speeds.0, right: speeds.1 });
+        }
+        Ok(())
+    }
+}
+
+fn ramp(current: i8, target: i8) -> i8 {
+    let (c, t) = (current as i16, target as i16);
+    (c + (t - c).clamp(-RAMP_STEP, RAMP_STEP)) as i8
+}
@@ -0,0 +1,39 @@
+//! Sunete scurte fără TTS – se aud şi când cloud-ul nu răspunde.
+
+use anyhow::Result;
+
+use crate::hal::AudioSink;
+
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// (Hz, ms); 0 Hz = pauză
+const OFFLINE: &[(f32, u32)] = &[(880.0, 120), (0.0, 60), (660.0, 120), (0.0, 60), (440.0, 250)];
+
+fn render(notes: &[(f32, u32)]) -> Vec<u8> {
+    let mut pcm = Vec::new();
+    for &(hz, ms) in notes {
+        let n = SAMPLE_RATE * ms / 1000;
+        for i in 0..n {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            // fade 5 ms la capete – fără „click”
+            let edge = (i.min(n - i) as f32 / (SAMPLE_RATE as f32 * 0.005)).min(1.0);
+            let s = if hz == 0.0 {
+                0
+            } else {
+                ((t * hz * std::f32::consts::TAU).sin() * 8000.0 * edge) as i16
+            };
+            pcm.extend_from_slice(&s.to_le_bytes());
+        }
+    }
+    pcm
+}
+
+/// trei tonuri descendente – „nu am internet / cloud-ul e căzut”
+pub fn offline() -> Vec<u8> {
+    render(OFFLINE)
+}
+
+pub fn play_offline(sink: &mut dyn AudioSink) -> Result<()> {
+    sink.write(&offline())?;
+    sink.flush()
+}
@@ -0,0 +1,282 @@
+//! Abstracţii hardware – logica robotului nu vede direct `esp-idf-hal`.
+//!
+//! Pe ESP32 (`target_os = "espidf"`) trăsăturile sunt implementate de
+//! driverele reale (LEDC, I²S, NVS, `EspHttpConnection`); pe PC avem
+//! variante „de bancă” (înregistrare în memorie, fişier WAV, răspunsuri
+//! HTTP scriptate), ca aplicaţia să ruleze şi sub `cargo test`.
+
+use anyhow::Result;
+use embedded_svc::http::Method;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::Ipv4Addr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+pub use embedded_svc::http::server::{Connection, Request};
+
+use crate::motors::MotorId;
+use crate::servo::ServoId;
+
+#[cfg(target_os = "espidf")]
+mod esp;
+#[cfg(target_os = "espidf")]
+pub use esp::*;
+
+#[cfg(not(target_os = "espidf"))]
+mod host;
+#[cfg(not(target_os = "espidf"))]
+pub use host::*;
+
+#[cfg(not(target_os = "espidf"))]
+mod host_net;
+#[cfg(not(target_os = "espidf"))]
+pub use host_net::*;
+
+/* ------------ motoare / servo --------------------------------------- */
+
+pub trait MotorDriver {
+    /// speed ∈ [-100, 100] (%)
+    fn drive(&mut self, id: MotorId, speed: i8) -> Result<()>;
+}
+
+pub trait ServoBank {
+    /// unghi ∈ [0, 180]°
+    fn set_angle(&mut self, id: ServoId, deg: f32) -> Result<()>;
+}
+
+/* ------------ audio -------------------------------------------------- */
+
+/// Destinaţia PCM-ului redat (16 kHz, 16 bit, mono, little-endian).
+pub trait AudioSink {
+    fn write(&mut self, pcm: &[u8]) -> Result<()>;
+
+    /// apelat la sfârşitul unei replici
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/* ------------ HTTP client ------------------------------------------- */
+
+pub trait HttpResponse {
+    fn status(&self) -> u16;
+    fn header(&self, name: &str) -> Option<&str>;
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    fn read_to_end(&mut self) -> Result<Vec<u8>> {
+        let mut out = Vec::<u8>::new();
+        let mut buf = [0u8; 512];
+        loop {
+            let n = self.read(&mut buf)?;
+            if n == 0 { break; }
+            out.extend_from_slice(&buf[..n]);
+        }
+        Ok(out)
+    }
+
+    /// Cel mult `limit` octeţi din corp – restul nu mai e citit, deci nici
+    /// ţinut în RAM.
+    fn read_up_to(&mut self, limit: usize) -> Result<Vec<u8>> {
+        let mut out = Vec::<u8>::new();
+        let mut buf = [0u8; 512];
+        while out.len() < limit {
+            let want = (limit - out.len()).min(buf.len());
+            let n = self.read(&mut buf[..want])?;
+            if n == 0 { break; }
+            out.extend_from_slice(&buf[..n]);
+        }
+        Ok(out)
+    }
+}
+
+/// Cererea n-a ajuns la server (DNS, conectare, TLS) – se poate relua fără
+/// grijă, chiar şi un POST care costă.
+#[derive(Debug)]
+pub struct NotSent(pub anyhow::Error);
+
+impl std::fmt::Display for NotSent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for NotSent {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+pub trait HttpClient {
+    /// Trimite cererea cu corpul complet; `Content-Length` se adaugă automat.
+    /// Erorile dinainte să plece ceva vin ca [`NotSent`].
+    fn request(
+        &mut self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<Box<dyn HttpResponse + '_>>;
+
+    fn post(
+        &mut self,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<Box<dyn HttpResponse + '_>> {
+        self.request(Method::Post, url, headers, body)
+    }
+
+    /// Timeout la conectare şi la citire; implicit nu face nimic.
+    fn set_timeouts(&mut self, _connect: Duration, _read: Duration) {}
+}
+
+/// Un client (deci o conexiune keep-alive) folosit din mai multe thread-uri.
+pub type SharedHttp = Arc<Mutex<dyn HttpClient + Send>>;
+
+/* ------------ HTTP server ------------------------------------------- */
+
+/// Ce are nevoie `http::register_handlers` de la un server: `EspHttpServer`
+/// pe placă, `HostHttpServer` (TCP simplu) în simulator.
+pub trait HttpServer {
+    type Conn<'r>: Connection<Error = Self::Error>;
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// `uri` poate să se termine în `*` (potrivire după prefix).
+    fn handler<F>(&mut self, uri: &str, method: Method, f: F) -> Result<()>
+    where
+        F: for<'r> Fn(Request<&mut Self::Conn<'r>>) -> Result<()> + Send + 'static;
+
+    /// Endpoint WebSocket; `f(session, eveniment)` e apelat la conectare,
+    /// pentru fiecare cadru text şi la închidere.
+    fn ws_handler<F>(&mut self, uri: &str, f: F) -> Result<()>
+    where
+        F: Fn(i32, WsEvent<'_>) -> Result<()> + Send + Sync + 'static;
+}
+
+/* ------------ WebSocket --------------------------------------------- */
+
+/// Trimite cadre text unui client; merge din orice thread.
+pub trait WsSender: Send {
+    fn send_text(&mut self, text: &str) -> Result<()>;
+    fn is_closed(&self) -> bool;
+}
+
+pub enum WsEvent<'a> {
+    Open(Box<dyn WsSender>),
+    Text(&'a str),
+    Closed,
+}
+
+/* ------------ stocare cheie/valoare (NVS) --------------------------- */
+
+/// Cheile respectă limita NVS: max. 15 caractere. Valorile, şi cele din
+/// `set_str`, sunt blob-uri (fără limita de 4000 B a string-urilor NVS).
+pub trait KeyValueStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    fn set(&mut self, key: &str, value: &[u8]) -> Result<()>;
+    fn remove(&mut self, key: &str) -> Result<bool>;
+
+    fn get_str(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.get(key)?.map(|v| String::from_utf8_lossy(&v).into_owned()))
+    }
+
+    fn set_str(&mut self, key: &str, value: &str) -> Result<()> {
+        self.set(key, value.as_bytes())
+    }
+}
+
+/* ------------ mDNS ---------------------------------------------------- */
+
+/// Anunţă `<hostname>.local` şi serviciul `_http._tcp`; apelată din nou
+/// cu aceleaşi date după o reconectare Wi-Fi.
+pub trait Advertiser {
+    fn advertise(&mut self, hostname: &str, port: u16, txt: &[(&str, &str)]) -> Result<()>;
+}
+
+/* ------------ OTA ---------------------------------------------------- */
+
+/// Slotul din care rulează firmware-ul acum.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RunningSlot {
+    /// `ota_0` / `ota_1`
+    pub label: String,
+    /// imagine nouă care nu s-a declarat încă sănătoasă – la o repornire
+    /// bootloader-ul revine la cea veche
+    pub pending_verify: bool,
+}
+
+/// Partiţiile `ota_0`/`ota_1`: imaginea nouă se scrie mereu în slotul
+/// care nu rulează şi devine slot de boot abia după verificare.
+pub trait Firmware {
+    /// octeţii disponibili în slotul inactiv
+    fn slot_size(&self) -> Result<usize>;
+    /// Şterge slotul inactiv şi începe scrierea.
+    fn begin(&mut self) -> Result<()>;
+    fn write(&mut self, data: &[u8]) -> Result<()>;
+    /// Validează imaginea scrisă şi o face slot de boot.
+    fn finish(&mut self) -> Result<()>;
+    /// Renunţă la scrierea în curs (dacă există).
+    fn abort(&mut self);
+
+    fn running(&self) -> Result<RunningSlot>;
+    /// Imaginea curentă merge – anulează rollback-ul.
+    fn mark_valid(&mut self) -> Result<()>;
+    /// Imaginea curentă e stricată: înapoi la cea veche şi repornire.
+    fn rollback(&mut self) -> Result<()>;
+    fn restart(&mut self);
+}
+
+/* ------------ Wi-Fi (staţie) ---------------------------------------- */
+
+/// O reţea văzută la scanare.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApInfo {
+    pub ssid: String,
+    pub rssi: i32,
+}
+
+/// Adresă fixă pentru staţie (telefon hotspot, router fără rezervări).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StaticIp {
+    pub ip:      Ipv4Addr,
+    pub gateway: Ipv4Addr,
+    pub netmask: Ipv4Addr,
+    /// cel mult două servere DNS
+    #[serde(default)]
+    pub dns:     Vec<Ipv4Addr>,
+}
+
+/// Configuraţia IP a interfeţei STA.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IpConfig {
+    /// numele trimis serverului DHCP (apare în lista routerului)
+    pub hostname:  String,
+    /// `None` = DHCP
+    #[serde(rename = "static", default)]
+    pub static_ip: Option<StaticIp>,
+}
+
+/// Radioul în mod staţie; la ce reţea se conectează decide `wifi::WifiManager`.
+pub trait WifiRadio {
+    fn scan(&mut self) -> Result<Vec<ApInfo>>;
+    /// Asociere + aşteptarea IP-ului (netif up); blochează până reuşeşte
+    /// sau expiră timeout-ul driverului.
+    fn connect(&mut self, ssid: &str, pass: &str) -> Result<Ipv4Addr>;
+    fn disconnect(&mut self) -> Result<()>;
+    /// `None` când nu e asociat
+    fn rssi(&self) -> Option<i32>;
+    /// Înlocuieşte interfaţa STA (DHCP cu hostname sau adresă fixă);
+    /// legătura curentă se pierde.
+    fn configure_ip(&mut self, cfg: &IpConfig) -> Result<()>;
+}
+
+/* ------------ ceas (SNTP) ------------------------------------------- */
+
+/// Sincronizarea ceasului sistemului; la fiecare reuşită implementarea
+/// apelează `clock::mark_synced`.
+pub trait TimeSync {
+    /// (Re)porneşte sincronizarea cu serverele date.
+    fn start(&mut self, servers: &[String]) -> Result<()>;
+}
@@ -0,0 +1,500 @@
+//! Implementările ESP-IDF ale trăsăturilor din `hal`.
+
+use anyhow::{anyhow, bail, Result};
+use embedded_svc::http::Method;
+use esp_idf_svc::{
+    hal::i2s::{I2sDriver, I2sTx},
+    http::client::{Configuration as HttpCfg, EspHttpConnection},
+    http::server::{EspHttpConnection as EspServerConnection, EspHttpServer},
+    http::server::ws::{EspHttpWsConnection, EspHttpWsDetachedSender},
+    ws::FrameType,
+    io::EspIOError,
+    ipv4::{self, ClientSettings, DHCPClientSettings, Mask, Subnet},
+    mdns::EspMdns,
+    netif::{EspNetif, NetifConfiguration},
+    sntp::{EspSntp, SntpConf},
+    wifi::{AuthMethod, BlockingWifi, ClientConfiguration, Configuration, EspWifi},
+    nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault},
+    sys::{
+        esp, esp_crt_bundle_attach, TickType_t,
+        esp_ota_abort, esp_ota_begin, esp_ota_end, esp_ota_get_next_update_partition,
+        esp_ota_get_running_partition, esp_ota_get_state_partition, esp_ota_handle_t,
+        esp_ota_img_states_t, esp_ota_img_states_t_ESP_OTA_IMG_PENDING_VERIFY,
+        esp_ota_mark_app_invalid_rollback_and_reboot, esp_ota_mark_app_valid_cancel_rollback,
+        esp_ota_set_boot_partition, esp_ota_write, esp_partition_t, esp_restart, ESP_OK,
+        OTA_WITH_SEQUENTIAL_WRITES,
+    },
+};
+use std::{ffi::CStr, net::Ipv4Addr, ptr, time::Duration};
+
+use super::{
+    Advertiser, ApInfo, AudioSink, Firmware, HttpClient, HttpResponse, HttpServer, IpConfig, KeyValueStore, MotorDriver,
+    NotSent, Request, RunningSlot, ServoBank, TimeSync, WifiRadio, WsEvent, WsSender,
+};
+use crate::motors::{MotorId, L9110S};
+use crate::servo::{DualServo, ServoId};
+
+impl MotorDriver for L9110S<'_> {
+    fn drive(&mut self, id: MotorId, speed: i8) -> Result<()> {
+        L9110S::drive(self, id, speed)
+    }
+}
+
+impl ServoBank for DualServo<'_> {
+    fn set_angle(&mut self, id: ServoId, deg: f32) -> Result<()> {
+        DualServo::set_angle(self, id, deg)
+    }
+}
+
+impl AudioSink for I2sDriver<'static, I2sTx> {
+    fn write(&mut self, pcm: &[u8]) -> Result<()> {
+        self.write_all(pcm, TickType_t::MAX)?;
+        Ok(())
+    }
+}
+
+/* ------------ HTTPS (bundle CA) ------------------------------------- */
+
+pub struct EspHttp {
+    cfg:    HttpCfg,
+    conn:   Option<EspHttpConnection>,
+    /// `https://host[:port]` al conexiunii deschise
+    origin: String,
+}
+
+impl EspHttp {
+    pub fn new() -> Self {
+        Self {
+            cfg: HttpCfg {
+                use_global_ca_store: true,
+                crt_bundle_attach: Some(esp_crt_bundle_attach),
+                ..Default::default()
+            },
+            conn:   None,
+            origin: String::new(),
+        }
+    }
+
+    /// buffere RX/TX explicite (Azure trimite headere lungi)
+    pub fn with_buffers(size: usize) -> Self {
+        let mut http = Self::new();
+        http.cfg.buffer_size = Some(size);
+        http.cfg.buffer_size_tx = Some(size);
+        http
+    }
+
+    fn connect(&mut self, url: &str) -> Result<()> {
+        // întâi o închidem pe cea veche – RAM-ul pentru TLS nu ajunge de două ori
+        self.conn = None;
+        self.conn = Some(EspHttpConnection::new(&self.cfg).map_err(|e| NotSent(e.into()))?);
+        self.origin = origin(url).to_owned();
+        Ok(())
+    }
+}
+
+impl Default for EspHttp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct EspResponse<'a>(&'a mut EspHttpConnection);
+
+impl HttpResponse for EspResponse<'_> {
+    fn status(&self) -> u16 {
+        self.0.status()
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.0.header(name)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Ok(self.0.read(buf)?)
+    }
+}
+
+impl HttpClient for EspHttp {
+    fn request(
+        &mut self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<Box<dyn HttpResponse + '_>> {
+        let clen = body.len().to_string();
+        let mut hdrs: Vec<(&str, &str)> = headers.to_vec();
+        if !hdrs.iter().any(|(k, _)| k.eq_ignore_ascii_case("Content-Length")) {
+            hdrs.push(("Content-Length", clen.as_str()));
+        }
+
+        // acelaşi host → aceeaşi conexiune (keep-alive), fără TCP + TLS la
+        // fiecare cerere; alt host → conexiune nouă – TLS-ul nu supravieţuieşte
+        // mereu schimbării
+        let reused = self.origin == origin(url) && self.conn.as_ref().is_some_and(|c| !c.is_request_initiated());
+        if !reused {
+            self.connect(url)?;
+        }
+        if let Err(e) = send(self.conn.as_mut().unwrap(), method, url, &hdrs, body) {
+            self.conn = None;
+            if !reused {
+                return Err(e);
+            }
+            // serverul a închis între timp conexiunea păstrată
+            log::debug!("🔁 keep-alive {}: {e:#} – conexiune nouă", self.origin);
+            self.connect(url)?;
+            if let Err(e) = send(self.conn.as_mut().unwrap(), method, url, &hdrs, body) {
+                self.conn = None;
+                return Err(e);
+            }
+        }
+
+        Ok(Box::new(EspResponse(self.conn.as_mut().unwrap())))
+    }
+
+    /// `esp_http_client` are un singur `timeout_ms` (conectare + citire),
+    /// fixat la crearea conexiunii
+    fn set_timeouts(&mut self, connect: Duration, read: Duration) {
+        let timeout = Some(connect.max(read));
+        if self.cfg.timeout != timeout {
+            self.cfg.timeout = timeout;
+            self.conn = None;
+        }
+    }
+}
+
+/// `"https://host:443/cale?x"` → `"https://host:443"`
+fn origin(url: &str) -> &str {
+    let start = url.find("://").map_or(0, |i| i + 3);
+    url[start..].find('/').map_or(url, |i| &url[..start + i])
+}
+
+fn send(conn: &mut EspHttpConnection, method: Method, url: &str, headers: &[(&str, &str)], body: &[u8]) -> Result<()> {
+    // restul unui răspuns necitit e golit de `initiate_request`; până aici
+    // (DNS, TCP, TLS, antet) serverul n-a primit o cerere completă
+    conn.initiate_request(method, url, headers).map_err(|e| NotSent(e.into()))?;
+    conn.write_all(body)?;
+    conn.initiate_response()?;
+    Ok(())
+}
+
+/* ------------ HTTP server ------------------------------------------- */
+
+impl HttpServer for EspHttpServer<'_> {
+    type Conn<'r> = EspServerConnection<'r>;
+    type Error = EspIOError;
+
+    fn handler<F>(&mut self, uri: &str, method: Method, f: F) -> Result<()>
+    where
+        F: for<'r> Fn(Request<&mut Self::Conn<'r>>) -> Result<()> + Send + 'static,
+    {
+        self.fn_handler(uri, method, f)?;
+        Ok(())
+    }
+
+    /// necesită `CONFIG_HTTPD_WS_SUPPORT=y` (vezi `sdkconfig.defaults`)
+    fn ws_handler<F>(&mut self, uri: &str, f: F) -> Result<()>
+    where
+        F: Fn(i32, WsEvent<'_>) -> Result<()> + Send + Sync + 'static,
+    {
+        EspHttpServer::ws_handler(self, uri, move |conn: &mut EspHttpWsConnection| -> Result<()> {
+            let session = conn.session();
+            if conn.is_new() {
+                return f(session, WsEvent::Open(Box::new(conn.create_detached_sender()?)));
+            }
+            if conn.is_closed() {
+                return f(session, WsEvent::Closed);
+            }
+
+            // întâi lungimea, apoi cadrul; textul vine cu un NUL la final
+            let (kind, len) = conn.recv(&mut [])?;
+            if !matches!(kind, FrameType::Text(false)) || len > MAX_WS_FRAME {
+                return Ok(());
+            }
+            let mut buf = vec![0u8; len];
+            conn.recv(&mut buf)?;
+            let text = core::str::from_utf8(&buf)?.trim_end_matches('\0');
+            f(session, WsEvent::Text(text))
+        })?;
+        Ok(())
+    }
+}
+
+/// cadrele mai mari (sau fragmentate) sunt ignorate
+const MAX_WS_FRAME: usize = 1024;
+
+impl WsSender for EspHttpWsDetachedSender {
+    fn send_text(&mut self, text: &str) -> Result<()> {
+        self.send(FrameType::Text(false), text.as_bytes())?;
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        EspHttpWsDetachedSender::is_closed(self)
+    }
+}
+
+/* ------------ NVS ---------------------------------------------------- */
+
+pub struct EspKv(EspNvs<NvsDefault>);
+
+impl EspKv {
+    pub fn new(part: EspDefaultNvsPartition, namespace: &str) -> Result<Self> {
+        Ok(Self(EspNvs::new(part, namespace, true)?))
+    }
+}
+
+impl KeyValueStore for EspKv {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let Some(len) = self.0.blob_len(key)? else {
+            return Ok(None);
+        };
+        let mut buf = vec![0u8; len];
+        Ok(self.0.get_raw(key, &mut buf)?.map(|v| v.to_vec()))
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        self.0.set_raw(key, value)?;
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<bool> {
+        Ok(self.0.remove(key)?)
+    }
+}
+
+/* ------------ mDNS ---------------------------------------------------- */
+
+/// Responder-ul mDNS din ESP-IDF (componenta `espressif/mdns`).
+pub struct EspAdvertiser {
+    mdns:  EspMdns,
+    added: bool,
+}
+
+impl EspAdvertiser {
+    pub fn take() -> Result<Self> {
+        Ok(Self { mdns: EspMdns::take()?, added: false })
+    }
+}
+
+impl Advertiser for EspAdvertiser {
+    fn advertise(&mut self, hostname: &str, port: u16, txt: &[(&str, &str)]) -> Result<()> {
+        // hostname setat din nou = anunţ nou, cu IP-ul curent
+        self.mdns.set_hostname(hostname)?;
+        self.mdns.set_instance_name("MyRoboAssistant")?;
+        if self.added {
+            self.mdns.set_service_port("_http", "_tcp", port)?;
+            self.mdns.set_service_txt("_http", "_tcp", txt)?;
+        } else {
+            self.mdns.add_service(Some("MyRoboAssistant"), "_http", "_tcp", port, txt)?;
+            self.added = true;
+        }
+        Ok(())
+    }
+}
+
+/* ------------ OTA ---------------------------------------------------- */
+
+/// `esp_ota_*` direct: `EspOtaUpdate` împrumută `EspOta`, iar aici
+/// scrierea trebuie să trăiască între apeluri.
+pub struct EspFirmware {
+    update: Option<(esp_ota_handle_t, *const esp_partition_t)>,
+}
+
+// pointerul arată în tabela de partiţii, care e statică
+unsafe impl Send for EspFirmware {}
+
+impl EspFirmware {
+    pub fn new() -> Self {
+        Self { update: None }
+    }
+
+    fn next_slot() -> Result<*const esp_partition_t> {
+        let part = unsafe { esp_ota_get_next_update_partition(ptr::null()) };
+        if part.is_null() {
+            bail!("nu există slot OTA – partitions.csv fără ota_0/ota_1?");
+        }
+        Ok(part)
+    }
+}
+
+impl Default for EspFirmware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Firmware for EspFirmware {
+    fn slot_size(&self) -> Result<usize> {
+        Ok(unsafe { (*Self::next_slot()?).size } as usize)
+    }
+
+    fn begin(&mut self) -> Result<()> {
+        self.abort();
+        let part = Self::next_slot()?;
+        let mut handle: esp_ota_handle_t = 0;
+        // şterge sector cu sector pe măsură ce scrie – altfel ştergerea
+        // întregului slot ţine task-ul httpd ocupat zeci de secunde
+        esp!(unsafe { esp_ota_begin(part, OTA_WITH_SEQUENTIAL_WRITES as usize, &mut handle) })?;
+        self.update = Some((handle, part));
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        let Some((handle, _)) = self.update else { bail!("nicio actualizare pornită") };
+        esp!(unsafe { esp_ota_write(handle, data.as_ptr().cast(), data.len()) })?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let Some((handle, part)) = self.update.take() else { bail!("nicio actualizare pornită") };
+        // `esp_ota_end` verifică imaginea (header, segmente, SHA-256 din ea)
+        esp!(unsafe { esp_ota_end(handle) })?;
+        esp!(unsafe { esp_ota_set_boot_partition(part) })?;
+        Ok(())
+    }
+
+    fn abort(&mut self) {
+        if let Some((handle, _)) = self.update.take() {
+            unsafe { esp_ota_abort(handle) };
+        }
+    }
+
+    fn running(&self) -> Result<RunningSlot> {
+        let part = unsafe { esp_ota_get_running_partition() };
+        if part.is_null() {
+            bail!("partiţia curentă nu a fost găsită");
+        }
+        let mut state: esp_ota_img_states_t = 0;
+        let ok = unsafe { esp_ota_get_state_partition(part, &mut state) } == ESP_OK;
+        let label = unsafe { CStr::from_ptr((*part).label.as_ptr()) };
+        Ok(RunningSlot {
+            label:          label.to_string_lossy().into_owned(),
+            pending_verify: ok && state == esp_ota_img_states_t_ESP_OTA_IMG_PENDING_VERIFY,
+        })
+    }
+
+    fn mark_valid(&mut self) -> Result<()> {
+        esp!(unsafe { esp_ota_mark_app_valid_cancel_rollback() })?;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        // reporneşte dacă reuşeşte
+        esp!(unsafe { esp_ota_mark_app_invalid_rollback_and_reboot() })?;
+        Ok(())
+    }
+
+    fn restart(&mut self) {
+        unsafe { esp_restart() };
+    }
+}
+
+/* ------------ Wi-Fi --------------------------------------------------- */
+
+/// `BlockingWifi` pornit în mod staţie (fără reţea configurată).
+pub struct EspRadio(BlockingWifi<EspWifi<'static>>);
+
+impl EspRadio {
+    pub fn new(mut wifi: BlockingWifi<EspWifi<'static>>) -> Result<Self> {
+        wifi.set_configuration(&Configuration::Client(ClientConfiguration::default()))?;
+        wifi.start()?;
+        Ok(Self(wifi))
+    }
+}
+
+impl WifiRadio for EspRadio {
+    fn scan(&mut self) -> Result<Vec<ApInfo>> {
+        Ok(self
+            .0
+            .scan()?
+            .into_iter()
+            .map(|ap| ApInfo { ssid: ap.ssid.as_str().to_owned(), rssi: ap.signal_strength as i32 })
+            .collect())
+    }
+
+    fn connect(&mut self, ssid: &str, pass: &str) -> Result<Ipv4Addr> {
+        if self.0.is_connected().unwrap_or(false) {
+            let _ = self.0.disconnect();
+        }
+        self.0.set_configuration(&Configuration::Client(ClientConfiguration {
+            ssid:        ssid.try_into().map_err(|_| anyhow!("SSID prea lung"))?,
+            password:    pass.try_into().map_err(|_| anyhow!("parolă prea lungă"))?,
+            auth_method: if pass.is_empty() { AuthMethod::None } else { AuthMethod::WPA2Personal },
+            ..Default::default()
+        }))?;
+        self.0.connect()?;
+        self.0.wait_netif_up()?;        // DHCP gata
+        Ok(self.0.wifi().sta_netif().get_ip_info()?.ip)
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        self.0.disconnect()?;
+        Ok(())
+    }
+
+    fn rssi(&self) -> Option<i32> {
+        self.0.wifi().get_rssi().ok()
+    }
+
+    fn configure_ip(&mut self, cfg: &IpConfig) -> Result<()> {
+        let ip = match &cfg.static_ip {
+            // hostname-ul contează doar pentru DHCP
+            None => ipv4::ClientConfiguration::DHCP(DHCPClientSettings {
+                hostname: Some(cfg.hostname.as_str().try_into().map_err(|_| anyhow!("hostname prea lung"))?),
+            }),
+            Some(st) => ipv4::ClientConfiguration::Fixed(ClientSettings {
+                ip:            st.ip,
+                subnet:        Subnet {
+                    gateway: st.gateway,
+                    mask:    Mask::try_from(st.netmask).map_err(|()| anyhow!("mască invalidă: {}", st.netmask))?,
+                },
+                dns:           st.dns.first().copied(),
+                secondary_dns: st.dns.get(1).copied(),
+            }),
+        };
+        let netif = EspNetif::new_with_conf(&NetifConfiguration {
+            ip_configuration: Some(ipv4::Configuration::Client(ip)),
+            ..NetifConfiguration::wifi_default_client()
+        })?;
+
+        // interfaţa se schimbă doar cu driverul oprit
+        let started = self.0.is_started()?;
+        if started {
+            self.0.stop()?;
+        }
+        self.0.wifi_mut().swap_netif_sta(netif)?;
+        if started {
+            self.0.start()?;
+        }
+        Ok(())
+    }
+}
+
+/* ------------ SNTP -------------------------------------------------- */
+
+/// `EspSntp` poate exista o singură dată – se reface la servere noi.
+#[derive(Default)]
+pub struct EspTimeSync(Option<EspSntp<'static>>);
+
+impl EspTimeSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TimeSync for EspTimeSync {
+    fn start(&mut self, servers: &[String]) -> Result<()> {
+        if servers.is_empty() {
+            bail!("niciun server NTP");
+        }
+        self.0 = None;
+        // `CONFIG_LWIP_SNTP_MAX_SERVERS` locuri – se repetă lista dacă e mai scurtă
+        let mut conf = SntpConf::default();
+        for (slot, server) in conf.servers.iter_mut().zip(servers.iter().cycle()) {
+            *slot = server.as_str();
+        }
+        self.0 = Some(EspSntp::new_with_callback(&conf, |_| crate::clock::mark_synced())?);
+        Ok(())
+    }
+}
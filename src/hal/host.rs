@@ -0,0 +1,478 @@
+//! Implementări „de bancă” pentru PC – fără placă, fără reţea.
+
+use anyhow::{anyhow, Result};
+use embedded_svc::http::Method;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{Seek, SeekFrom, Write as _},
+    net::Ipv4Addr,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use super::{
+    Advertiser, ApInfo, AudioSink, Firmware, HttpClient, HttpResponse, IpConfig, KeyValueStore, MotorDriver,
+    RunningSlot, ServoBank, TimeSync, WifiRadio,
+};
+use crate::motors::MotorId;
+use crate::servo::ServoId;
+
+/* ------------ motoare / servo --------------------------------------- */
+
+/// Ţine minte ultima viteză pe fiecare motor + istoricul comenzilor.
+#[derive(Debug, Default)]
+pub struct RecordingMotors {
+    pub left:    i8,
+    pub right:   i8,
+    pub history: Vec<(MotorId, i8)>,
+}
+
+impl MotorDriver for RecordingMotors {
+    fn drive(&mut self, id: MotorId, speed: i8) -> Result<()> {
+        let speed = speed.clamp(-100, 100);
+        match id {
+            MotorId::Left  => self.left = speed,
+            MotorId::Right => self.right = speed,
+        }
+        self.history.push((id, speed));
+        log::debug!("motor {id:?} = {speed}%");
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RecordingServos {
+    pub left:    f32,
+    pub right:   f32,
+    pub history: Vec<(ServoId, f32)>,
+}
+
+impl ServoBank for RecordingServos {
+    fn set_angle(&mut self, id: ServoId, deg: f32) -> Result<()> {
+        let deg = deg.clamp(0.0, 180.0);
+        match id {
+            ServoId::Left  => self.left = deg,
+            ServoId::Right => self.right = deg,
+        }
+        self.history.push((id, deg));
+        log::debug!("servo {id:?} = {deg:.0}°");
+        Ok(())
+    }
+}
+
+/* ------------ audio -------------------------------------------------- */
+
+/// Colectează tot PCM-ul primit; `utterances` numără apelurile `flush`.
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    pub pcm:        Vec<u8>,
+    pub utterances: usize,
+}
+
+impl AudioSink for MemorySink {
+    fn write(&mut self, pcm: &[u8]) -> Result<()> {
+        self.pcm.extend_from_slice(pcm);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.utterances += 1;
+        Ok(())
+    }
+}
+
+/// Scrie PCM-ul într-un WAV; headerul e rescris la fiecare `flush`.
+pub struct WavFileSink {
+    file:        File,
+    sample_rate: u32,
+    data_len:    u32,
+}
+
+impl WavFileSink {
+    pub fn create(path: impl AsRef<Path>, sample_rate: u32) -> Result<Self> {
+        let mut sink = Self {
+            file: File::create(path)?,
+            sample_rate,
+            data_len: 0,
+        };
+        sink.write_header()?;
+        Ok(sink)
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        let header = crate::util::pcm_to_wav(&[], self.sample_rate);
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header[..4])?;
+        self.file.write_all(&(36 + self.data_len).to_le_bytes())?;
+        self.file.write_all(&header[8..40])?;
+        self.file.write_all(&self.data_len.to_le_bytes())?;
+        self.file.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+}
+
+impl AudioSink for WavFileSink {
+    fn write(&mut self, pcm: &[u8]) -> Result<()> {
+        self.file.write_all(pcm)?;
+        self.data_len += pcm.len() as u32;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.write_header()?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for WavFileSink {
+    fn drop(&mut self) {
+        let _ = self.write_header();
+    }
+}
+
+/* ------------ HTTP scriptat ----------------------------------------- */
+
+#[derive(Clone, Debug)]
+pub struct ScriptedResponse {
+    pub status:  u16,
+    pub headers: Vec<(String, String)>,
+    pub body:    Vec<u8>,
+}
+
+impl ScriptedResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self { status, headers: Vec::new(), body: body.into() }
+    }
+
+    pub fn json(status: u16, value: &serde_json::Value) -> Self {
+        Self::new(status, value.to_string())
+            .with_header("Content-Type", "application/json")
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// O cerere văzută de `ScriptedHttp`.
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method:  Method,
+    pub url:     String,
+    pub headers: Vec<(String, String)>,
+    pub body:    Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Default)]
+struct Script {
+    responses: VecDeque<ScriptedResponse>,
+    requests:  Vec<RecordedRequest>,
+}
+
+/// Răspunde în ordine cu răspunsurile puse în coadă. Clonele împart
+/// aceeaşi coadă, aşa că un test poate da câte o clonă fiecărui thread.
+#[derive(Clone, Default)]
+pub struct ScriptedHttp(Arc<Mutex<Script>>);
+
+impl ScriptedHttp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, resp: ScriptedResponse) -> &Self {
+        self.0.lock().unwrap().responses.push_back(resp);
+        self
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.0.lock().unwrap().requests.clone()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.0.lock().unwrap().responses.len()
+    }
+}
+
+struct BufResponse {
+    resp: ScriptedResponse,
+    pos:  usize,
+}
+
+impl HttpResponse for BufResponse {
+    fn status(&self) -> u16 {
+        self.resp.status
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.resp
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let rest = &self.resp.body[self.pos..];
+        let n = rest.len().min(buf.len());
+        buf[..n].copy_from_slice(&rest[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl HttpClient for ScriptedHttp {
+    fn request(
+        &mut self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<Box<dyn HttpResponse + '_>> {
+        let mut script = self.0.lock().unwrap();
+        script.requests.push(RecordedRequest {
+            method,
+            url: url.into(),
+            headers: headers.iter().map(|(k, v)| ((*k).into(), (*v).into())).collect(),
+            body: body.to_vec(),
+        });
+        let resp = script
+            .responses
+            .pop_front()
+            .ok_or_else(|| anyhow!("ScriptedHttp: niciun răspuns pentru {url}"))?;
+        Ok(Box::new(BufResponse { resp, pos: 0 }))
+    }
+}
+
+/* ------------ KV în memorie ----------------------------------------- */
+
+#[derive(Clone, Debug, Default)]
+pub struct MemoryKv(HashMap<String, Vec<u8>>);
+
+impl KeyValueStore for MemoryKv {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(key).cloned())
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        if key.len() > 15 {
+            return Err(anyhow!("cheie NVS prea lungă: {key}"));
+        }
+        self.0.insert(key.into(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<bool> {
+        Ok(self.0.remove(key).is_some())
+    }
+}
+
+/* ------------ mDNS înregistrat --------------------------------------- */
+
+/// Un anunţ mDNS: hostname, port, TXT.
+pub type Announcement = (String, u16, Vec<(String, String)>);
+
+/// Păstrează anunţurile (clonele împart lista).
+#[derive(Clone, Debug, Default)]
+pub struct RecordingAdvertiser(pub Arc<Mutex<Vec<Announcement>>>);
+
+impl Advertiser for RecordingAdvertiser {
+    fn advertise(&mut self, hostname: &str, port: u16, txt: &[(&str, &str)]) -> Result<()> {
+        let txt = txt.iter().map(|&(k, v)| (k.to_owned(), v.to_owned())).collect();
+        self.0.lock().unwrap().push((hostname.to_owned(), port, txt));
+        Ok(())
+    }
+}
+
+/* ------------ OTA în memorie ----------------------------------------- */
+
+#[derive(Debug, Default)]
+pub struct FirmwareState {
+    pub slot_size:      usize,
+    /// ce s-a scris în slotul inactiv
+    pub image:          Vec<u8>,
+    pub writing:        bool,
+    /// `finish` reuşit – slotul inactiv e noul slot de boot
+    pub activated:      bool,
+    pub pending_verify: bool,
+    pub rollbacks:      usize,
+    pub restarts:       usize,
+}
+
+/// Slotul inactiv e un `Vec`; clonele împart starea, ca testul să vadă
+/// ce a ajuns „în flash”.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryFirmware(pub Arc<Mutex<FirmwareState>>);
+
+impl MemoryFirmware {
+    pub fn new(slot_size: usize) -> Self {
+        Self(Arc::new(Mutex::new(FirmwareState { slot_size, ..Default::default() })))
+    }
+}
+
+impl Firmware for MemoryFirmware {
+    fn slot_size(&self) -> Result<usize> {
+        Ok(self.0.lock().unwrap().slot_size)
+    }
+
+    fn begin(&mut self) -> Result<()> {
+        let mut fw = self.0.lock().unwrap();
+        fw.image.clear();
+        fw.writing = true;
+        fw.activated = false;
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        let mut fw = self.0.lock().unwrap();
+        if !fw.writing {
+            return Err(anyhow!("nicio actualizare pornită"));
+        }
+        if fw.image.len() + data.len() > fw.slot_size {
+            return Err(anyhow!("imaginea nu încape în slot"));
+        }
+        fw.image.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let mut fw = self.0.lock().unwrap();
+        if !std::mem::take(&mut fw.writing) {
+            return Err(anyhow!("nicio actualizare pornită"));
+        }
+        fw.activated = true;
+        Ok(())
+    }
+
+    fn abort(&mut self) {
+        self.0.lock().unwrap().writing = false;
+    }
+
+    fn running(&self) -> Result<RunningSlot> {
+        Ok(RunningSlot { label: "ota_0".into(), pending_verify: self.0.lock().unwrap().pending_verify })
+    }
+
+    fn mark_valid(&mut self) -> Result<()> {
+        self.0.lock().unwrap().pending_verify = false;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        let mut fw = self.0.lock().unwrap();
+        fw.rollbacks += 1;
+        fw.restarts += 1;
+        Ok(())
+    }
+
+    fn restart(&mut self) {
+        self.0.lock().unwrap().restarts += 1;
+    }
+}
+
+/* ------------ Wi-Fi scriptat ------------------------------------------ */
+
+#[derive(Debug, Default)]
+pub struct RadioState {
+    /// ce găseşte scanarea
+    pub visible:   Vec<ApInfo>,
+    /// (SSID, parolă) cu care asocierea reuşeşte – dacă reţeaua e vizibilă
+    pub accepts:   Vec<(String, String)>,
+    /// SSID-urile încercate, în ordine
+    pub attempts:  Vec<String>,
+    pub connected: Option<String>,
+    pub ip:        Option<IpConfig>,
+}
+
+/// Un „aer” cu reţele configurabile din test; clonele împart starea.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedRadio(pub Arc<Mutex<RadioState>>);
+
+impl ScriptedRadio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn network(&self, ssid: &str, pass: &str, rssi: i32) -> &Self {
+        let mut st = self.0.lock().unwrap();
+        st.visible.push(ApInfo { ssid: ssid.into(), rssi });
+        st.accepts.push((ssid.into(), pass.into()));
+        self
+    }
+
+    /// Reţeaua dispare (routerul s-a oprit); clientul rămâne deconectat.
+    pub fn drop_network(&self, ssid: &str) {
+        let mut st = self.0.lock().unwrap();
+        st.visible.retain(|ap| ap.ssid != ssid);
+        if st.connected.as_deref() == Some(ssid) {
+            st.connected = None;
+        }
+    }
+}
+
+impl WifiRadio for ScriptedRadio {
+    fn scan(&mut self) -> Result<Vec<ApInfo>> {
+        Ok(self.0.lock().unwrap().visible.clone())
+    }
+
+    fn connect(&mut self, ssid: &str, pass: &str) -> Result<Ipv4Addr> {
+        let mut st = self.0.lock().unwrap();
+        st.attempts.push(ssid.into());
+        st.connected = None;
+        let pos = st.visible.iter().position(|ap| ap.ssid == ssid);
+        match pos {
+            Some(i) if st.accepts.iter().any(|(s, p)| s == ssid && p == pass) => {
+                st.connected = Some(ssid.into());
+                match st.ip.as_ref().and_then(|c| c.static_ip.as_ref()) {
+                    Some(fixed) => Ok(fixed.ip),
+                    None => Ok(Ipv4Addr::new(192, 168, 1, 10 + i as u8)),
+                }
+            }
+            Some(_) => Err(anyhow!("{ssid}: parolă greşită")),
+            None => Err(anyhow!("{ssid}: reţea negăsită")),
+        }
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        self.0.lock().unwrap().connected = None;
+        Ok(())
+    }
+
+    fn rssi(&self) -> Option<i32> {
+        let st = self.0.lock().unwrap();
+        let ssid = st.connected.as_ref()?;
+        st.visible.iter().find(|ap| &ap.ssid == ssid).map(|ap| ap.rssi)
+    }
+
+    fn configure_ip(&mut self, cfg: &IpConfig) -> Result<()> {
+        let mut st = self.0.lock().unwrap();
+        st.connected = None;
+        st.ip = Some(cfg.clone());
+        Ok(())
+    }
+}
+
+/* ------------ ceas ---------------------------------------------------- */
+
+/// Ceasul PC-ului e deja corect: orice `start` se consideră sincronizat.
+/// Listele de servere primite rămân în `.0` pentru teste.
+#[derive(Clone, Debug, Default)]
+pub struct HostTimeSync(pub Arc<Mutex<Vec<Vec<String>>>>);
+
+impl TimeSync for HostTimeSync {
+    fn start(&mut self, servers: &[String]) -> Result<()> {
+        self.0.lock().unwrap().push(servers.to_vec());
+        crate::clock::mark_synced();
+        Ok(())
+    }
+}
@@ -0,0 +1,852 @@
+//! Reţea pe PC: un server HTTP/1.1 minimal (acelaşi `register_handlers`
+//! ca pe placă) şi un client HTTP simplu, fără TLS, pentru stand-in-uri
+//! locale ale OpenAI / Azure.
+
+use anyhow::{anyhow, bail, Context, Result};
+use embedded_svc::http::{server::Connection, Headers, Method, Query};
+use embedded_svc::io::{ErrorType, Read, Write};
+use std::{
+    io::{self, BufRead, BufReader, Read as _, Write as _},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use super::{HttpClient, HttpResponse, HttpServer, NotSent, Request, WsEvent, WsSender};
+
+const MAX_HEADERS: usize = 64;
+
+fn method_from_str(s: &str) -> Option<Method> {
+    Some(match s {
+        "GET"     => Method::Get,
+        "POST"    => Method::Post,
+        "PUT"     => Method::Put,
+        "PATCH"   => Method::Patch,
+        "DELETE"  => Method::Delete,
+        "HEAD"    => Method::Head,
+        "OPTIONS" => Method::Options,
+        _         => return None,
+    })
+}
+
+fn method_str(m: Method) -> &'static str {
+    match m {
+        Method::Get     => "GET",
+        Method::Post    => "POST",
+        Method::Put     => "PUT",
+        Method::Patch   => "PATCH",
+        Method::Delete  => "DELETE",
+        Method::Head    => "HEAD",
+        Method::Options => "OPTIONS",
+        _               => "GET",
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Citeşte liniile de header până la linia goală.
+fn read_head(reader: &mut impl BufRead) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            let msg = "conexiune închisă în timpul headerelor";
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg).into());
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() { break; }
+        if lines.len() > MAX_HEADERS {
+            bail!("prea multe headere");
+        }
+        lines.push(line.to_owned());
+    }
+    Ok(lines)
+}
+
+fn parse_headers(lines: &[String]) -> Vec<(String, String)> {
+    lines
+        .iter()
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+        .collect()
+}
+
+/* ------------ server ------------------------------------------------- */
+
+/// Partea „read-only” a cererii (metodă, URI, headere).
+pub struct HostHead {
+    method:  Method,
+    uri:     String,
+    headers: Vec<(String, String)>,
+}
+
+impl Query for HostHead {
+    fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    fn method(&self) -> Method {
+        self.method
+    }
+}
+
+impl Headers for HostHead {
+    fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// Corpul cererii, limitat la `Content-Length`.
+pub struct HostBody {
+    reader: BufReader<TcpStream>,
+    left:   usize,
+}
+
+impl ErrorType for HostBody {
+    type Error = io::Error;
+}
+
+impl Read for HostBody {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        let max = buf.len().min(self.left);
+        if max == 0 { return Ok(0); }
+        let n = self.reader.read(&mut buf[..max])?;
+        self.left -= n;
+        Ok(n)
+    }
+}
+
+impl HostBody {
+    /// Ca httpd: restul corpului necitit (ex. după un 401) e aruncat –
+    /// închis cu date necitite, socketul trimite RST şi clientul pierde
+    /// răspunsul.
+    fn discard(&mut self) -> io::Result<()> {
+        let mut buf = [0u8; 1024];
+        while self.read(&mut buf)? > 0 {}
+        Ok(())
+    }
+}
+
+/// Socketul brut (pentru upgrade-uri de protocol).
+pub struct HostRaw(TcpStream);
+
+impl ErrorType for HostRaw {
+    type Error = io::Error;
+}
+
+impl Read for HostRaw {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for HostRaw {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.0.flush()
+    }
+}
+
+pub struct HostConnection {
+    head:      HostHead,
+    body:      HostBody,
+    raw:       HostRaw,
+    responded: bool,
+}
+
+impl HostConnection {
+    fn accept(stream: TcpStream) -> Result<Self> {
+        let raw = HostRaw(stream.try_clone()?);
+        let mut reader = BufReader::new(stream);
+        let lines = read_head(&mut reader)?;
+
+        let (first, rest) = lines.split_first().context("cerere goală")?;
+        let mut parts = first.split_whitespace();
+        let method = parts
+            .next()
+            .and_then(method_from_str)
+            .ok_or_else(|| anyhow!("metodă necunoscută: {first}"))?;
+        let uri = parts.next().context("lipseşte URI")?.to_owned();
+
+        let headers = parse_headers(rest);
+        let left = find_header(&headers, "Content-Length")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        Ok(Self {
+            head: HostHead { method, uri, headers },
+            body: HostBody { reader, left },
+            raw,
+            responded: false,
+        })
+    }
+
+    fn path(&self) -> &str {
+        self.head.uri.split('?').next().unwrap_or("")
+    }
+}
+
+impl Query for HostConnection {
+    fn uri(&self) -> &str {
+        self.head.uri()
+    }
+
+    fn method(&self) -> Method {
+        self.head.method
+    }
+}
+
+impl Headers for HostConnection {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.head.header(name)
+    }
+}
+
+impl ErrorType for HostConnection {
+    type Error = io::Error;
+}
+
+impl Read for HostConnection {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        self.body.read(buf)
+    }
+}
+
+impl Write for HostConnection {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        if !self.responded {
+            return Err(io::Error::other("răspunsul nu a fost iniţiat"));
+        }
+        self.raw.0.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.raw.0.flush()
+    }
+}
+
+impl Connection for HostConnection {
+    type Headers = HostHead;
+    type Read = HostBody;
+    type RawConnectionError = io::Error;
+    type RawConnection = HostRaw;
+
+    fn split(&mut self) -> (&HostHead, &mut HostBody) {
+        (&self.head, &mut self.body)
+    }
+
+    fn initiate_response<'a>(
+        &'a mut self,
+        status: u16,
+        message: Option<&'a str>,
+        headers: &'a [(&'a str, &'a str)],
+    ) -> Result<(), io::Error> {
+        if self.responded {
+            return Err(io::Error::other("răspuns deja iniţiat"));
+        }
+        let mut head = format!("HTTP/1.1 {status} {}\r\n", message.unwrap_or(reason(status)));
+        for (k, v) in headers {
+            head.push_str(&format!("{k}: {v}\r\n"));
+        }
+        // un singur răspuns per conexiune – corpul se termină la închidere
+        head.push_str("Connection: close\r\n\r\n");
+        self.raw.0.write_all(head.as_bytes())?;
+        self.responded = true;
+        Ok(())
+    }
+
+    fn is_response_initiated(&self) -> bool {
+        self.responded
+    }
+
+    fn raw_connection(&mut self) -> Result<&mut HostRaw, io::Error> {
+        Ok(&mut self.raw)
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        101 => "Switching Protocols",
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _   => "",
+    }
+}
+
+type HostHandler = Box<dyn Fn(&mut HostConnection) -> Result<()> + Send + Sync>;
+
+struct Route {
+    uri:     String,
+    method:  Method,
+    handler: HostHandler,
+}
+
+impl Route {
+    fn matches(&self, path: &str, method: Method) -> bool {
+        if self.method != method { return false; }
+        match self.uri.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None         => path == self.uri,
+        }
+    }
+}
+
+type WsHandler = Arc<dyn Fn(i32, WsEvent<'_>) -> Result<()> + Send + Sync>;
+
+/// Server HTTP pe PC. Ca `esp_http_server`, tratează conexiunile pe rând,
+/// pe un singur thread; o sesiune WebSocket primeşte thread-ul ei.
+#[derive(Default)]
+pub struct HostHttpServer {
+    routes: Vec<Route>,
+    ws:     Vec<(String, WsHandler)>,
+}
+
+impl HostHttpServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aceeaşi limită ca `max_uri_handlers` pe placă.
+    fn check_room(&self) -> Result<()> {
+        if self.routes.len() + self.ws.len() >= crate::http::MAX_URI_HANDLERS {
+            bail!("HANDLERS_FULL: peste {} rute", crate::http::MAX_URI_HANDLERS);
+        }
+        Ok(())
+    }
+
+    /// Porneşte bucla de accept pe un thread separat; întoarce adresa reală
+    /// (util cu portul 0 în teste).
+    pub fn start(self, addr: impl ToSocketAddrs) -> Result<SocketAddr> {
+        let listener = TcpListener::bind(addr)?;
+        let local = listener.local_addr()?;
+        let routes = Arc::new(self.routes);
+        let ws = self.ws;
+
+        thread::Builder::new()
+            .name("host_httpd".into())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    if let Err(e) = serve_one(&routes, &ws, stream) {
+                        log::warn!("httpd: {e:?}");
+                    }
+                }
+            })?;
+
+        Ok(local)
+    }
+}
+
+fn serve_one(routes: &[Route], ws: &[(String, WsHandler)], stream: TcpStream) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut conn = HostConnection::accept(stream)?;
+    let method = conn.head.method;
+    log::debug!("httpd: {} {}", method_str(method), conn.head.uri);
+
+    let upgrade = conn.head.header("Upgrade").is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    if let Some((_, handler)) = ws.iter().find(|(uri, _)| upgrade && uri == conn.path()) {
+        return ws_accept(conn, handler.clone());
+    }
+
+    let Some(route) = routes.iter().find(|r| r.matches(conn.path(), method)) else {
+        conn.initiate_response(404, None, &[("Content-Type", "text/plain")])?;
+        conn.write_all(b"Not Found")?;
+        return Ok(());
+    };
+
+    if let Err(e) = (route.handler)(&mut conn) {
+        log::error!("handler {}: {e:?}", route.uri);
+        if !conn.responded {
+            conn.initiate_response(500, None, &[("Content-Type", "text/plain")])?;
+            conn.write_all(e.to_string().as_bytes())?;
+        }
+    }
+    conn.flush()?;
+    conn.body.discard()?;
+    Ok(())
+}
+
+impl HttpServer for HostHttpServer {
+    type Conn<'r> = HostConnection;
+    type Error = io::Error;
+
+    fn handler<F>(&mut self, uri: &str, method: Method, f: F) -> Result<()>
+    where
+        F: for<'r> Fn(Request<&mut Self::Conn<'r>>) -> Result<()> + Send + 'static,
+    {
+        self.check_room()?;
+        // `Fn` e apelat doar de thread-ul serverului, dar `Arc` cere `Sync`
+        let f = std::sync::Mutex::new(f);
+        self.routes.push(Route {
+            uri: uri.into(),
+            method,
+            handler: Box::new(move |c| (f.lock().unwrap())(Request::wrap(c))),
+        });
+        Ok(())
+    }
+
+    fn ws_handler<F>(&mut self, uri: &str, f: F) -> Result<()>
+    where
+        F: Fn(i32, WsEvent<'_>) -> Result<()> + Send + Sync + 'static,
+    {
+        self.check_room()?;
+        self.ws.push((uri.into(), Arc::new(f)));
+        Ok(())
+    }
+}
+
+/* ------------ WebSocket (RFC 6455, doar cadre nefragmentate) -------- */
+
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const OP_TEXT:  u8 = 0x1;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING:  u8 = 0x9;
+const OP_PONG:  u8 = 0xA;
+
+static WS_SESSIONS: AtomicI32 = AtomicI32::new(1);
+
+fn ws_accept(mut conn: HostConnection, handler: WsHandler) -> Result<()> {
+    let Some(key) = conn.head.header("Sec-WebSocket-Key").map(str::to_owned) else {
+        conn.initiate_response(400, None, &[])?;
+        return Ok(());
+    };
+    let accept = base64(&sha1(format!("{key}{WS_GUID}").as_bytes()));
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+         Connection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+    );
+    conn.raw.0.write_all(head.as_bytes())?;
+
+    let session = WS_SESSIONS.fetch_add(1, Ordering::Relaxed);
+    let out = Arc::new(Mutex::new(conn.raw.0.try_clone()?));
+    let closed = Arc::new(AtomicBool::new(false));
+    let sender = HostWsSender { out: out.clone(), closed: closed.clone() };
+    handler(session, WsEvent::Open(Box::new(sender)))?;
+
+    let mut reader = conn.body.reader;
+    reader.get_ref().set_read_timeout(None)?;
+    thread::Builder::new().name(format!("ws#{session}")).spawn(move || {
+        while let Ok((op, payload)) = read_frame(&mut reader) {
+            match op {
+                OP_TEXT => {
+                    let text = String::from_utf8_lossy(&payload);
+                    if let Err(e) = handler(session, WsEvent::Text(&text)) {
+                        log::warn!("ws#{session}: {e:?}");
+                    }
+                }
+                OP_PING => { let _ = write_frame(&mut *out.lock().unwrap(), OP_PONG, &payload, false); }
+                OP_CLOSE => {
+                    let _ = write_frame(&mut *out.lock().unwrap(), OP_CLOSE, &payload, false);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        closed.store(true, Ordering::Relaxed);
+        let _ = handler(session, WsEvent::Closed);
+    })?;
+    Ok(())
+}
+
+struct HostWsSender {
+    out:    Arc<Mutex<TcpStream>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl WsSender for HostWsSender {
+    fn send_text(&mut self, text: &str) -> Result<()> {
+        if self.is_closed() {
+            bail!("ws închis");
+        }
+        write_frame(&mut *self.out.lock().unwrap(), OP_TEXT, text.as_bytes(), false)?;
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
+
+fn read_frame(r: &mut impl io::Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut hdr = [0u8; 2];
+    r.read_exact(&mut hdr)?;
+    let op = hdr[0] & 0x0F;
+    let masked = hdr[1] & 0x80 != 0;
+    let len = match hdr[1] & 0x7F {
+        126 => {
+            let mut b = [0u8; 2];
+            r.read_exact(&mut b)?;
+            u16::from_be_bytes(b) as usize
+        }
+        127 => {
+            let mut b = [0u8; 8];
+            r.read_exact(&mut b)?;
+            u64::from_be_bytes(b) as usize
+        }
+        n => n as usize,
+    };
+    if len > 64 * 1024 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "cadru ws prea mare"));
+    }
+    let mut mask = [0u8; 4];
+    if masked {
+        r.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0u8; len];
+    r.read_exact(&mut payload)?;
+    if masked {
+        payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+    }
+    Ok((op, payload))
+}
+
+/// Serverul trimite nemascat, clientul mascat (cu o mască oarecare).
+fn write_frame(w: &mut impl io::Write, op: u8, payload: &[u8], masked: bool) -> io::Result<()> {
+    let mut frame = vec![0x80 | op];
+    let mask_bit = if masked { 0x80 } else { 0 };
+    match payload.len() {
+        n if n < 126    => frame.push(mask_bit | n as u8),
+        n if n < 65_536 => {
+            frame.push(mask_bit | 126);
+            frame.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+    if masked {
+        let mask = [0x37, 0xFA, 0x21, 0x3D];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    } else {
+        frame.extend_from_slice(payload);
+    }
+    w.write_all(&frame)
+}
+
+/// Client WebSocket pentru teste şi simulator.
+pub struct HostWsClient {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl HostWsClient {
+    pub fn connect(addr: SocketAddr, path: &str) -> Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        let head = format!(
+            "GET {path} HTTP/1.1\r\nHost: {addr}\r\nUpgrade: websocket\r\n\
+             Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+             Sec-WebSocket-Version: 13\r\n\r\n"
+        );
+        stream.write_all(head.as_bytes())?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let lines = read_head(&mut reader)?;
+        let first = lines.first().map(String::as_str).unwrap_or("");
+        if first.split_whitespace().nth(1) != Some("101") {
+            bail!("upgrade refuzat: {first}");
+        }
+        // valoarea din RFC 6455 pentru cheia de mai sus
+        let accept = find_header(&parse_headers(&lines[1..]), "Sec-WebSocket-Accept").map(str::to_owned);
+        if accept.as_deref() != Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=") {
+            bail!("Sec-WebSocket-Accept greşit: {accept:?}");
+        }
+        Ok(Self { stream, reader })
+    }
+
+    pub fn send_text(&mut self, text: &str) -> Result<()> {
+        write_frame(&mut self.stream, OP_TEXT, text.as_bytes(), true)?;
+        Ok(())
+    }
+
+    /// Următorul cadru text; `None` la închidere sau după `timeout`.
+    pub fn recv_text(&mut self, timeout: Duration) -> Option<String> {
+        self.reader.get_ref().set_read_timeout(Some(timeout)).ok()?;
+        loop {
+            match read_frame(&mut self.reader).ok()? {
+                (OP_TEXT, payload) => return String::from_utf8(payload).ok(),
+                (OP_CLOSE, _)      => return None,
+                _                  => continue,
+            }
+        }
+    }
+
+    pub fn close(mut self) -> Result<()> {
+        write_frame(&mut self.stream, OP_CLOSE, &[], true)?;
+        Ok(())
+    }
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in msg.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19  => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _       => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let t = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+
+    let mut out = [0u8; 20];
+    for (chunk, word) in out.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+fn base64(data: &[u8]) -> String {
+    const ABC: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ABC[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/* ------------ client ------------------------------------------------- */
+
+/// Client HTTP fără TLS. URL-urile cloud pot fi redirecţionate către un
+/// stand-in local: `HostHttp::new().redirect("https://api.openai.com", "http://127.0.0.1:9000")`.
+#[derive(Clone, Debug, Default)]
+pub struct HostHttp {
+    redirects: Vec<(String, String)>,
+    timeout:   Option<Duration>,
+    connect:   Option<Duration>,
+}
+
+impl HostHttp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn redirect(mut self, from: &str, to: &str) -> Self {
+        self.redirects.push((from.trim_end_matches('/').into(), to.trim_end_matches('/').into()));
+        self
+    }
+
+    pub fn timeout(mut self, t: Duration) -> Self {
+        self.timeout = Some(t);
+        self
+    }
+
+    fn resolve(&self, url: &str) -> String {
+        for (from, to) in &self.redirects {
+            if let Some(rest) = url.strip_prefix(from.as_str()) {
+                return format!("{to}{rest}");
+            }
+        }
+        url.to_owned()
+    }
+}
+
+enum BodyKind {
+    Length(usize),
+    Chunked { left: usize, done: bool },
+    UntilClose,
+}
+
+pub struct HostHttpResponse {
+    status:  u16,
+    headers: Vec<(String, String)>,
+    reader:  BufReader<TcpStream>,
+    kind:    BodyKind,
+}
+
+impl HostHttpResponse {
+    fn read_chunk_size(&mut self) -> Result<usize> {
+        let mut line = String::new();
+        self.reader.read_line(&mut line)?;
+        if line.trim().is_empty() {
+            // CRLF de după chunk-ul anterior
+            line.clear();
+            self.reader.read_line(&mut line)?;
+        }
+        let size = line.trim().split(';').next().unwrap_or("");
+        usize::from_str_radix(size, 16).with_context(|| format!("chunk invalid: {line:?}"))
+    }
+}
+
+impl HttpResponse for HostHttpResponse {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self.kind {
+            BodyKind::Length(0) => Ok(0),
+            BodyKind::Length(left) => {
+                let max = buf.len().min(left);
+                let n = self.reader.read(&mut buf[..max])?;
+                if n == 0 {
+                    // eroare de I/O, ca o conexiune ruptă – `retry` o reîncearcă
+                    let msg = format!("corp trunchiat: lipsesc {left} B");
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg).into());
+                }
+                self.kind = BodyKind::Length(left - n);
+                Ok(n)
+            }
+            BodyKind::Chunked { done: true, .. } => Ok(0),
+            BodyKind::Chunked { left: 0, .. } => {
+                let size = self.read_chunk_size()?;
+                if size == 0 {
+                    self.kind = BodyKind::Chunked { left: 0, done: true };
+                    return Ok(0);
+                }
+                self.kind = BodyKind::Chunked { left: size, done: false };
+                self.read(buf)
+            }
+            BodyKind::Chunked { left, .. } => {
+                let max = buf.len().min(left);
+                let n = self.reader.read(&mut buf[..max])?;
+                if n == 0 {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "chunk trunchiat").into());
+                }
+                self.kind = BodyKind::Chunked { left: left - n, done: false };
+                Ok(n)
+            }
+            BodyKind::UntilClose => Ok(self.reader.read(buf)?),
+        }
+    }
+}
+
+impl HttpClient for HostHttp {
+    fn request(
+        &mut self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<Box<dyn HttpResponse + '_>> {
+        let url = self.resolve(url);
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| anyhow!("HostHttp: doar http:// (fără TLS) – {url}"))?;
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None    => (rest, "/"),
+        };
+        let addr = if host.contains(':') { host.to_owned() } else { format!("{host}:80") };
+
+        let connect = || -> Result<TcpStream> {
+            let stream = match self.connect {
+                Some(t) => {
+                    let sa = addr
+                        .to_socket_addrs()?
+                        .next()
+                        .ok_or_else(|| anyhow!("adresă nerezolvată: {addr}"))?;
+                    TcpStream::connect_timeout(&sa, t)
+                }
+                None => TcpStream::connect(&addr),
+            };
+            stream.with_context(|| format!("connect {addr}"))
+        };
+        let mut stream = connect().map_err(NotSent)?;
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+
+        let mut head = format!("{} {path} HTTP/1.1\r\nHost: {host}\r\n", method_str(method));
+        for (k, v) in headers {
+            if k.eq_ignore_ascii_case("Content-Length") || k.eq_ignore_ascii_case("Host") {
+                continue;
+            }
+            head.push_str(&format!("{k}: {v}\r\n"));
+        }
+        head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", body.len()));
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut reader = BufReader::new(stream);
+        let lines = read_head(&mut reader)?;
+        let (first, rest) = lines
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "răspuns gol"))?;
+        let status = first
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| anyhow!("linie de status invalidă: {first}"))?;
+        let headers = parse_headers(rest);
+
+        let kind = if find_header(&headers, "Transfer-Encoding")
+            .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+        {
+            BodyKind::Chunked { left: 0, done: false }
+        } else if let Some(len) = find_header(&headers, "Content-Length").and_then(|v| v.parse().ok()) {
+            BodyKind::Length(len)
+        } else {
+            BodyKind::UntilClose
+        };
+
+        Ok(Box::new(HostHttpResponse { status, headers, reader, kind }))
+    }
+
+    fn set_timeouts(&mut self, connect: Duration, read: Duration) {
+        self.connect = Some(connect);
+        self.timeout = Some(read);
+    }
+}
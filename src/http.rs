@@ -1,101 +1,251 @@
 use anyhow::{anyhow, Result};
 use embedded_svc::{
-    http::{Method},
+    http::{Headers, Method},
     io::{Read as IoRead, Write as IoWrite},
 };
 
 use std::sync::{Arc, Mutex};  
-use esp_idf_svc::http::server::{EspHttpServer, Request, Connection};
-use include_dir::{include_dir, Dir};
-use std::sync::mpsc::{Receiver, Sender};
+use std::time::Duration;
+use std::sync::mpsc::Receiver;
 
-use esp_idf_svc::hal::i2s::{I2sDriver, I2sTx};
+use crate::assets::{self, Asset};
+use crate::auth::{self, Access, Auth, PairError};
+use crate::audio::{Exchange, Job};
+use crate::azure_tts::{self, Speech, VoiceCatalog, VoiceOptions};
+use crate::backup::{self, BackupError};
+use crate::certs::{self, CertStore, Source};
+use crate::clock::Clock;
+use crate::config::{self, Store};
+use crate::cloud_error::{lang_from, CloudError};
+use crate::drive::{self, Drive};
+use crate::hal::{Connection, HttpServer, Request};
+use crate::mdns::Mdns;
+use crate::queue::Tx;
+use crate::status;
+use crate::updater::Updater;
+use crate::motion::{self, Motion};
+use crate::ota::{self, Ota, OtaError};
+use crate::wifi::{self, WifiManager};
+use crate::ws::{self, Event, Hub};
 
 
 
-static STATIC_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/static");
+/// servită cu 404 pentru orice cale necunoscută
+const NOT_FOUND_PAGE: &str = "404.html";
 
-fn mime_for(path: &str) -> &'static str {
-    match path.rsplit('.').next().unwrap_or("") {
-        "html" => "text/html",
-        "js"   => "text/javascript",
-        "wav"  => "audio/wav",
-        _      => "application/octet-stream",
+/// corpurile JSON mici din rutele de configurare (hostname, ...)
+const MAX_SETTING: usize = 256;
+/// lista de reţele Wi-Fi (cel mult `wifi::MAX_NETWORKS`)
+const MAX_WIFI: usize = 2048;
+
+/// `max_uri_handlers` al serverului principal: peste limită
+/// `httpd_register_uri_handler` dă `HANDLERS_FULL` (şi `HostHttpServer` la
+/// fel). Acum 42: `register_handlers` 21 + `/ws`, tls 4, mdns 2, ota 2,
+/// updater 3, wifi 2, clock 2, config 2, tts 1, backup 2, static 1.
+pub const MAX_URI_HANDLERS: usize = 48;
+
+/// MIME după extensie; textul e servit ca UTF-8
+pub fn mime_for(path: &str) -> &'static str {
+    let ext = path.rsplit_once('.').map(|(_, e)| e).unwrap_or("");
+    match ext.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css"          => "text/css; charset=utf-8",
+        "js" | "mjs"   => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "webmanifest"  => "application/manifest+json",
+        "txt"          => "text/plain; charset=utf-8",
+        "xml"          => "application/xml",
+        "svg"          => "image/svg+xml",
+        "png"          => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif"          => "image/gif",
+        "webp"         => "image/webp",
+        "ico"          => "image/x-icon",
+        "wav"          => "audio/wav",
+        "mp3"          => "audio/mpeg",
+        "ogg"          => "audio/ogg",
+        "woff"         => "font/woff",
+        "woff2"        => "font/woff2",
+        "ttf"          => "font/ttf",
+        "pdf"          => "application/pdf",
+        "wasm"         => "application/wasm",
+        _              => "application/octet-stream",
+    }
+}
+
+/// Ce se întâmplă cu o cerere GET pentru un fişier static.
+#[derive(Debug, PartialEq)]
+pub enum StaticLookup {
+    Found(&'static str),
+    NotFound,
+    /// `..`, `\\`, `%00`, codificare invalidă
+    Rejected,
+}
+
+/// `/chat.html?x=1` → `chat.html`; `/` şi `/dir/` → `index.html` din director;
+/// `/dir` → `dir/index.html` dacă există.
+pub fn resolve_static(uri: &str) -> StaticLookup {
+    let path = uri.split(['?', '#']).next().unwrap_or("");
+    let Some(path) = percent_decode(path) else {
+        return StaticLookup::Rejected;
+    };
+    let rel = path.trim_start_matches('/');
+    if rel.contains(['\\', '\0'])
+        || rel.split('/').any(|seg| seg == ".." || seg == ".")
+        || rel.contains("//")
+    {
+        return StaticLookup::Rejected;
     }
+
+    let candidates = if rel.is_empty() || rel.ends_with('/') {
+        vec![format!("{rel}index.html")]
+    } else {
+        vec![rel.to_owned(), format!("{rel}/index.html")]
+    };
+    candidates
+        .iter()
+        .find_map(|c| assets::find(c))
+        .map_or(StaticLookup::NotFound, |a| StaticLookup::Found(a.path))
 }
 
-pub fn register_handlers(
-    srv: &mut EspHttpServer,
-    tx_audio: Sender<Vec<u8>>,
-    rx_audio: Arc<Mutex<Receiver<(String, String)>>>,
-    i2s_ref: Arc<Mutex<I2sDriver<'static, I2sTx>>>, 
-    tx_tts: Sender<String>,
+fn percent_decode(s: &str) -> Option<String> {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%' {
+            let hex = core::str::from_utf8(b.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(b[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+pub fn register_handlers<S: HttpServer>(
+    srv: &mut S,
+    tx_audio: Tx<Job>,
+    rx_audio: Arc<Mutex<Receiver<Exchange>>>,
+    motion: Arc<Mutex<Motion>>,
+    tx_tts: Tx<Speech>,
+    hub: Arc<Hub>,
+    auth: Arc<Auth>,
 ) -> anyhow::Result<()>{
-    /* -------- GET / (şi alte fişiere statice) ----------------------- */
-    srv.fn_handler("/", Method::Get, |req| -> Result<()> {
-        send_static(req, "index.html")
+    /* -------- preflight CORS – doar originile din allowlist ---------- */
+    srv.handler("/*", Method::Options, {
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(origin) = auth.cors_origin(req.header("Origin"), req.header("Host")) else {
+                let mut resp = req.into_status_response(403)?;
+                resp.flush()?;
+                return Ok(());
+            };
+            let headers = &[
+                ("Access-Control-Allow-Origin",  origin.as_str()),
+                ("Access-Control-Allow-Methods", "GET, POST, PUT, PATCH, DELETE, OPTIONS"),
+                ("Access-Control-Allow-Headers", "Authorization, Content-Type, X-Passphrase"),
+                ("Access-Control-Max-Age",       "600"),
+                ("Vary",                         "Origin"),
+            ];
+            let mut resp = req.into_response(204, None::<&str>, headers)?;
+            resp.flush()?;
+            Ok(())
+        }
     })?;
 
-    srv.fn_handler("/index.html", Method::Get, |req| -> Result<()> {
-        send_static(req, "index.html")
-    })?;
+srv.handler("/hello", Method::Get, {
+    let auth = auth.clone();
+    move |req| -> Result<()> {
+        log::info!("Ping /hello – OK");
+        respond(req, &auth, 200, &[], b"Hello received!")
+    }
+})?;
 
-    srv.fn_handler("/wav-encoder.js", Method::Get, |req| -> Result<()> {
-        send_static(req, "wav-encoder.js")
+/* -------- /api/status – diagnostic pentru dashboard ------------- */
+srv.handler("/api/status", Method::Get, {
+    let (auth, motion, hub) = (auth.clone(), motion.clone(), hub.clone());
+    move |req| -> Result<()> {
+        let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+        // handler-ul rulează pe task-ul httpd
+        status::watch_stack("httpd");
+        send_json(req, &auth, 200, &status::snapshot(&motion, &hub))
+    }
+})?;
+
+/* -------- mişcări / gesturi (control.html) ----------------------- */
+for &(name, ..) in motion::MOVES {
+    let motion = motion.clone();
+    let auth = auth.clone();
+    srv.handler(&format!("/move/{name}"), Method::Get, move |req| -> Result<()> {
+        let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+        motion.lock().unwrap().step(name)?;
+        respond(req, &auth, 200, &[], b"OK")
     })?;
+}
 
-    srv.fn_handler("/transcribe", Method::Options, |req| -> Result<()> {
-        let headers = &[
-            ("Access-Control-Allow-Origin",  "*"),
-            ("Access-Control-Allow-Methods", "POST, OPTIONS"),
-            ("Access-Control-Allow-Headers", "Content-Type"),
-        ];
-        let mut resp = req.into_response(204, None::<&str>, headers)?;
-        resp.flush()?;
-        Ok(())
+for &(name, _) in motion::ACTIONS {
+    let motion = motion.clone();
+    let auth = auth.clone();
+    srv.handler(&format!("/action/{name}"), Method::Get, move |req| -> Result<()> {
+        let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+        motion.lock().unwrap().gesture(name)?;
+        respond(req, &auth, 200, &[], b"OK")
     })?;
+}
 
-srv.fn_handler("/hello", Method::Get, |req| -> Result<()> {
-    let headers = &[("Access-Control-Allow-Origin", "*")];
-    let mut resp = req.into_response(200, None::<&str>, headers)?;
-    IoWrite::write_all(&mut resp, b"Hello received!")?;
-    log::info!("Ping /hello – OK");
-    Ok(())
-})?;
-srv.fn_handler("/send_text", Method::Post, {
+srv.handler("/send_text", Method::Post, {
     let tx_tts = tx_tts.clone();
-    move |mut req| -> anyhow::Result<()> {
-        const HDRS: &[(&str, &str)] = &[
-            ("Access-Control-Allow-Origin",  "*"),
-            ("Access-Control-Allow-Methods", "POST, OPTIONS"),
-            ("Access-Control-Allow-Headers", "Content-Type"),
-        ];
+    let auth = auth.clone();
+    move |req| -> anyhow::Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
 
-        let mut buf = [0u8; 512];
-        let n = embedded_svc::io::Read::read(&mut req, &mut buf)?;
-        let txt = core::str::from_utf8(&buf[..n]).unwrap_or("").trim().to_owned();
+        let buf = read_body(&mut req, MAX_CHAT)?;
+        let raw = core::str::from_utf8(&buf).unwrap_or("").trim();
+        // text simplu, `{"text": "...", "voice": {...}}` sau `{"ssml": "<speak …>"}`
+        let speech = match serde_json::from_str::<serde_json::Value>(raw) {
+            Ok(v) if v.is_object() => match (v["ssml"].as_str(), voice_options(&v)) {
+                (Some(_), Ok(voice)) if voice != VoiceOptions::default() => {
+                    return send_json(req, &auth, 400, &auth_error("bad_request", "ssml conţine deja vocea – fără voice"));
+                }
+                (Some(doc), _) => match azure_tts::check_ssml(doc) {
+                    Ok(()) => Speech::ssml(doc.to_owned()),
+                    Err(e) => return send_json(req, &auth, 400, &auth_error("bad_request", &e.to_string())),
+                },
+                (None, Ok(voice)) => Speech { text: v["text"].as_str().unwrap_or("").trim().to_owned(), voice, ssml: false },
+                (None, Err(e)) => return send_json(req, &auth, 400, &auth_error("bad_request", &e)),
+            },
+            _ => raw.to_owned().into(),
+        };
+        let txt = &speech.text;
 
         log::info!("📝 Text primit de la browser: \"{txt}\"");
 
-        let _ = tx_tts.send(txt);
-        let mut resp = req.into_response(202, None::<&str>, HDRS)?;
-        embedded_svc::io::Write::write_all(&mut resp, b"ACCEPTED")?;
-        Ok(())
+        let _ = tx_tts.send(speech);
+        respond(req, &auth, 202, &[], b"ACCEPTED")
     }
 })?;
 
 
 
 
-const MAX_WAV: usize = 1024 * 1024;  
+const MAX_WAV: usize = 1024 * 1024;
+const MAX_CHAT: usize = 2048;
+const MAX_DRIVE: usize = 128;
 
-#[link_section = ".external_ram.bss"]     
+#[cfg_attr(target_os = "espidf", link_section = ".external_ram.bss")]
 static mut WAV_BUF: [u8; MAX_WAV] = [0; MAX_WAV]; 
 
-srv.fn_handler(
-    "/transcribe",
-    Method::Post,
-    move |mut req| -> Result<()> {
+srv.handler("/transcribe", Method::Post, {
+    let tx_audio = tx_audio.clone();
+    let rx_audio = rx_audio.clone();
+    let tx_tts = tx_tts.clone();
+    let hub = hub.clone();
+    let auth = auth.clone();
+    move |req| -> Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
         let len = req
             .header("Content-Length")
             .and_then(|s| s.parse::<usize>().ok())
@@ -108,51 +258,897 @@ srv.fn_handler(
             return Ok(());
         }
 
-        let wav = unsafe { &mut WAV_BUF[..len] };
+        // serverul tratează o singură cerere odată → acces exclusiv
+        let wav = unsafe { &mut *core::ptr::addr_of_mut!(WAV_BUF) };
+        let wav = &mut wav[..len];
         let mut off = 0;
         while off < len {
             off += IoRead::read(&mut req, &mut wav[off..])?;
         }
 
-        tx_audio.send(wav[..len].into())?;  
+        tx_audio.send(Job::Wav(wav[..len].into()))?;
 
-        let (text, reply) = {
+        let exchange = {
             let guard = rx_audio.lock().unwrap();
             guard.recv()?
         };
 
+        let (text, reply) = match exchange {
+            Ok(pair) => pair,
+            Err(e)   => return send_cloud_error(req, &auth, &e, &tx_tts),
+        };
+
+        hub.publish(&Event::Transcript { text: text.clone() });
+        hub.publish(&Event::Reply { text: reply.clone() });
 
-        let mut resp = req.into_response(200, None::<&str>, &[
-            ("Content-Type","application/json"),
-            ("Access-Control-Allow-Origin","*")
-        ])?;
-        write!(resp, r#"{{"transcript":"{}","reply":"{}"}}"#, text, reply)?;
-        Ok(())
+        // robotul rosteşte replica, pagina doar o afişează
+        let _ = tx_tts.send(reply.clone().into());
+        let body = serde_json::json!({ "transcript": text, "reply": reply });
+        send_json(req, &auth, 200, &body)
     }
-)?;
+})?;
+
+    /* -------- /api/chat – text → ChatGPT (cheia rămâne pe placă) ---- */
+    srv.handler("/api/chat", Method::Post, {
+        let tx_audio = tx_audio.clone();
+        let rx_audio = rx_audio.clone();
+        let tx_tts = tx_tts.clone();
+        let hub = hub.clone();
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+            // `{"text": "...", "speak": false, "voice": {...}}` sau text simplu
+            let buf = read_body(&mut req, MAX_CHAT)?;
+            let raw = core::str::from_utf8(&buf).unwrap_or("").trim();
+            let (text, speak, voice) = match serde_json::from_str::<serde_json::Value>(raw) {
+                Ok(v) if v.is_object() => match voice_options(&v) {
+                    Ok(voice) => (
+                        v["text"].as_str().unwrap_or("").trim().to_owned(),
+                        v["speak"].as_bool().unwrap_or(true),
+                        voice,
+                    ),
+                    Err(e) => return send_json(req, &auth, 400, &auth_error("bad_request", &e)),
+                },
+                _ => (raw.to_owned(), true, VoiceOptions::default()),
+            };
+            if text.is_empty() {
+                let body = serde_json::json!({ "error": { "kind": "bad_request", "message": "text gol" } });
+                return send_json(req, &auth, 400, &body);
+            }
+            log::info!("💬 /api/chat: \"{text}\"");
+
+            tx_audio.send(Job::Text(text))?;
+            let exchange = rx_audio.lock().unwrap().recv()?;
+            match exchange {
+                Ok((text, reply)) => {
+                    hub.publish(&Event::Transcript { text });
+                    hub.publish(&Event::Reply { text: reply.clone() });
+                    if speak {
+                        let _ = tx_tts.send(Speech { text: reply.clone(), voice, ssml: false });
+                    }
+                    send_json(req, &auth, 200, &serde_json::json!({ "reply": reply }))
+                }
+                Err(e) => send_cloud_error(req, &auth, &e, &tx_tts),
+            }
+        }
+    })?;
+
+    srv.handler("/api/chat", Method::Delete, {
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            tx_audio.send(Job::Reset)?;
+            respond(req, &auth, 204, &[], b"")
+        }
+    })?;
+
+    /* -------- /api/drive – joystick (heartbeat ≥ 5 Hz) ------------ */
+    let drive = Drive::start(motion.clone(), hub.clone())?;
+
+    srv.handler("/api/drive", Method::Post, {
+        let drive = drive.clone();
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+            // `{"linear": 0.5, "angular": -0.2}`
+            let buf = read_body(&mut req, MAX_DRIVE)?;
+            let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+            let (Some(linear), Some(angular)) = (v["linear"].as_f64(), v["angular"].as_f64()) else {
+                let body = serde_json::json!({ "error": { "kind": "bad_request", "message": "linear/angular lipsă" } });
+                return send_json(req, &auth, 400, &body);
+            };
+            let (left, right) = drive.set(drive::HTTP, linear as f32, angular as f32);
+            send_json(req, &auth, 200, &serde_json::json!({ "left": left, "right": right }))
+        }
+    })?;
+
+    srv.handler("/api/drive", Method::Delete, {
+        let drive = drive.clone();
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            drive.stop()?;
+            respond(req, &auth, 204, &[], b"")
+        }
+    })?;
+
+    /* -------- /api/pair – asociere cu PIN rostit de robot ---------- */
+    srv.handler("/api/pair/start", Method::Post, {
+        let auth = auth.clone();
+        let tx_tts = tx_tts.clone();
+        move |req| -> Result<()> {
+            if !auth.origin_allowed(req.header("Origin"), req.header("Host")) {
+                return send_json(req, &auth, 403, &auth_error("forbidden", "origine nepermisă"));
+            }
+            let pin = match auth.start_pairing() {
+                Ok(pin) => pin,
+                Err(e) => return send_pair_error(req, &auth, e),
+            };
+            log::info!("🔑 PIN de asociere: {pin}");
+            // cifrele separate – TTS-ul le citeşte una câte una
+            let digits = pin.chars().map(String::from).collect::<Vec<_>>().join(" ");
+            let _ = tx_tts.send(format!("Codul de asociere este {digits}").into());
+            let body = serde_json::json!({ "expires_in": auth::PAIR_TTL.as_secs() });
+            send_json(req, &auth, 202, &body)
+        }
+    })?;
+
+    srv.handler("/api/pair", Method::Post, {
+        let auth = auth.clone();
+        move |mut req| -> Result<()> {
+            if !auth.origin_allowed(req.header("Origin"), req.header("Host")) {
+                return send_json(req, &auth, 403, &auth_error("forbidden", "origine nepermisă"));
+            }
+            let buf = read_body(&mut req, MAX_DRIVE)?;
+            let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+            match auth.pair(v["pin"].as_str().unwrap_or("")) {
+                Ok(token) => send_json(req, &auth, 200, &serde_json::json!({ "token": token })),
+                Err(e) => send_pair_error(req, &auth, e),
+            }
+        }
+    })?;
 
+    // token nou: toate paginile asociate până acum primesc 401
+    srv.handler("/api/pair", Method::Delete, {
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            let token = auth.rotate()?;
+            send_json(req, &auth, 200, &serde_json::json!({ "token": token }))
+        }
+    })?;
+
+    /* -------- /api/cors – originile străine permise ---------------- */
+    srv.handler("/api/cors", Method::Get, {
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            send_json(req, &auth, 200, &serde_json::json!({ "origins": auth.origins() }))
+        }
+    })?;
+
+    srv.handler("/api/cors", Method::Put, {
+        let auth = auth.clone();
+        move |req| -> Result<()> {
+            let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+            // `{"origins": ["http://192.168.1.10:8080"]}`
+            let buf = read_body(&mut req, MAX_CHAT)?;
+            let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+            let Some(list) = v["origins"].as_array() else {
+                return send_json(req, &auth, 400, &auth_error("bad_request", "lipseşte origins"));
+            };
+            let origins: Vec<String> = list.iter().filter_map(|o| o.as_str()).map(str::to_owned).collect();
+            auth.set_origins(&origins)?;
+            send_json(req, &auth, 200, &serde_json::json!({ "origins": auth.origins() }))
+        }
+    })?;
+
+    /* -------- /ws – comenzi şi evenimente în timp real ------------ */
+    ws::register(srv, hub, motion, drive, auth, tx_tts)
+}
+
+/// `/api/tls` – HTTPS pornit/oprit, certificat încărcat, resetat sau
+/// generat pe robot. Toate intră în vigoare la repornire. `names` dă
+/// celelalte nume ale robotului pentru certificatul generat (`<hostname>.local`,
+/// IP-ul din reţea).
+pub fn register_tls<S: HttpServer>(
+    srv: &mut S,
+    auth: Arc<Auth>,
+    certs: Arc<CertStore>,
+    names: impl Fn() -> Vec<String> + Send + 'static,
+) -> Result<()> {
+    fn state(certs: &CertStore) -> serde_json::Value {
+        serde_json::json!({ "https": certs.https_enabled(), "source": certs.source() })
+    }
+    // după o modificare: serverul citeşte certificatul doar la pornire
+    fn changed(certs: &CertStore) -> serde_json::Value {
+        let mut body = state(certs);
+        body["restart_required"] = true.into();
+        body
+    }
+
+    srv.handler("/api/tls", Method::Get, {
+        let (auth, certs) = (auth.clone(), certs.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            send_json(req, &auth, 200, &state(&certs))
+        }
+    })?;
+
+    // `{"https": true}` şi/sau `{"cert": "-----BEGIN CERTIFICATE-----…", "key": "…"}`
+    srv.handler("/api/tls", Method::Put, {
+        let (auth, certs) = (auth.clone(), certs.clone());
+        move |req| -> Result<()> {
+            let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+            let buf = read_body(&mut req, 3 * certs::MAX_PEM)?;
+            let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+            match (v["cert"].as_str(), v["key"].as_str()) {
+                (Some(cert), Some(key)) => {
+                    if let Err(e) = certs.install(cert, key, Source::Uploaded) {
+                        return send_json(req, &auth, 400, &auth_error("bad_request", &e.to_string()));
+                    }
+                }
+                (None, None) => {}
+                _ => return send_json(req, &auth, 400, &auth_error("bad_request", "cert şi key merg împreună")),
+            }
+            if let Some(on) = v["https"].as_bool() {
+                certs.set_https(on)?;
+            }
+            send_json(req, &auth, 200, &changed(&certs))
+        }
+    })?;
+
+    srv.handler("/api/tls", Method::Delete, {
+        let (auth, certs) = (auth.clone(), certs.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            certs.reset()?;
+            send_json(req, &auth, 200, &changed(&certs))
+        }
+    })?;
+
+    // CN = numele cu care a fost accesat robotul (ex. `myrobo.local`)
+    srv.handler("/api/tls/self-signed", Method::Post, move |req| -> Result<()> {
+        let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+        let cn = host_name(req.header("Host")).unwrap_or("myrobo.local").to_owned();
+        let mut san = vec![cn.trim_start_matches('[').trim_end_matches(']').to_owned()];
+        for name in names() {
+            if !san.contains(&name) {
+                san.push(name);
+            }
+        }
+        log::info!("🔒 generez certificat auto-semnat pentru {}…", san.join(", "));
+        let res = certs::self_signed(&cn, &san).and_then(|(cert, key)| certs.install(&cert, &key, Source::SelfSigned));
+        match res {
+            Ok(()) => send_json(req, &auth, 201, &changed(&certs)),
+            Err(e) => send_json(req, &auth, 500, &auth_error("internal", &e.to_string())),
+        }
+    })?;
+
+    Ok(())
+}
+
+/// `/api/mdns` – numele robotului în reţea (`<hostname>.local`).
+pub fn register_mdns<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, mdns: Arc<Mutex<Mdns>>) -> Result<()> {
+    fn state(mdns: &Mdns) -> serde_json::Value {
+        serde_json::json!({ "hostname": mdns.hostname(), "fqdn": format!("{}.local", mdns.hostname()) })
+    }
+
+    srv.handler("/api/mdns", Method::Get, {
+        let (auth, mdns) = (auth.clone(), mdns.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            let body = state(&mdns.lock().unwrap());
+            send_json(req, &auth, 200, &body)
+        }
+    })?;
+
+    // `{"hostname": "robo-bucatarie"}`
+    srv.handler("/api/mdns", Method::Put, move |req| -> Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+        let buf = read_body(&mut req, MAX_SETTING)?;
+        let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+        let mut mdns = mdns.lock().unwrap();
+        match mdns.set_hostname(v["hostname"].as_str().unwrap_or("")) {
+            Ok(()) => {
+                let body = state(&mdns);
+                drop(mdns);
+                send_json(req, &auth, 200, &body)
+            }
+            Err(e) => {
+                drop(mdns);
+                send_json(req, &auth, 400, &auth_error("bad_request", &e.to_string()))
+            }
+        }
+    })?;
+
+    Ok(())
+}
+
+/// `/api/ota` – firmware nou încărcat din pagină sau cu
+/// `curl --data-binary @fw.bin -H "X-Sha256: …"`; robotul reporneşte în el.
+pub fn register_ota<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, ota: Arc<Ota>, hub: Arc<Hub>) -> Result<()> {
+    srv.handler("/api/ota", Method::Get, {
+        let (auth, ota) = (auth.clone(), ota.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            let body = serde_json::json!({
+                "version":  crate::VERSION,
+                "running":  ota.running().ok(),
+                "signed":   ota.signed(),
+                "progress": ota.progress(),
+            });
+            send_json(req, &auth, 200, &body)
+        }
+    })?;
+
+    // corpul = imaginea (`espflash save-image`); `X-Sha256` şi, pentru
+    // firmware compilat cu `OTA_PUBKEY`, `X-Signature` (ECDSA-P256) în hex
+    srv.handler("/api/ota", Method::Post, move |req| -> Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+        let sha256 = req.header("X-Sha256").map(|h| ota::parse_digest(h).ok_or("X-Sha256: 64 de caractere hex"));
+        let signature = req.header("X-Signature").map(|h| ota::parse_signature(h).ok_or("X-Signature: 128 de caractere hex"));
+        let (sha256, signature) = match (sha256.transpose(), signature.transpose()) {
+            (Ok(sha), Ok(sig)) => (sha, sig),
+            (Err(msg), _) | (_, Err(msg)) => return send_json(req, &auth, 400, &auth_error("bad_request", msg)),
+        };
+        let total = req.content_len().map(|n| n as usize);
+
+        log::info!("⬆️ OTA: primesc {} B…", total.map_or("?".into(), |n| n.to_string()));
+        let res = ota.update(
+            |buf| Ok(IoRead::read(&mut req, buf)?),
+            total,
+            sha256,
+            signature,
+            |p| hub.publish(&Event::Ota { state: p.state, received: p.received, total: p.total }),
+        );
+        match res {
+            Ok(progress) => {
+                let mut body = serde_json::to_value(progress)?;
+                body["restarting"] = true.into();
+                send_json(req, &auth, 200, &body)?;
+                ota.restart_later(Duration::from_secs(1));
+                Ok(())
+            }
+            Err(e) => {
+                let (status, kind) = match e {
+                    OtaError::Busy         => (409, "busy"),
+                    OtaError::BadImage(_)  => (400, "bad_request"),
+                    OtaError::TooLarge(_)  => (413, "too_large"),
+                    OtaError::Mismatch     => (422, "checksum_mismatch"),
+                    OtaError::BadSignature => (422, "bad_signature"),
+                    OtaError::Failed(_)    => (500, "internal"),
+                };
+                send_json(req, &auth, status, &auth_error(kind, &e.to_string()))
+            }
+        }
+    })?;
+
+    Ok(())
+}
+
+/// `/api/ota/pull` – actualizări trase de robot de pe serverul flotei.
+pub fn register_updater<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, updater: Arc<Updater>) -> Result<()> {
+    fn state(updater: &Updater) -> serde_json::Value {
+        serde_json::json!({ "settings": updater.settings(), "report": updater.report() })
+    }
+
+    srv.handler("/api/ota/pull", Method::Get, {
+        let (auth, updater) = (auth.clone(), updater.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            send_json(req, &auth, 200, &state(&updater))
+        }
+    })?;
+
+    // `{"url": "https://…/manifest.json", "interval_min": 360, "window": "02:00-05:00"}`;
+    // cheile lipsă rămân neschimbate, `null` le şterge
+    srv.handler("/api/ota/pull", Method::Put, {
+        let (auth, updater) = (auth.clone(), updater.clone());
+        move |req| -> Result<()> {
+            let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+            let buf = read_body(&mut req, MAX_SETTING)?;
+            let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+            let mut new = updater.settings();
+            let text = |key: &str| v[key].as_str().map(str::trim).filter(|s| !s.is_empty()).map(str::to_owned);
+            if v.get("url").is_some() {
+                new.url = text("url");
+            }
+            if v.get("window").is_some() {
+                new.window = text("window");
+            }
+            if let Some(min) = v.get("interval_min") {
+                new.interval_min = min.as_u64().and_then(|m| u32::try_from(m).ok()).unwrap_or(0);
+            }
+            match updater.set_settings(new) {
+                Ok(()) => send_json(req, &auth, 200, &state(&updater)),
+                Err(e) => send_json(req, &auth, 400, &auth_error("bad_request", &e.to_string())),
+            }
+        }
+    })?;
+
+    // verificare imediată, în afara ferestrei; rezultatul apare în GET
+    srv.handler("/api/ota/check", Method::Post, move |req| -> Result<()> {
+        let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+        if !updater.check_now() {
+            return send_json(req, &auth, 409, &auth_error("not_configured", "setează întâi URL-ul manifestului"));
+        }
+        send_json(req, &auth, 202, &state(&updater))
+    })?;
 
     Ok(())
 }
 
-use esp_idf_hal::io::{ErrorType}; 
+/// `/api/wifi` – reţelele cunoscute (fără parole), configuraţia IP şi
+/// legătura curentă.
+pub fn register_wifi<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, wifi: Arc<WifiManager>) -> Result<()> {
+    fn state(wifi: &WifiManager) -> serde_json::Value {
+        let networks: Vec<_> = wifi
+            .networks()
+            .iter()
+            .map(|n| serde_json::json!({ "ssid": n.ssid, "priority": n.priority, "open": n.pass.is_empty(), "last_ok": n.last_ok }))
+            .collect();
+        serde_json::json!({ "link": wifi.link(), "networks": networks, "max": wifi::MAX_NETWORKS, "ip": wifi.ip_config() })
+    }
 
-fn send_static<C>(req: Request<C>, path: &str) -> Result<()>
+    srv.handler("/api/wifi", Method::Get, {
+        let (auth, wifi) = (auth.clone(), wifi.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            send_json(req, &auth, 200, &state(&wifi))
+        }
+    })?;
+
+    // `{"networks": [{"ssid": "Acasa", "pass": "…", "priority": 2}, …]}` înlocuieşte
+    // lista; fără `pass` reţeaua existentă îşi păstrează parola.
+    // `{"ip": {"hostname": "myrobo", "static": {"ip": "192.168.1.50", "gateway": "192.168.1.1",
+    // "netmask": "255.255.255.0", "dns": ["1.1.1.1"]}}}` – `"static": null` = DHCP
+    srv.handler("/api/wifi", Method::Put, move |req| -> Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+        let buf = read_body(&mut req, MAX_WIFI)?;
+        let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+        let bad = |req, msg: &str| send_json(req, &auth, 400, &auth_error("bad_request", msg));
+        if v.get("networks").is_none() && v.get("ip").is_none() {
+            return bad(req, "lipseşte \"networks\" sau \"ip\"");
+        }
+
+        let networks = match v.get("networks") {
+            None => None,
+            Some(serde_json::Value::Array(entries)) => {
+                let old = wifi.networks();
+                let list: Vec<_> = entries
+                    .iter()
+                    .map(|e| {
+                        let ssid = e["ssid"].as_str().unwrap_or("");
+                        let pass = match e["pass"].as_str() {
+                            Some(p) => p,
+                            None => old.iter().find(|n| n.ssid == ssid).map_or("", |n| n.pass.as_str()),
+                        };
+                        let priority = e["priority"].as_u64().unwrap_or(0).min(u8::MAX as u64) as u8;
+                        wifi::Network::new(ssid, pass, priority)
+                    })
+                    .collect();
+                Some(list)
+            }
+            Some(_) => return bad(req, "\"networks\" trebuie să fie o listă"),
+        };
+
+        // cheile lipsă din `ip` rămân neschimbate
+        let ip = match v.get("ip") {
+            None => None,
+            Some(ip) => {
+                let mut cfg = wifi.ip_config();
+                if let Some(h) = ip.get("hostname") {
+                    cfg.hostname = h.as_str().unwrap_or("").trim().to_ascii_lowercase();
+                }
+                if let Some(st) = ip.get("static") {
+                    match serde_json::from_value(st.clone()) {
+                        Ok(st) => cfg.static_ip = st,
+                        Err(e) => return bad(req, &format!("adresă fixă: {e}")),
+                    }
+                }
+                if let Err(e) = wifi::validate_ip(&cfg) {
+                    return bad(req, &e.to_string());
+                }
+                Some(cfg)
+            }
+        };
+
+        if let Some(list) = networks {
+            if let Err(e) = wifi.set_networks(list) {
+                return bad(req, &e.to_string());
+            }
+        }
+        if let Some(cfg) = ip {
+            if let Err(e) = wifi.set_ip_config(cfg) {
+                return send_json(req, &auth, 500, &auth_error("internal", &format!("{e:#}")));
+            }
+        }
+        send_json(req, &auth, 200, &state(&wifi))
+    })?;
+
+    Ok(())
+}
+
+/// `/api/clock` – ora locală, starea SNTP, serverele şi fusul orar.
+pub fn register_clock<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, clock: Arc<Clock>) -> Result<()> {
+    srv.handler("/api/clock", Method::Get, {
+        let (auth, clock) = (auth.clone(), clock.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            send_json(req, &auth, 200, &serde_json::to_value(clock.state())?)
+        }
+    })?;
+
+    // `{"tz": "EET-2EEST,M3.5.0/3,M10.5.0/4", "servers": ["ro.pool.ntp.org"]}`;
+    // cheile lipsă rămân neschimbate
+    srv.handler("/api/clock", Method::Put, move |req| -> Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+        let buf = read_body(&mut req, MAX_SETTING)?;
+        let v: serde_json::Value = serde_json::from_slice(&buf).unwrap_or_default();
+        let mut new = clock.settings();
+        if let Some(tz) = v.get("tz") {
+            new.tz = tz.as_str().unwrap_or("").trim().to_owned();
+        }
+        if let Some(servers) = v.get("servers") {
+            new.servers = servers
+                .as_array()
+                .map(|list| list.iter().map(|s| s.as_str().unwrap_or("").trim().to_owned()).collect())
+                .unwrap_or_default();
+        }
+        match clock.set_settings(new) {
+            Ok(()) => send_json(req, &auth, 200, &serde_json::to_value(clock.state())?),
+            Err(e) => send_json(req, &auth, 400, &auth_error("bad_request", &e.to_string())),
+        }
+    })?;
+
+    Ok(())
+}
+
+/// `/api/config` – toate setările; cheile şi parolele apar ca `config::MASK`.
+pub fn register_config<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, store: Arc<Store>) -> Result<()> {
+    let state = |store: &Store| {
+        serde_json::json!({ "config": store.get().masked(), "restart": store.restart_pending() })
+    };
+
+    srv.handler("/api/config", Method::Get, {
+        let (auth, store) = (auth.clone(), store.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            send_json(req, &auth, 200, &state(&store))
+        }
+    })?;
+
+    // JSON Merge Patch: `{"azure": {"voice": "ro-RO-EmilNeural"}}`; `null`
+    // = valoarea din firmware
+    srv.handler("/api/config", Method::Patch, move |req| -> Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+        let buf = read_body(&mut req, config::MAX_JSON + 1)?;
+        if buf.len() > config::MAX_JSON {
+            return send_json(req, &auth, 413, &auth_error("too_large", "configuraţie prea mare"));
+        }
+        let patch: serde_json::Value = match serde_json::from_slice(&buf) {
+            Ok(v) => v,
+            Err(e) => return send_json(req, &auth, 400, &auth_error("bad_request", &format!("JSON invalid: {e}"))),
+        };
+        match store.patch(&patch) {
+            Ok(changed) => {
+                let mut body = state(&store);
+                body["changed"] = serde_json::to_value(changed)?;
+                send_json(req, &auth, 200, &body)
+            }
+            Err(e) => send_json(req, &auth, 400, &auth_error("bad_request", &format!("{e:#}"))),
+        }
+    })?;
+
+    Ok(())
+}
+
+/// `/api/tts/voices` – vocile Azure din regiunea curentă, din cache-ul
+/// `catalog`; `?lang=ro` sau `?locale=ro-RO` filtrează, `?refresh=1`
+/// descarcă lista din nou. Fără listă încă: 503 cu `Retry-After`.
+pub fn register_tts<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, catalog: Arc<VoiceCatalog>) -> Result<()> {
+    srv.handler("/api/tts/voices", Method::Get, move |req| -> Result<()> {
+        let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+        if query_flag(req.uri(), "refresh") {
+            catalog.invalidate();
+        }
+        let lang = query_param(req.uri(), "lang").map(str::to_owned);
+        let locale = query_param(req.uri(), "locale").map(str::to_owned);
+        let (voices, age) = match catalog.get() {
+            Ok(Some(v)) => v,
+            // prima descărcare rulează în fundal
+            Ok(None) => {
+                let body = auth_error("unavailable", "lista vocilor se descarcă");
+                let headers = [("Content-Type", "application/json"), ("Retry-After", "2")];
+                return respond(req, &auth, 503, &headers, body.to_string().as_bytes());
+            }
+            Err(e) => {
+                let e = CloudError::classify("Azure voices", e);
+                return send_json(req, &auth, e.http_status(), &e.to_json());
+            }
+        };
+        let list: Vec<_> = voices
+            .iter()
+            .filter(|v| lang.as_deref().map_or(true, |l| crate::lang::language_of(&v.locale) == l))
+            .filter(|v| locale.as_deref().map_or(true, |l| v.locale.eq_ignore_ascii_case(l)))
+            .collect();
+        let cfg = config::get();
+        let body = serde_json::json!({
+            "region":  cfg.azure.region,
+            "age_s":   age.as_secs(),
+            "default": { "voice": cfg.azure.voice, "locale": cfg.azure.locale },
+            "voices":  list,
+        });
+        send_json(req, &auth, 200, &body)
+    })
+}
+
+/// `/api/config/export` şi `/api/config/import` – copia de siguranţă (vezi
+/// `backup`); parola, dacă e, vine în `X-Passphrase`, nu în URL.
+pub fn register_backup<S: HttpServer>(srv: &mut S, auth: Arc<Auth>, store: Arc<Store>) -> Result<()> {
+    srv.handler("/api/config/export", Method::Get, {
+        let (auth, store) = (auth.clone(), store.clone());
+        move |req| -> Result<()> {
+            let Some(req) = authorize(req, &auth)? else { return Ok(()) };
+            let passphrase = req.header("X-Passphrase").map(str::to_owned);
+            let doc = backup::export(&store.get(), passphrase.as_deref())?;
+            let body = serde_json::to_vec_pretty(&doc)?;
+            respond(req, &auth, 200, &[
+                ("Content-Type", "application/json"),
+                ("Content-Disposition", "attachment; filename=\"myrobo-config.json\""),
+                ("Cache-Control", "no-store"),
+            ], &body)
+        }
+    })?;
+
+    // `?dry_run=1` – doar raportul, nimic salvat
+    srv.handler("/api/config/import", Method::Post, move |req| -> Result<()> {
+        let Some(mut req) = authorize(req, &auth)? else { return Ok(()) };
+        let dry_run = query_flag(req.uri(), "dry_run");
+        let passphrase = req.header("X-Passphrase").map(str::to_owned);
+        let buf = read_body(&mut req, backup::MAX_BACKUP + 1)?;
+        if buf.len() > backup::MAX_BACKUP {
+            return send_json(req, &auth, 413, &auth_error("too_large", "fişier prea mare"));
+        }
+        let doc: serde_json::Value = match serde_json::from_slice(&buf) {
+            Ok(v) => v,
+            Err(e) => return send_json(req, &auth, 400, &auth_error("bad_request", &format!("JSON invalid: {e}"))),
+        };
+        match backup::import(&store, &doc, passphrase.as_deref(), dry_run) {
+            Ok(report) => send_json(req, &auth, 200, &serde_json::to_value(report)?),
+            Err(e) => {
+                let (status, kind) = match e {
+                    BackupError::Invalid(_)      => (400, "bad_request"),
+                    BackupError::Newer(_)        => (409, "schema_newer"),
+                    BackupError::NeedsPassphrase => (400, "passphrase_required"),
+                    BackupError::WrongPassphrase => (422, "wrong_passphrase"),
+                    BackupError::Failed(_)       => (500, "internal"),
+                };
+                send_json(req, &auth, status, &auth_error(kind, &e.to_string()))
+            }
+        }
+    })?;
+
+    Ok(())
+}
+
+/// `?dry_run=1` / `?dry_run=true` / `?dry_run`
+fn query_flag(uri: &str, name: &str) -> bool {
+    matches!(query_param(uri, name), Some("" | "1" | "true" | "yes"))
+}
+
+/// Valoarea lui `name` din query (`""` pentru `?name` fără `=`).
+fn query_param<'a>(uri: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = uri.split_once('?')?;
+    query.split('&').find_map(|pair| match pair.split_once('=') {
+        Some((k, v)) => (k == name).then_some(v),
+        None => (pair == name).then_some(""),
+    })
+}
+
+/// `"voice"` dintr-un corp JSON (lipsă = setările din `config.azure`).
+fn voice_options(body: &serde_json::Value) -> Result<VoiceOptions, String> {
+    let Some(v) = body.get("voice").filter(|v| !v.is_null()) else { return Ok(VoiceOptions::default()) };
+    let opts: VoiceOptions = serde_json::from_value(v.clone()).map_err(|e| format!("voice: {e}"))?;
+    opts.validate().map_err(|e| format!("{e:#}"))?;
+    Ok(opts)
+}
+
+/// Serverul de pe portul 80 când e pornit HTTPS: orice cerere → aceeaşi
+/// adresă pe `https://` (301 pentru GET, 308 păstrează metoda şi corpul).
+pub fn register_redirect<S: HttpServer>(srv: &mut S, https_port: u16) -> Result<()> {
+    for method in [Method::Get, Method::Post, Method::Put, Method::Delete] {
+        srv.handler("/*", method, move |req| -> Result<()> {
+            let Some(host) = host_name(req.header("Host")).map(str::to_owned) else {
+                let mut resp = req.into_status_response(400)?;
+                IoWrite::write_all(&mut resp, b"Bad Request")?;
+                return Ok(());
+            };
+            let port = if https_port == 443 { String::new() } else { format!(":{https_port}") };
+            let location = format!("https://{host}{port}{}", req.uri());
+            let status = if method == Method::Get { 301 } else { 308 };
+            let mut resp = req.into_response(status, None, &[("Location", location.as_str())])?;
+            resp.flush()?;
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// `Host` fără port: `myrobo.local:80` → `myrobo.local`, `[::1]:80` → `[::1]`.
+fn host_name(host: Option<&str>) -> Option<&str> {
+    let host = host?.trim();
+    let name = match host.rfind(':') {
+        Some(i) if !host[i..].contains(']') => &host[..i],
+        _ => host,
+    };
+    (!name.is_empty()).then_some(name)
+}
+
+/// Fişierele din `static/`; ultima rută înregistrată – httpd alege primul
+/// handler potrivit, iar `/*` le-ar prinde pe toate.
+pub fn register_static<S: HttpServer>(srv: &mut S) -> Result<()> {
+    srv.handler("/*", Method::Get, |req| -> Result<()> {
+        match resolve_static(req.uri()) {
+            StaticLookup::Found(path) => send_static(req, path, 200),
+            StaticLookup::NotFound => {
+                log::info!("404 {}", req.uri());
+                send_static(req, NOT_FOUND_PAGE, 404)
+            }
+            StaticLookup::Rejected => {
+                log::warn!("cale respinsă: {}", req.uri());
+                let mut resp = req.into_status_response(400)?;
+                IoWrite::write_all(&mut resp, b"Bad Request")?;
+                Ok(())
+            }
+        }
+    })?;
+
+    Ok(())
+}
+
+/// gzip dacă browser-ul acceptă, 304 dacă are deja versiunea curentă
+fn send_static<C>(req: Request<C>, path: &str, status: u16) -> Result<()>
 where
-    C: Connection + IoWrite + ErrorType,
-    <C as ErrorType>::Error: std::error::Error + Send + Sync + 'static,
+    C: Connection,
+    C::Error: std::error::Error + Send + Sync + 'static,
 {
-    let file = STATIC_DIR
-        .get_file(path)
-        .ok_or_else(|| anyhow!("file not found"))?;
-
-    let mime = mime_for(path);
-    let headers = &[
-        ("Content-Type", mime),
-        ("Access-Control-Allow-Origin", "*"),
+    let asset: &Asset = assets::find(path).ok_or_else(|| anyhow!("file not found: {path}"))?;
+
+    let gzip = asset.gzip && assets::accepts_gzip(req.header("Accept-Encoding"));
+    let etag = asset.etag(gzip);
+    let mut headers = vec![
+        ("Content-Type", mime_for(path)),
+        ("Cache-Control", asset.cache_control()),
+        ("ETag", etag.as_str()),
+        ("Vary", "Accept-Encoding"),
     ];
 
-    let mut resp = req.into_response(200, None::<&str>, headers)?;
-    IoWrite::write_all(&mut resp, file.contents())?;
+    if status == 200 && assets::etag_matches(req.header("If-None-Match"), asset) {
+        let mut resp = req.into_response(304, None::<&str>, &headers)?;
+        resp.flush()?;
+        return Ok(());
+    }
+
+    let body = if gzip {
+        headers.push(("Content-Encoding", "gzip"));
+        std::borrow::Cow::Borrowed(asset.data)
+    } else {
+        asset.decoded()
+    };
+
+    let mut resp = req.into_response(status, None::<&str>, &headers)?;
+    IoWrite::write_all(&mut resp, &body)?;
+    Ok(())
+}
+
+/// Corpul cererii, cel mult `max` octeţi (restul e ignorat).
+fn read_body<R>(req: &mut R, max: usize) -> Result<Vec<u8>>
+where
+    R: IoRead,
+    R::Error: std::error::Error + Send + Sync + 'static,
+{
+    let mut buf = vec![0u8; max];
+    let mut n = 0;
+    while n < buf.len() {
+        match IoRead::read(req, &mut buf[n..])? {
+            0 => break,
+            k => n += k,
+        }
+    }
+    buf.truncate(n);
+    Ok(buf)
+}
+
+/// Status + corp; `Access-Control-Allow-Origin` doar pentru originile din allowlist.
+fn respond<C>(req: Request<C>, auth: &Auth, status: u16, headers: &[(&str, &str)], body: &[u8]) -> Result<()>
+where
+    C: Connection,
+    C::Error: std::error::Error + Send + Sync + 'static,
+{
+    let cors = auth.cors_origin(req.header("Origin"), req.header("Host"));
+    let mut all = headers.to_vec();
+    if let Some(origin) = &cors {
+        all.push(("Access-Control-Allow-Origin", origin.as_str()));
+        all.push(("Vary", "Origin"));
+    }
+    let mut resp = req.into_response(status, None, &all)?;
+    IoWrite::write_all(&mut resp, body)?;
     Ok(())
 }
+
+/// 403 pentru origini străine, 401 fără token valid; `None` = s-a răspuns deja.
+fn authorize<C>(req: Request<C>, auth: &Auth) -> Result<Option<Request<C>>>
+where
+    C: Connection,
+    C::Error: std::error::Error + Send + Sync + 'static,
+{
+    let access = auth.check(req.header("Authorization"), req.header("Origin"), req.header("Host"));
+    let (status, body) = match access {
+        Access::Allowed      => return Ok(Some(req)),
+        Access::Forbidden    => (403, auth_error("forbidden", "origine nepermisă")),
+        Access::Unauthorized => (401, auth_error("unauthorized", "token lipsă sau greşit – asociază pagina din /pair.html")),
+    };
+    log::warn!("{status} {}", req.uri());
+    let json = body.to_string();
+    respond(req, auth, status, &[
+        ("Content-Type", "application/json"),
+        ("WWW-Authenticate", "Bearer"),
+    ], json.as_bytes())?;
+    Ok(None)
+}
+
+fn auth_error(kind: &str, message: &str) -> serde_json::Value {
+    serde_json::json!({ "error": { "kind": kind, "message": message } })
+}
+
+/// `PairError` → status; blocarea vine cu `Retry-After`.
+fn send_pair_error<C>(req: Request<C>, auth: &Auth, e: PairError) -> Result<()>
+where
+    C: Connection,
+    C::Error: std::error::Error + Send + Sync + 'static,
+{
+    let (status, body, retry) = match e {
+        PairError::WrongPin        => (401, auth_error("unauthorized", "PIN greşit"), None),
+        PairError::NotStarted      => (409, auth_error("conflict", "nicio asociere în curs"), None),
+        PairError::TooManyAttempts => (429, auth_error("rate_limited", "prea multe PIN-uri greşite – asocierea e blocată"), None),
+        PairError::Locked(left)    => {
+            let secs = left.as_secs() + 1;
+            (429, auth_error("rate_limited", &format!("asocierea e blocată încă {secs} s")), Some(secs.to_string()))
+        }
+    };
+    let mut headers = vec![("Content-Type", "application/json")];
+    if let Some(secs) = &retry {
+        headers.push(("Retry-After", secs.as_str()));
+    }
+    respond(req, auth, status, &headers, body.to_string().as_bytes())
+}
+
+fn send_json<C>(req: Request<C>, auth: &Auth, status: u16, body: &serde_json::Value) -> Result<()>
+where
+    C: Connection,
+    C::Error: std::error::Error + Send + Sync + 'static,
+{
+    respond(req, auth, status, &[("Content-Type", "application/json")], body.to_string().as_bytes())
+}
+
+/// Status + JSON cu eroarea; robotul spune ce s-a întâmplat, în limba browser-ului.
+fn send_cloud_error<C>(req: Request<C>, auth: &Auth, e: &CloudError, tx_tts: &Tx<Speech>) -> Result<()>
+where
+    C: Connection,
+    C::Error: std::error::Error + Send + Sync + 'static,
+{
+    let lang = lang_from(req.header("Accept-Language"));
+    let spoken = e.spoken(lang);
+    let _ = tx_tts.send(spoken.into());
+
+    let mut body = e.to_json();
+    body["reply"] = spoken.into();
+    let retry = e.retry_after().map(|d| d.as_secs().max(1).to_string());
+    let mut headers = vec![("Content-Type", "application/json")];
+    if let Some(r) = &retry {
+        headers.push(("Retry-After", r.as_str()));
+    }
+    respond(req, auth, e.http_status(), &headers, body.to_string().as_bytes())
+}
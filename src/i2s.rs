@@ -1,6 +1,6 @@
 use anyhow::Result;
 use esp_idf_svc::hal::{
-    gpio::AnyIOPin,
+    gpio::{AnyIOPin, AnyOutputPin},
     i2s::{
         config::{
             Config as CoreCfg, DataBitWidth, SlotMode, StdClkConfig, StdConfig,
@@ -11,18 +11,20 @@ use esp_idf_svc::hal::{
     peripherals::Peripherals,
 };
 
+use crate::config::Pins;
+
 // src/i2s.rs
-pub fn init() -> Result<I2sDriver<'static, I2sTx>> {
+pub fn init(pins: &Pins) -> Result<I2sDriver<'static, I2sTx>> {
     // Wi-Fi a „consumat” deja Peripherals; aici doar le „împrumutăm” forţat
     // în loc de steal()
     let p = unsafe { Peripherals::new() };
 
 
 
-    // pinii “cruzi”
-    let bclk = p.pins.gpio27;            // IN/OUT
-    let dout = p.pins.gpio26;            // OUT (DAT)
-    let ws   = p.pins.gpio25;            // IN/OUT (LRCK)
+    // pinii din `config` (validaţi ca ieşiri, nefolosiţi de altcineva)
+    let bclk = unsafe { AnyIOPin::new(pins.i2s_bclk as i32) };      // IN/OUT
+    let dout = unsafe { AnyOutputPin::new(pins.i2s_dout as i32) };  // OUT (DAT)
+    let ws   = unsafe { AnyIOPin::new(pins.i2s_ws as i32) };        // IN/OUT (LRCK)
     let mclk = None::<AnyIOPin>;                    // nu folosim MCLK → None
 
     let clk_cfg  = StdClkConfig::from_sample_rate_hz(16_000);
@@ -0,0 +1,58 @@
+//! Limba unei replici, fără dicţionare: literele specifice şi cele mai
+//! frecvente cuvinte ale fiecărei limbi. Destul ca să aleagă vocea TTS
+//! (`config.azure.voices`); la un text prea scurt sau ambiguu – `None`.
+
+/// limba → cuvinte foarte frecvente (scrise cu litere mici)
+const WORDS: [(&str, &[&str]); 6] = [
+    ("ro", &["şi", "și", "si", "este", "sunt", "nu", "pe", "cu", "în", "care", "mai", "pentru", "eu", "tu", "să", "ce", "bună", "foarte"]),
+    ("en", &["the", "and", "is", "are", "you", "to", "of", "it", "that", "this", "with", "for", "what", "hello", "i", "can"]),
+    ("fr", &["le", "les", "et", "est", "vous", "je", "une", "des", "pour", "pas", "que", "bonjour", "avec", "c'est", "il"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "ich", "sie", "ein", "eine", "mit", "zu", "hallo", "wie", "du"]),
+    ("es", &["el", "los", "las", "y", "es", "que", "por", "para", "una", "con", "hola", "está", "muy", "pero", "yo"]),
+    ("it", &["il", "gli", "e", "è", "che", "non", "per", "una", "sono", "con", "ciao", "della", "anche", "io"]),
+];
+
+/// litere care apar (aproape) doar într-o limbă
+const LETTERS: [(&str, &str); 5] = [
+    ("ro", "ăşșţț"),
+    ("de", "äöüß"),
+    ("fr", "çêœë"),
+    ("es", "ñ¿¡"),
+    ("it", "ìò"),
+];
+
+/// Codul ISO 639-1 al limbii dominante din `text`.
+pub fn detect(text: &str) -> Option<&'static str> {
+    // alfabetul chirilic decide singur
+    let cyrillic = text.chars().filter(|c| ('\u{400}'..='\u{4ff}').contains(c)).count();
+    if cyrillic * 2 > text.chars().filter(|c| c.is_alphabetic()).count() {
+        return Some(if text.contains(['і', 'ї', 'є', 'ґ']) { "uk" } else { "ru" });
+    }
+
+    let lower = text.to_lowercase();
+    let mut scores = [0usize; WORDS.len()];
+    for word in lower.split(|c: char| !c.is_alphabetic() && c != '\'') {
+        for (score, (_, words)) in scores.iter_mut().zip(WORDS) {
+            if words.contains(&word) {
+                *score += 1;
+            }
+        }
+    }
+    for c in lower.chars() {
+        for (lang, letters) in LETTERS {
+            if letters.contains(c) {
+                let i = WORDS.iter().position(|(l, _)| *l == lang).unwrap();
+                scores[i] += 2;
+            }
+        }
+    }
+
+    let (best, &top) = scores.iter().enumerate().max_by_key(|(_, s)| **s)?;
+    let ambiguous = scores.iter().enumerate().any(|(i, s)| i != best && *s == top);
+    (top >= 2 && !ambiguous).then_some(WORDS[best].0)
+}
+
+/// `"en-US"` → `"en"`
+pub fn language_of(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
@@ -0,0 +1,39 @@
+// ===================== lib.rs =====================
+//! Logica asistentului. Ce depinde direct de ESP-IDF e sub
+//! `target_os = "espidf"`; restul compilează şi pe PC (`cargo test`).
+
+pub mod assets;
+pub mod audio;
+pub mod auth;
+pub mod azure_auth;
+pub mod azure_tts;
+pub mod backup;
+pub mod certs;
+pub mod clock;
+pub mod cloud_error;
+pub mod config;
+pub mod drive;
+pub mod earcon;
+pub mod hal;
+pub mod lang;
+pub mod http;
+pub mod mdns;
+pub mod motion;
+pub mod motors;
+pub mod openai;
+pub mod ota;
+pub mod queue;
+pub mod retry;
+pub mod servo;
+pub mod status;
+pub mod tts_text;
+pub mod updater;
+pub mod util;
+pub mod wifi;
+pub mod ws;
+
+#[cfg(target_os = "espidf")]
+pub mod i2s;
+
+/// versiunea firmware-ului (din `Cargo.toml`)
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,115 +1,185 @@
 // ===================== main.rs =====================
+#[cfg(target_os = "espidf")]
 use anyhow::Result;
-use esp_idf_hal::peripherals::Peripherals;
+#[cfg(target_os = "espidf")]
+use esp_idf_hal::{gpio::AnyOutputPin, peripherals::Peripherals};
+#[cfg(target_os = "espidf")]
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
     http::server::EspHttpServer,
     log::EspLogger,
     nvs::EspDefaultNvsPartition,
     sys::link_patches,
-    wifi::{BlockingWifi, ClientConfiguration, Configuration, EspWifi},
+    tls::X509,
+    wifi::{BlockingWifi, EspWifi, WifiEvent},
 };
 
-use heapless::String as HString;
+#[cfg(target_os = "espidf")]
 use log::{error, info};
+#[cfg(target_os = "espidf")]
 use std::{
-    fmt::Write as _,
     sync::{mpsc, Arc, Mutex},
     thread,
     time::Duration,
 };
 
+#[cfg(target_os = "espidf")]
 use esp_idf_svc::http::server::Configuration as HttpCfg;
-mod audio;
-mod http;
-mod i2s;
-mod openai;
-mod azure_tts;
-mod servo;
-mod motors;
-
-use servo::{DualServo, ServoId};
-use motors::{L9110S, MotorId};
+#[cfg(target_os = "espidf")]
+use esp32_hello_world::{
+    audio, auth::Auth, azure_auth::AzureAuth, azure_tts, certs::{CertStore, Source}, clock::Clock, config::{self, Section, Store}, earcon,
+    hal::{EspAdvertiser, EspFirmware, EspHttp, EspKv, EspRadio, EspTimeSync}, http, i2s, mdns::Mdns, motion::Motion,
+    ota::{self, Ota}, queue, updater::Updater, status::{self, WifiInfo}, motors::L9110S, retry, servo::DualServo,
+    wifi::{Backoff, Link, WifiManager},
+    ws::{Event, Hub},
+};
 
 /* ------------ date Wi-Fi -------------------------------------------- */
-const STA_SSID: &str = "Constantin)";
-const STA_PASS: &str = "11111111";
+/// cât aşteaptă pornirea prima conexiune; după aceea merge şi fără
+#[cfg(target_os = "espidf")]
+const WIFI_FIRST: Duration = Duration::from_secs(30);
 
 /* ------------ iniţializare STA -------------------------------------- */
-fn init_sta() -> Result<Box<BlockingWifi<EspWifi<'static>>>> {
-    let per   = Peripherals::take()?;
-    let sys   = EspSystemEventLoop::take()?;
-    let nvs   = EspDefaultNvsPartition::take()?;
-    let modem = per.modem;
-
-    let mut ssid: HString<32> = HString::new();
-    ssid.push_str(STA_SSID).unwrap();
-    let mut pass: HString<64> = HString::new();
-    pass.push_str(STA_PASS).unwrap();
-
-    let wifi_drv = EspWifi::new(modem, sys.clone(), Some(nvs))?;
-    let mut wifi = BlockingWifi::wrap(wifi_drv, sys)?;
-
-    wifi.set_configuration(&Configuration::Client(ClientConfiguration {
-        ssid,
-        password: pass,
-        ..Default::default()
-    }))?;
-
-    wifi.start()?;
-    wifi.connect()?;
-    wifi.wait_netif_up()?;               // DHCP gata
-
-    // info
-    let ip  = wifi.wifi().sta_netif().get_ip_info()?.ip;
-    let mac = wifi.wifi().sta_netif().get_mac()?;
-    let mut mac_s = HString::<18>::new();
-    write!(
-        mac_s,
-        "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
-        mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]
-    )
-    .unwrap();
-    info!("IP  : {ip}");
-    info!("MAC : {mac_s}");
-    println!("Copiază în front-end ➜ const BACKEND_HOST = \"http://{ip}\";");
-
-    Ok(Box::new(wifi))
+/// Managerul de reţele; reconectarea porneşte din `StaDisconnected`.
+/// Lista goală (prima pornire) primeşte reţeaua din `config`.
+#[cfg(target_os = "espidf")]
+fn init_wifi(nvs: EspDefaultNvsPartition, seed: &config::Wifi) -> Result<Arc<WifiManager>> {
+    let per = Peripherals::take()?;
+    let sys = EspSystemEventLoop::take()?;
+
+    let drv   = EspWifi::new(per.modem, sys.clone(), Some(nvs.clone()))?;
+    let radio = EspRadio::new(BlockingWifi::wrap(drv, sys.clone())?)?;
+    let wifi  = WifiManager::new(radio, EspKv::new(nvs, "myrobo")?, Backoff::DEFAULT)?;
+    if wifi.networks().is_empty() && !seed.ssid.is_empty() {
+        wifi.add_network(&seed.ssid, &seed.pass, 0)?;
+    }
+    let wifi = wifi.start()?;
+
+    let weak = Arc::downgrade(&wifi);
+    let sub = sys.subscribe::<WifiEvent, _>(move |event| {
+        if let (WifiEvent::StaDisconnected(_), Some(wifi)) = (event, weak.upgrade()) {
+            wifi.link_lost();
+        }
+    })?;
+    std::mem::forget(sub);               // abonat cât rulează firmware-ul
+    Ok(wifi)
 }
 /* -------------------------------------------------------------------- */
 
+#[cfg(not(target_os = "espidf"))]
+fn main() {
+    eprintln!("Firmware-ul rulează doar pe ESP32 – pe PC rulaţi `cargo test`.");
+}
+
+#[cfg(target_os = "espidf")]
 fn main() -> Result<()> {
     link_patches();
     EspLogger::initialize_default();
 
-    // 1️⃣  Wi-Fi  (blocant până obţine IP)
-    let wifi: &'static mut BlockingWifi<_> = Box::leak(init_sta()?);
+    // `OTA_PUBKEY` la compilare → se acceptă doar imagini semnate (ECDSA-P256)
+    let ota_key = option_env!("OTA_PUBKEY").map(ota::public_key).transpose()?;
+    let ota = Arc::new(Ota::new(EspFirmware::new(), ota_key));
+    ota.guard(ota::HEALTH_TIMEOUT)?;
+
+    // 0️⃣a setările (`/api/config`): cheile cloud, vocea, pinii, reţeaua de pornire
+    let nvs = EspDefaultNvsPartition::take()?;
+    let config = Arc::new(Store::load(EspKv::new(nvs.clone(), "myrobo")?)?);
+    let pins = config.get().pins.clone();
+
+    // 1️⃣  Wi-Fi – prima conexiune, apoi thread-ul `wifi` se ocupă de reconectări
+    let wifi = init_wifi(nvs.clone(), &config.get().wifi)?;
+    if !wifi.wait_up(WIFI_FIRST) {
+        log::warn!("📶 fără reţea după {} s – pornesc oricum", WIFI_FIRST.as_secs());
+    }
+
+    // 1️⃣a ceasul: fusul orar din NVS acum, SNTP când există reţea
+    let clock = Arc::new(Clock::new(EspTimeSync::new(), EspKv::new(nvs.clone(), "myrobo")?)?);
+    if wifi.link().is_up() {
+        if let Err(e) = clock.start() {
+            error!("SNTP: {e:?}");
+        }
+    }
+
+    // 1️⃣b token API (generat la prima pornire) + allowlist CORS; certificatul HTTPS
+    let auth  = Arc::new(Auth::load(EspKv::new(nvs.clone(), "myrobo")?)?);
+    let certs = Arc::new(CertStore::new(EspKv::new(nvs.clone(), "myrobo")?));
 
     // 2️⃣  I²S + test TTS
-    let i2s = Arc::new(std::sync::Mutex::new(i2s::init()?));
+    let i2s = Arc::new(std::sync::Mutex::new(i2s::init(&pins)?));
+
+    // 2️⃣b motoare + servo (LEDC) – acelaşi „împrumut” ca în i2s::init;
+    // `config::Pins::validate` garantează ieşiri distincte
+    let motion = {
+        let mut p = unsafe { Peripherals::new() };
+        let pin = |gpio: u8| unsafe { AnyOutputPin::new(gpio as i32) };
+        let motors = L9110S::new(
+            &mut p.ledc,
+            pin(pins.motor_left[0]), pin(pins.motor_left[1]),
+            pin(pins.motor_right[0]), pin(pins.motor_right[1]),
+        )?;
+        let servos = DualServo::new(&mut p.ledc, pin(pins.servo_left), pin(pins.servo_right))?;
+        Arc::new(Mutex::new(Motion::new(motors, servos)))
+    };
 
     // 3️⃣  canale WAV / text
-    let (tx_http2audio, rx_http2audio) = mpsc::channel::<Vec<u8>>();
-    let (tx_audio2http, rx_audio2http) = mpsc::channel::<(String, String)>();
+    let (tx_http2audio, rx_http2audio) = queue::channel::<audio::Job>("audio");
+    let (tx_audio2http, rx_audio2http) = mpsc::channel::<audio::Exchange>();
     let rx_audio2http = Arc::new(Mutex::new(rx_audio2http));
 
     /* înainte de canalele WAV / text existente */  
-    let (tx_tts, rx_tts) = mpsc::channel::<String>();
+    let (tx_tts, rx_tts) = queue::channel::<azure_tts::Speech>("tts");
 
     const TTS_STACK: usize = 24 * 1024;            // 24 KB – suficient pentru TLS
 
+    // evenimente pentru clienţii /ws
+    let hub = Hub::start();
+
+    // setări schimbate: conversaţie nouă cu prompt-ul nou, reţeaua nouă în
+    // listă, paginile deschise anunţate; restul se citeşte la fiecare folosire
+    {
+        let (tx_audio, wifi, hub) = (tx_http2audio.clone(), wifi.clone(), hub.clone());
+        config.subscribe(move |cfg, changed| {
+            if changed.contains(&Section::Openai) {
+                let _ = tx_audio.send(audio::Job::Reset);
+            }
+            if changed.contains(&Section::Wifi) && !cfg.wifi.ssid.is_empty() {
+                let priority = wifi.networks().iter().find(|n| n.ssid == cfg.wifi.ssid).map_or(0, |n| n.priority);
+                if let Err(e) = wifi.add_network(&cfg.wifi.ssid, &cfg.wifi.pass, priority) {
+                    log::warn!("📶 reţeaua din setări: {e:#}");
+                }
+            }
+            hub.publish(&Event::Config { changed: changed.to_vec() });
+        });
+    }
+
+    // token Azure comun workerului TTS şi listei de voci, cu conexiunea lui
+    let azure_auth = Arc::new(AzureAuth::new(EspHttp::new()));
+    // aceeaşi conexiune HTTPS de la o replică la alta (keep-alive); lista
+    // vocilor e pe acelaşi host şi o descarcă thread-ul ei, care aşteaptă
+    // replica în curs – httpd nu aşteaptă niciodată conexiunea
+    let tts_http = Arc::new(Mutex::new(EspHttp::with_buffers(2048)));
+
     {
         let i2s_ref = i2s.clone();
+        let (hub, azure_auth, tts_http) = (hub.clone(), azure_auth.clone(), tts_http.clone());
         std::thread::Builder::new()
             .name("tts_worker".into())
             .stack_size(TTS_STACK)                  // 👈 stack mai mare
             .spawn(move || {
-                while let Ok(txt) = rx_tts.recv() {
-                    log::info!("🔊 TTS worker: \"{txt}\"");
+                status::watch_stack("tts_worker");
+                while let Some(speech) = azure_tts::next_speech(&rx_tts, &azure_auth) {
+                    log::info!("🔊 TTS worker: \"{}\"", speech.text);
+                    hub.publish(&Event::TtsStart { text: speech.caption() });
                     let mut i2s = i2s_ref.lock().unwrap();
-                    if let Err(e) = azure_tts::tts_and_play(&mut *i2s, &txt) {
+                    let mut http = tts_http.lock().unwrap();
+                    if let Err(e) = azure_tts::speak(&mut *http, &azure_auth, &mut *i2s, &speech) {
                         log::error!("tts_and_play error: {:?}", e);
+                        // Azure căzut de mai multe ori – măcar un semnal sonor
+                        if retry::AZURE.is_open() {
+                            let _ = earcon::play_offline(&mut *i2s);
+                        }
                     }
+                    hub.publish(&Event::TtsStop);
                 }
             })
             .unwrap();      // dacă nu porneşte vrem panic în build-time
@@ -119,80 +189,171 @@ fn main() -> Result<()> {
     {
         let rx_audio2http = rx_audio2http.clone();
         thread::spawn(move || {
-
-            let _ = audio::audio_task(rx_http2audio, tx_audio2http);
+            status::watch_stack("audio");
+            audio::audio_task(rx_http2audio, tx_audio2http, || Ok(EspHttp::new()));
 
             drop(rx_audio2http);     // nu se atinge niciodată, dar linter-ul e fericit
         });
     }
 
     // 4️⃣  HTTP server – retry până porneşte
+    // HTTPS e opţional (`PUT /api/tls {"https": true}` + repornire)
+    let https = certs.https_enabled();
+
+    // actualizări de pe serverul flotei (`PUT /api/ota/pull`), acelaşi TLS ca openai.rs
+    let updater = Updater::new(ota.clone(), EspHttp::new(), EspKv::new(nvs.clone(), "myrobo")?)?.start()?;
+    // lista vocilor Azure pentru `/api/tts/voices`, descărcată în fundal la prima cerere
+    let voices = Arc::new(azure_tts::VoiceCatalog::new(tts_http, azure_auth));
+
+    // mDNS: myrobo.local (hostname din NVS) + `_http._tcp`
+    let mdns = Mdns::start(
+        EspAdvertiser::take()?,
+        EspKv::new(nvs, "myrobo")?,
+        if https { 443 } else { 80 },
+        https,
+    )?;
+    let mdns = Arc::new(Mutex::new(mdns));
+
+    // conectivitate nouă: anunţ mDNS cu IP-ul nou; eşecurile cloud de până
+    // acum erau ale Wi-Fi-ului; SNTP, dacă nu pornise. Serverul HTTP ascultă
+    // pe orice adresă – doar adresa nouă în log.
+    {
+        let (mdns, clock) = (mdns.clone(), clock.clone());
+        let scheme = if https { "https" } else { "http" };
+        wifi.subscribe(move |link| match link {
+            Link::Up { ssid, ip, .. } => {
+                info!("📶 {ssid} – {scheme}://{ip}/");
+                retry::OPENAI.reset();
+                retry::AZURE.reset();
+                if let Err(e) = mdns.lock().unwrap().refresh() {
+                    log::warn!("mDNS: {e:?}");
+                }
+                if let Err(e) = clock.start() {
+                    log::warn!("SNTP: {e:?}");
+                }
+            }
+            _ => log::warn!("📶 fără reţea – aştept reconectarea"),
+        });
+    }
     loop {
-        let cfg = HttpCfg {
-            max_uri_handlers: 16,
-            stack_size: 8192,
+        let mut cfg = HttpCfg {
+            max_uri_handlers: http::MAX_URI_HANDLERS,
+            stack_size: if https { 10240 } else { 8192 },  // handshake-ul TLS cere stivă
+            uri_match_wildcard: true,       // `/*` – fişierele din static/
             ..Default::default()
         };
+        let mut source = Source::Bundled;
+        if https {
+            let pem = certs.load()?;
+            source = pem.source;
+            // httpd_ssl îşi copiază certificatul, dar `X509` cere 'static
+            let cert: &'static str = Box::leak(pem.cert.into_boxed_str());
+            let key:  &'static str = Box::leak(pem.key.into_boxed_str());
+            cfg.server_certificate = Some(X509::pem_until_nul(cert.as_bytes()));
+            cfg.private_key        = Some(X509::pem_until_nul(key.as_bytes()));
+        }
 
         match EspHttpServer::new(&cfg) {
         Ok(mut server) => {
-            if let Err(e) = http::register_handlers(
+            let res = http::register_handlers(
                 &mut server,
                 tx_http2audio.clone(),
                 rx_audio2http.clone(),
-                i2s.clone(),
+                motion.clone(),
                 tx_tts.clone(),
-            ) {
+                hub.clone(),
+                auth.clone(),
+            )
+            .and_then(|()| {
+                let (mdns, wifi) = (mdns.clone(), wifi.clone());
+                http::register_tls(&mut server, auth.clone(), certs.clone(), move || {
+                    let mut names = vec![format!("{}.local", mdns.lock().unwrap().hostname())];
+                    if let Link::Up { ip, .. } = wifi.link() {
+                        names.push(ip.to_string());
+                    }
+                    names
+                })
+            })
+            .and_then(|()| http::register_mdns(&mut server, auth.clone(), mdns.clone()))
+            .and_then(|()| http::register_ota(&mut server, auth.clone(), ota.clone(), hub.clone()))
+            .and_then(|()| http::register_updater(&mut server, auth.clone(), updater.clone()))
+            .and_then(|()| http::register_wifi(&mut server, auth.clone(), wifi.clone()))
+            .and_then(|()| http::register_clock(&mut server, auth.clone(), clock.clone()))
+            .and_then(|()| http::register_config(&mut server, auth.clone(), config.clone()))
+            .and_then(|()| http::register_backup(&mut server, auth.clone(), config.clone()))
+            .and_then(|()| http::register_tts(&mut server, auth.clone(), voices.clone()))
+            .and_then(|()| http::register_static(&mut server));
+            if let Err(e) = res {
                 error!("register_handlers error: {e:?}. Reîncerc în 2 s…");
                 thread::sleep(Duration::from_secs(2));
                 continue;
             }
 
-            // (2) – înlocuieşte unwrap_or_default()
-            use core::net::Ipv4Addr;
-            let ip = match wifi.wifi().sta_netif().get_ip_info() {
-                Ok(info) => info.ip,
-                Err(_)   => Ipv4Addr::new(0, 0, 0, 0),
+            let ip = match wifi.link() {
+                Link::Up { ip, .. } => ip,
+                _ => core::net::Ipv4Addr::UNSPECIFIED,
             };
 
-            info!("HTTP ready – http://{ip}/");
+            if https {
+                // portul 80 doar redirecţionează; alt port de control decât serverul principal
+                let redirect = HttpCfg {
+                    ctrl_port: 32769,
+                    max_uri_handlers: 4,
+                    max_open_sockets: 2,
+                    stack_size: 4096,
+                    uri_match_wildcard: true,
+                    ..Default::default()
+                };
+                match EspHttpServer::new(&redirect) {
+                    Ok(mut srv) => match http::register_redirect(&mut srv, cfg.https_port) {
+                        Ok(()) => { Box::leak(Box::new(srv)); }
+                        Err(e) => error!("redirect HTTP → HTTPS: {e:?}"),
+                    },
+                    Err(e) => error!("redirect HTTP → HTTPS: {e:?}"),
+                }
+                info!("HTTPS ready – https://{ip}/ (certificat {source:?})");
+            } else {
+                info!("HTTP ready – http://{ip}/");
+            }
             thread::sleep(Duration::from_secs(1));
-            
             Box::leak(Box::new(server));
             break;
         }
         Err(e) => {
             error!("EspHttpServer::new error: {e:?}. Reîncerc în 2 s…");
+            // un certificat încărcat greşit nu are voie să lase robotul fără server
+            if https && source != Source::Bundled {
+                log::warn!("🔒 revin la certificatul din firmware");
+                let _ = certs.reset();
+            }
             thread::sleep(Duration::from_secs(2));
         }
     }
     }
 
 
-    /* 5️⃣  bucla principală – watchdog Wi-Fi & idle */
+    /* 5️⃣  bucla principală – starea Wi-Fi pentru /ws şi /api/status;
+           reconectarea e treaba thread-ului `wifi` */
+    let mut confirmed = false;
     loop {
-        //  fără propagare de erori!
-        if wifi.is_started().unwrap_or(false)
-            && !wifi.is_connected().unwrap_or(false)
-        {
-            log::warn!("Wi-Fi down – reconectez…");
-
-            // secvenţa de reconectare – nu verifica rezultatul,
-            // doar încearcă de câteva ori
-            for _ in 0..3 {
-                let _ = wifi.stop();
-                let _ = wifi.start();
-                if wifi.connect().is_ok() {
-                    break;
+        let link = wifi.link();
+        if let Link::Up { rssi, .. } = link {
+            // Wi-Fi + HTTP merg → imaginea curentă rămâne
+            if !confirmed {
+                confirmed = true;
+                if let Err(e) = ota.confirm() {
+                    error!("OTA confirm: {e:?}");
                 }
             }
+            if hub.clients() > 0 {
+                hub.publish(&Event::Rssi { dbm: rssi });
+            }
         }
+        status::set_wifi(match link {
+            Link::Up { ssid, ip, rssi } => Some(WifiInfo { ssid, rssi, ip: ip.to_string() }),
+            _ => None,
+        });
 
-    
-    thread::sleep(Duration::from_secs(5));
-}
-
-   
-
-
+        thread::sleep(Duration::from_secs(5));
+    }
 }
@@ -0,0 +1,90 @@
+//! mDNS: robotul se anunţă ca `myrobo.local` (hostname configurabil) cu
+//! serviciul `_http._tcp`; TXT-ul spune versiunea firmware-ului şi ce
+//! ştie robotul, pentru aplicaţiile care îl caută în reţea.
+
+use anyhow::{bail, Result};
+use log::{info, warn};
+
+use crate::hal::{Advertiser, KeyValueStore};
+
+pub const DEFAULT_HOSTNAME: &str = "myrobo";
+const KEY_HOSTNAME: &str = "hostname";
+
+/// funcţiile expuse de firmware (TXT `caps`)
+pub const CAPABILITIES: &str = "chat,voice,tts,drive,servo,ws";
+
+pub struct Mdns {
+    advertiser: Box<dyn Advertiser + Send>,
+    kv:         Box<dyn KeyValueStore + Send>,
+    hostname:   String,
+    port:       u16,
+    https:      bool,
+}
+
+impl Mdns {
+    /// Hostname-ul din NVS (sau `myrobo`) + primul anunţ. Un anunţ eşuat
+    /// nu opreşte robotul – se reîncearcă la `refresh`.
+    pub fn start(
+        advertiser: impl Advertiser + Send + 'static,
+        kv: impl KeyValueStore + Send + 'static,
+        port: u16,
+        https: bool,
+    ) -> Result<Self> {
+        let hostname = match kv.get_str(KEY_HOSTNAME)? {
+            Some(h) if valid_hostname(&h) => h,
+            Some(h) => {
+                warn!("mDNS: hostname invalid în NVS ({h:?}) – folosesc {DEFAULT_HOSTNAME}");
+                DEFAULT_HOSTNAME.into()
+            }
+            None => DEFAULT_HOSTNAME.into(),
+        };
+        let mut mdns = Self { advertiser: Box::new(advertiser), kv: Box::new(kv), hostname, port, https };
+        if let Err(e) = mdns.refresh() {
+            warn!("mDNS: {e:?}");
+        }
+        Ok(mdns)
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    /// Nume nou (litere mici, cifre, `-`); salvat în NVS şi anunţat imediat.
+    pub fn set_hostname(&mut self, name: &str) -> Result<()> {
+        let name = name.trim().trim_end_matches(".local").to_ascii_lowercase();
+        if !valid_hostname(&name) {
+            bail!("hostname invalid: {name:?} (1–63 caractere a-z, 0-9, '-')");
+        }
+        self.kv.set_str(KEY_HOSTNAME, &name)?;
+        self.hostname = name;
+        self.refresh()
+    }
+
+    /// Anunţ din nou – după reconectare IP-ul poate fi altul.
+    pub fn refresh(&mut self) -> Result<()> {
+        let txt = self.txt();
+        let txt: Vec<(&str, &str)> = txt.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.advertiser.advertise(&self.hostname, self.port, &txt)?;
+        info!("📡 mDNS: http{}://{}.local/", if self.https { "s" } else { "" }, self.hostname);
+        Ok(())
+    }
+
+    pub fn txt(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("version", crate::VERSION.into()),
+            ("board",   "esp32".into()),
+            ("path",    "/".into()),
+            ("caps",    CAPABILITIES.into()),
+            ("auth",    "bearer".into()),
+            ("https",   if self.https { "1" } else { "0" }.into()),
+        ]
+    }
+}
+
+/// O etichetă DNS (RFC 1123): 1–63 caractere, fără `-` la capete.
+pub fn valid_hostname(name: &str) -> bool {
+    (1..=63).contains(&name.len())
+        && name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+}
@@ -0,0 +1,115 @@
+//! Mişcări (butoanele din `control.html`) şi gesturi cu servo-urile.
+
+use anyhow::{bail, Result};
+use std::{thread, time::Duration};
+
+use crate::hal::{MotorDriver, ServoBank};
+use crate::motors::MotorId;
+use crate::servo::ServoId;
+
+/// `/move/<nume>` – (stânga %, dreapta %) ţinute `STEP_MS`
+pub const MOVES: &[(&str, i8, i8)] = &[
+    ("inainte",  70,  70),
+    ("inapoi",  -70, -70),
+    ("stanga",  -50,  50),
+    ("dreapta",  50, -50),
+];
+
+/// paşi (servo stânga °, servo dreapta °, ms)
+pub type Gesture = &'static [(f32, f32, u64)];
+
+/// `/action/<nume>`
+pub const ACTIONS: &[(&str, Gesture)] = &[
+    ("salut", &[
+        (90.0, 170.0, 300), (90.0, 120.0, 250), (90.0, 170.0, 250),
+        (90.0, 120.0, 250), (90.0,  90.0, 300),
+    ]),
+    ("disco", &[
+        (  0.0, 180.0, 250), (180.0,   0.0, 250), (  0.0, 180.0, 250),
+        (180.0,   0.0, 250), ( 90.0,  90.0, 300),
+    ]),
+];
+
+const STEP_MS: u64 = 600;
+
+/// Joystick → (stânga %, dreapta %). `linear` > 0 înainte, `angular` > 0
+/// rotire spre dreapta; ambele ∈ [-1, 1].
+pub fn mix(linear: f32, angular: f32) -> (i8, i8) {
+    let clamp = |v: f32| if v.is_finite() { v.clamp(-1.0, 1.0) } else { 0.0 };
+    let (linear, angular) = (clamp(linear), clamp(angular));
+    let (left, right) = (linear + angular, linear - angular);
+    // păstrăm raportul dintre roţi când suma depăşeşte 100 %
+    let scale = left.abs().max(right.abs()).max(1.0);
+    let pct = |v: f32| (v / scale * 100.0).round() as i8;
+    (pct(left), pct(right))
+}
+
+pub struct Motion {
+    motors: Box<dyn MotorDriver + Send>,
+    servos: Box<dyn ServoBank + Send>,
+    speeds: (i8, i8),
+    /// `None` până la prima comandă pe servo-ul respectiv
+    angles: (Option<f32>, Option<f32>),
+}
+
+impl Motion {
+    pub fn new(
+        motors: impl MotorDriver + Send + 'static,
+        servos: impl ServoBank + Send + 'static,
+    ) -> Self {
+        Self { motors: Box::new(motors), servos: Box::new(servos), speeds: (0, 0), angles: (None, None) }
+    }
+
+    pub fn drive(&mut self, left: i8, right: i8) -> Result<()> {
+        self.motors.drive(MotorId::Left, left)?;
+        self.motors.drive(MotorId::Right, right)?;
+        self.speeds = (left, right);
+        Ok(())
+    }
+
+    /// ultima comandă (stânga %, dreapta %)
+    pub fn speeds(&self) -> (i8, i8) {
+        self.speeds
+    }
+
+    pub fn servo(&mut self, id: ServoId, deg: f32) -> Result<()> {
+        self.servos.set_angle(id, deg)?;
+        let deg = Some(deg.clamp(0.0, 180.0));
+        match id {
+            ServoId::Left  => self.angles.0 = deg,
+            ServoId::Right => self.angles.1 = deg,
+        }
+        Ok(())
+    }
+
+    /// ultimul unghi comandat (stânga °, dreapta °)
+    pub fn angles(&self) -> (Option<f32>, Option<f32>) {
+        self.angles
+    }
+
+    pub fn stop(&mut self) -> Result<()> {
+        self.drive(0, 0)
+    }
+
+    /// mers scurt în direcţia cerută, apoi oprire
+    pub fn step(&mut self, name: &str) -> Result<()> {
+        let Some(&(_, left, right)) = MOVES.iter().find(|(n, ..)| *n == name) else {
+            bail!("mişcare necunoscută: {name}");
+        };
+        self.drive(left, right)?;
+        thread::sleep(Duration::from_millis(STEP_MS));
+        self.stop()
+    }
+
+    pub fn gesture(&mut self, name: &str) -> Result<()> {
+        let Some(&(_, steps)) = ACTIONS.iter().find(|(n, _)| *n == name) else {
+            bail!("gest necunoscut: {name}");
+        };
+        for &(left, right, ms) in steps {
+            self.servo(ServoId::Left, left)?;
+            self.servo(ServoId::Right, right)?;
+            thread::sleep(Duration::from_millis(ms));
+        }
+        Ok(())
+    }
+}
@@ -1,28 +1,33 @@
+#[cfg(target_os = "espidf")]
 use esp_idf_hal::{
-    ledc::{config::TimerConfig, LedcDriver, LedcTimerDriver, Resolution, LEDC},
-    gpio::{Gpio18, Gpio19, Gpio21, Gpio22},
+    ledc::{config::TimerConfig, LedcDriver, LedcTimerDriver, Resolution, LEDC, TIMER1},
+    gpio::AnyOutputPin,
     units::KiloHertz,
     prelude::*,
 };
+#[cfg(target_os = "espidf")]
 use core::ptr;
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum MotorId {
     Left,
     Right,
 }
 
+#[cfg(target_os = "espidf")]
 pub struct L9110S<'d> {
+    _timer: LedcTimerDriver<'d, TIMER1>,   // drop → reset timer, PWM moare
     m1_a: LedcDriver<'d>,
     m1_b: LedcDriver<'d>,
     m2_a: LedcDriver<'d>,
     m2_b: LedcDriver<'d>,
 }
 
+#[cfg(target_os = "espidf")]
 impl<'d> L9110S<'d> {
     pub fn new(
         ledc: &mut LEDC,
-        m1_a: Gpio18, m1_b: Gpio19,
-        m2_a: Gpio21, m2_b: Gpio22,
+        m1_a: AnyOutputPin, m1_b: AnyOutputPin,
+        m2_a: AnyOutputPin, m2_b: AnyOutputPin,
     ) -> anyhow::Result<Self> {
        let timer = LedcTimerDriver::new(
            unsafe { ptr::read(&ledc.timer1) },
@@ -36,7 +41,7 @@ impl<'d> L9110S<'d> {
         let m2_a = LedcDriver::new(unsafe { ptr::read(&ledc.channel4) }, &timer, m2_a)?;
         let m2_b = LedcDriver::new(unsafe { ptr::read(&ledc.channel5) }, &timer, m2_b)?;
 
-        Ok(Self { m1_a, m1_b, m2_a, m2_b })
+        Ok(Self { _timer: timer, m1_a, m1_b, m2_a, m2_b })
     }
 
     /// speed ∈ [-100, 100] (%)
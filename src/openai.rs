@@ -1,51 +1,31 @@
-use anyhow::{bail, Context, Result};
-use embedded_svc::http::client::{Client, Request, Response};
-use esp_idf_svc::{
-    http::client::{Configuration as HttpCfg, EspHttpConnection},
-    sys::esp_crt_bundle_attach,
-    io::Write,
-};
+use anyhow::{Context, Result};
 use core::str;
-use std::vec::Vec;
-
-const OPENAI_KEY: &str = env!("OPENAI_API_KEY");
-const WHISPER_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
-const CHAT_URL:    &str = "https://api.openai.com/v1/chat/completions";
-
-fn pcm_to_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
-    let mut wav = Vec::with_capacity(44 + samples.len() * 2);
-    let data_len = (samples.len() * 2) as u32;
-    let riff_len = 36 + data_len;
-
-    wav.extend(b"RIFF");
-    wav.extend(&riff_len.to_le_bytes());
-    wav.extend(b"WAVEfmt ");
-    wav.extend(&16u32.to_le_bytes());
-    wav.extend(&1u16.to_le_bytes());
-    wav.extend(&1u16.to_le_bytes());
-    wav.extend(&sample_rate.to_le_bytes());
-    wav.extend(&(sample_rate * 2).to_le_bytes());
-    wav.extend(&2u16.to_le_bytes());
-    wav.extend(&16u16.to_le_bytes());
-    wav.extend(b"data");
-    wav.extend(&data_len.to_le_bytes());
-    for s in samples {
-        wav.extend(&s.to_le_bytes());
-    }
-    wav
-}
-
-fn whisper_inner(wav: &[u8], language: &str) -> Result<String> {
+use serde_json::{json, Value};
+use std::{collections::VecDeque, vec::Vec};
+
+use crate::clock;
+use crate::config;
+use crate::hal::HttpClient;
+use crate::cloud_error::CloudError;
+use crate::retry::OPENAI;
+use crate::util::pcm_to_wav;
+
+fn whisper_inner(http: &mut dyn HttpClient, wav: &[u8], language: &str) -> Result<String> {
+    let cfg  = config::get();
     let bnd  = "ESP32BOUNDARY";
     let mut body = Vec::<u8>::new();
     let add = |buf: &mut Vec<u8>, s: &str| buf.extend_from_slice(s.as_bytes());
 
     add(&mut body, &format!("--{bnd}\r\n"));
-    add(&mut body, "Content-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n");
+    add(&mut body, &format!("Content-Disposition: form-data; name=\"model\"\r\n\r\n{}\r\n", cfg.openai.whisper_model));
 
     add(&mut body, &format!("--{bnd}\r\n"));
     add(&mut body, &format!("Content-Disposition: form-data; name=\"language\"\r\n\r\n{language}\r\n"));
 
+    // text simplu în loc de JSON – răspunsul e direct transcrierea
+    add(&mut body, &format!("--{bnd}\r\n"));
+    add(&mut body, "Content-Disposition: form-data; name=\"response_format\"\r\n\r\ntext\r\n");
+
     add(&mut body, &format!("--{bnd}\r\n"));
     add(
         &mut body,
@@ -56,14 +36,8 @@ fn whisper_inner(wav: &[u8], language: &str) -> Result<String> {
     add(&mut body, "\r\n");
     add(&mut body, &format!("--{bnd}--\r\n"));
 
-    let conn = EspHttpConnection::new(&HttpCfg {
-        use_global_ca_store: true,
-        crt_bundle_attach: Some(esp_crt_bundle_attach),
-        ..Default::default()
-    })?;
-    let mut client = Client::wrap(conn);
-
-    let auth  = format!("Bearer {}", OPENAI_KEY);
+    let url   = format!("{}/v1/audio/transcriptions", cfg.openai.url.trim_end_matches('/'));
+    let auth  = format!("Bearer {}", cfg.openai.key);
     let ctype = format!("multipart/form-data; boundary={bnd}");
     let clen  = body.len().to_string();
     let headers = [
@@ -72,47 +46,110 @@ fn whisper_inner(wav: &[u8], language: &str) -> Result<String> {
         ("Content-Length", clen.as_str()),
     ];
 
-    let mut req: Request<_> = client.post(WHISPER_URL, &headers)?;
-    req.write_all(&body)?;
-    let mut resp: Response<_> = req.submit()?;
-    if resp.status() != 200 {
-        bail!("Whisper HTTP {}", resp.status());
+    OPENAI.call("Whisper", http, |http| {
+        let mut resp = http.post(&url, &headers, &body)?;
+        if resp.status() != 200 {
+            return Err(CloudError::from_response("Whisper", &mut *resp).into());
+        }
+
+        let out = resp.read_to_end()?;
+        Ok(core::str::from_utf8(&out)?.trim().into())
+    })
+}
+
+pub fn whisper_transcribe(http: &mut dyn HttpClient, pcm: &[i16], language: &str) -> Result<String> {
+    let wav = pcm_to_wav(pcm, 16_000);
+    whisper_inner(http, &wav, language)
+}
+
+pub fn whisper_wav(http: &mut dyn HttpClient, wav: &[u8], language: &str) -> Result<String> {
+    whisper_inner(http, wav, language)
+}
+
+/* ------------ conversaţie ------------------------------------------- */
+
+/// implicit; cel folosit e `config.openai.system_prompt`
+pub const SYSTEM_PROMPT: &str =
+    "Eşti MyRoboAssistant, un robot prietenos. Răspunzi în română, clar şi concis.";
+
+/// Ultimele replici, trimise la fiecare cerere ca ChatGPT să aibă context.
+pub struct Conversation {
+    system:  String,
+    history: VecDeque<(String, String)>,
+    /// perechi (utilizator, asistent) păstrate
+    turns:   usize,
+}
+
+impl Conversation {
+    pub fn new(system: &str, turns: usize) -> Self {
+        Self { system: system.into(), history: VecDeque::new(), turns }
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    fn messages(&self, prompt: &str) -> Vec<Value> {
+        // „cât e ceasul?” – doar cu ceasul sincronizat, altfel ar inventa
+        let system = match clock::now() {
+            Some(now) => format!("{} Acum este {} ({}).", self.system, now.describe(), now.abbr),
+            None => self.system.clone(),
+        };
+        let mut msgs = vec![json!({"role": "system", "content": system})];
+        for (user, reply) in &self.history {
+            msgs.push(json!({"role": "user",      "content": user}));
+            msgs.push(json!({"role": "assistant", "content": reply}));
+        }
+        msgs.push(json!({"role": "user", "content": prompt}));
+        msgs
     }
 
-    let mut out = Vec::<u8>::new();
-    let mut buf = [0u8; 512];
-    loop {
-        let n = resp.read(&mut buf)?;
-        if n == 0 { break; }
-        out.extend_from_slice(&buf[..n]);
+    fn push(&mut self, prompt: &str, reply: &str) {
+        self.history.push_back((prompt.into(), reply.into()));
+        while self.history.len() > self.turns {
+            self.history.pop_front();
+        }
     }
-    Ok(core::str::from_utf8(&out)?.trim().into())
 }
 
-pub fn whisper_transcribe(pcm: &[i16], language: &str) -> Result<String> {
-    let wav = pcm_to_wav(pcm, 16_000);
-    whisper_inner(&wav, language)
+/// Prompt-ul şi numărul de replici din `config`.
+impl Default for Conversation {
+    fn default() -> Self {
+        let cfg = config::get();
+        Self::new(&cfg.openai.system_prompt, cfg.openai.turns as usize)
+    }
 }
 
-pub fn whisper_wav(wav: &[u8], language: &str) -> Result<String> {
-    whisper_inner(wav, language)
+/// Replică cu istoric; conversaţia se actualizează doar dacă a reuşit.
+pub fn chat_in(http: &mut dyn HttpClient, conv: &mut Conversation, prompt: &str) -> Result<String> {
+    let reply = complete(http, conv.messages(prompt))?;
+    conv.push(prompt, &reply);
+    Ok(reply)
 }
 
-pub fn chat(prompt: &str) -> Result<String> {
-    let body = serde_json::json!({
-        "model": "gpt-3.5-turbo",
-        "messages": [{"role":"user","content":prompt}]
+/// O singură întrebare, fără context.
+pub fn chat(http: &mut dyn HttpClient, prompt: &str) -> Result<String> {
+    complete(http, vec![json!({"role": "user", "content": prompt})])
+}
+
+fn complete(http: &mut dyn HttpClient, messages: Vec<Value>) -> Result<String> {
+    let cfg = config::get();
+    let body = json!({
+        "model": cfg.openai.chat_model,
+        "messages": messages,
     })
     .to_string();
 
-    let conn = EspHttpConnection::new(&HttpCfg {
-        use_global_ca_store: true,
-        crt_bundle_attach: Some(esp_crt_bundle_attach),
-        ..Default::default()
-    })?;
-    let mut client = Client::wrap(conn);
-
-    let auth = format!("Bearer {}", OPENAI_KEY);
+    let url  = format!("{}/v1/chat/completions", cfg.openai.url.trim_end_matches('/'));
+    let auth = format!("Bearer {}", cfg.openai.key);
     let clen = body.len().to_string();
     let headers = [
         ("Authorization",  auth.as_str()),
@@ -120,23 +157,17 @@ pub fn chat(prompt: &str) -> Result<String> {
         ("Content-Length", clen.as_str()),
     ];
 
-    let mut req = client.post(CHAT_URL, &headers)?;
-    req.write_all(body.as_bytes())?;
-    let mut resp = req.submit()?;
-    if resp.status() != 200 {
-        bail!("ChatGPT HTTP {}", resp.status());
-    }
-
-    let mut json = Vec::<u8>::new();
-    let mut buf = [0u8; 512];
-    loop {
-        let n = resp.read(&mut buf)?;
-        if n == 0 { break; }
-        json.extend_from_slice(&buf[..n]);
-    }
-    let v: serde_json::Value = serde_json::from_slice(&json)?;
-    let reply = v["choices"][0]["message"]["content"]
-        .as_str()
-        .context("bad json")?;
-    Ok(reply.trim().into())
+    OPENAI.call("ChatGPT", http, |http| {
+        let mut resp = http.post(&url, &headers, body.as_bytes())?;
+        if resp.status() != 200 {
+            return Err(CloudError::from_response("ChatGPT", &mut *resp).into());
+        }
+
+        let json = resp.read_to_end()?;
+        let v: Value = serde_json::from_slice(&json).context("JSON invalid")?;
+        let reply = v["choices"][0]["message"]["content"]
+            .as_str()
+            .context("lipseşte choices[0].message.content")?;
+        Ok(reply.trim().into())
+    })
 }
@@ -0,0 +1,312 @@
+//! Actualizare firmware prin reţea (OTA). Imaginea vine pe bucăţi, se
+//! scrie în slotul inactiv (`ota_0`/`ota_1`) cu SHA-256 calculat din mers
+//! şi devine slot de boot doar dacă se verifică totul.
+//!
+//! Firmware compilat cu `OTA_PUBKEY` (cheie publică P-256, SEC1 în hex)
+//! acceptă doar imagini semnate ECDSA-P256/SHA-256: `r‖s`, 64 de octeţi în
+//! hex. Cheia privată rămâne la cel care publică firmware-ul.
+//!
+//! După repornire firmware-ul nou e „în probă”: dacă nu ajunge sănătos
+//! (Wi-Fi + server HTTP) în `HEALTH_TIMEOUT`, sau cade înainte, bootloader-ul
+//! revine la imaginea veche.
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use p256::ecdsa::{signature::DigestVerifier, Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use serde::Serialize;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::hal::{Firmware, RunningSlot};
+use crate::util;
+
+/// primul octet al unei imagini de aplicaţie ESP32 (`esp_image_header_t`)
+pub const IMAGE_MAGIC: u8 = 0xE9;
+/// cât are voie imaginea nouă până să se declare sănătoasă
+pub const HEALTH_TIMEOUT: Duration = Duration::from_secs(120);
+/// progresul se raportează la fiecare … octeţi
+pub const PROGRESS_STEP: usize = 64 * 1024;
+
+/// `esp_app_desc_t` vine după header (24 B) şi primul segment (8 B)
+const APP_DESC:       usize = 32;
+const APP_DESC_MAGIC: u32   = 0xABCD_5432;
+/// cât din începutul imaginii trebuie păstrat pentru `image_version`
+const HEAD:           usize = APP_DESC + 48;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    Idle,
+    Receiving,
+    /// imaginea scrisă, aşteaptă repornirea
+    Ready,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Progress {
+    pub state:    State,
+    pub received: usize,
+    pub total:    Option<usize>,
+    /// versiunea din `esp_app_desc_t` a imaginii primite
+    pub version:  Option<String>,
+    pub sha256:   Option<String>,
+    pub error:    Option<String>,
+}
+
+impl Progress {
+    fn idle() -> Self {
+        Self { state: State::Idle, received: 0, total: None, version: None, sha256: None, error: None }
+    }
+}
+
+#[derive(Debug)]
+pub enum OtaError {
+    /// altă actualizare e în curs
+    Busy,
+    /// nu e o imagine de aplicaţie ESP32, e goală sau incompletă
+    BadImage(String),
+    /// nu încape în slot
+    TooLarge(usize),
+    /// SHA-256 diferit de cel anunţat
+    Mismatch,
+    /// firmware compilat cu `OTA_PUBKEY`: semnătura lipseşte sau e greşită
+    BadSignature,
+    /// citire din reţea sau scriere în flash
+    Failed(anyhow::Error),
+}
+
+impl fmt::Display for OtaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Busy          => write!(f, "o actualizare e deja în curs"),
+            Self::BadImage(why) => write!(f, "imagine invalidă: {why}"),
+            Self::TooLarge(max) => write!(f, "imaginea nu încape în slot (max. {max} B)"),
+            Self::Mismatch      => write!(f, "SHA-256 diferit de cel anunţat"),
+            Self::BadSignature  => write!(f, "semnătură lipsă sau greşită"),
+            Self::Failed(e)     => write!(f, "{e:#}"),
+        }
+    }
+}
+
+pub struct Ota {
+    fw:       Mutex<Box<dyn Firmware + Send>>,
+    /// cheia publică; dacă există, imaginile nesemnate sunt refuzate
+    key:      Option<VerifyingKey>,
+    progress: Mutex<Progress>,
+}
+
+impl Ota {
+    pub fn new(fw: impl Firmware + Send + 'static, key: Option<VerifyingKey>) -> Self {
+        Self {
+            fw:       Mutex::new(Box::new(fw)),
+            key,
+            progress: Mutex::new(Progress::idle()),
+        }
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    pub fn running(&self) -> Result<RunningSlot> {
+        self.fw.lock().unwrap().running()
+    }
+
+    /// Se cere semnătură (`X-Signature`) pentru imagini noi?
+    pub fn signed(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Scrie imaginea citită cu `read` (0 = sfârşit) în slotul inactiv.
+    /// `on_progress` e apelat la fiecare `PROGRESS_STEP` octeţi şi la final.
+    pub fn update(
+        &self,
+        mut read: impl FnMut(&mut [u8]) -> Result<usize>,
+        total: Option<usize>,
+        sha256: Option<[u8; 32]>,
+        signature: Option<Signature>,
+        mut on_progress: impl FnMut(&Progress),
+    ) -> Result<Progress, OtaError> {
+        {
+            let mut p = self.progress.lock().unwrap();
+            if p.state == State::Receiving {
+                return Err(OtaError::Busy);
+            }
+            *p = Progress { state: State::Receiving, total, ..Progress::idle() };
+        }
+
+        let mut fw = self.fw.lock().unwrap();
+        let res = self.write_image(&mut **fw, &mut read, total, sha256, signature, &mut on_progress);
+        if res.is_err() {
+            fw.abort();
+        }
+        drop(fw);
+
+        let mut p = self.progress.lock().unwrap();
+        match res {
+            Ok(()) => {
+                p.state = State::Ready;
+                info!("⬆️ firmware {} scris ({} B) – activ după repornire", p.version.as_deref().unwrap_or("?"), p.received);
+            }
+            Err(ref e) => {
+                p.state = State::Failed;
+                p.error = Some(e.to_string());
+                warn!("⬆️ OTA eşuat: {e}");
+            }
+        }
+        on_progress(&p);
+        res.map(|()| p.clone())
+    }
+
+    fn write_image(
+        &self,
+        fw: &mut dyn Firmware,
+        read: &mut dyn FnMut(&mut [u8]) -> Result<usize>,
+        total: Option<usize>,
+        sha256: Option<[u8; 32]>,
+        signature: Option<Signature>,
+        on_progress: &mut dyn FnMut(&Progress),
+    ) -> Result<(), OtaError> {
+        let slot = fw.slot_size().map_err(OtaError::Failed)?;
+        if total.is_some_and(|t| t > slot) {
+            return Err(OtaError::TooLarge(slot));
+        }
+        fw.begin().map_err(OtaError::Failed)?;
+
+        let mut hash = Sha256::new();
+        let mut head = Vec::with_capacity(HEAD);
+        let mut buf = vec![0u8; 4096];
+        let mut received = 0;
+        let mut reported = 0;
+        loop {
+            let n = read(&mut buf).map_err(OtaError::Failed)?;
+            if n == 0 {
+                break;
+            }
+            let chunk = &buf[..n];
+            if received == 0 && chunk[0] != IMAGE_MAGIC {
+                return Err(OtaError::BadImage(format!("primul octet 0x{:02X}, nu 0xE9", chunk[0])));
+            }
+            received += n;
+            if received > slot {
+                return Err(OtaError::TooLarge(slot));
+            }
+            if head.len() < HEAD {
+                let take = (HEAD - head.len()).min(n);
+                head.extend_from_slice(&chunk[..take]);
+            }
+            hash.update(chunk);
+            fw.write(chunk).map_err(OtaError::Failed)?;
+
+            if received - reported >= PROGRESS_STEP {
+                reported = received;
+                let mut p = self.progress.lock().unwrap();
+                p.received = received;
+                p.version = image_version(&head);
+                on_progress(&p);
+            }
+        }
+
+        let digest: [u8; 32] = hash.clone().finalize().into();
+        {
+            let mut p = self.progress.lock().unwrap();
+            p.received = received;
+            p.version = image_version(&head);
+            p.sha256 = Some(util::hex(&digest));
+        }
+        if received == 0 {
+            return Err(OtaError::BadImage("corp gol".into()));
+        }
+        if total.is_some_and(|t| t != received) {
+            return Err(OtaError::BadImage(format!("incompletă: {received} din {} B", total.unwrap_or(0))));
+        }
+        if sha256.is_some_and(|s| s != digest) {
+            return Err(OtaError::Mismatch);
+        }
+        if let Some(key) = &self.key {
+            if signature.map_or(true, |s| key.verify_digest(hash, &s).is_err()) {
+                return Err(OtaError::BadSignature);
+            }
+        }
+        fw.finish().map_err(OtaError::Failed)
+    }
+
+    /// Reporneşte după `after` (cât să plece răspunsul HTTP).
+    pub fn restart_later(self: &Arc<Self>, after: Duration) {
+        let ota = self.clone();
+        thread::spawn(move || {
+            thread::sleep(after);
+            info!("⬆️ repornesc în firmware-ul nou…");
+            ota.fw.lock().unwrap().restart();
+        });
+    }
+
+    /* ------------ imaginea în probă --------------------------------- */
+
+    /// Apelată când Wi-Fi-ul şi serverul HTTP merg: imaginea curentă
+    /// rămâne. `true` dacă tocmai a ieşit din probă.
+    pub fn confirm(&self) -> Result<bool> {
+        let mut fw = self.fw.lock().unwrap();
+        if !fw.running()?.pending_verify {
+            return Ok(false);
+        }
+        fw.mark_valid()?;
+        info!("⬆️ firmware-ul nou e sănătos – rollback anulat");
+        Ok(true)
+    }
+
+    /// Dacă imaginea e în probă, revine la cea veche când `confirm` nu
+    /// vine în `timeout`.
+    pub fn guard(self: &Arc<Self>, timeout: Duration) -> Result<()> {
+        let slot = self.running()?;
+        if !slot.pending_verify {
+            return Ok(());
+        }
+        warn!("⬆️ firmware nou în {} – are {} s să pornească", slot.label, timeout.as_secs());
+        let ota = self.clone();
+        thread::Builder::new().name("ota_guard".into()).stack_size(4096).spawn(move || {
+            thread::sleep(timeout);
+            let mut fw = ota.fw.lock().unwrap();
+            if matches!(fw.running(), Ok(s) if s.pending_verify) {
+                warn!("⬆️ firmware-ul nou nu a ajuns sănătos – revin la cel vechi");
+                if let Err(e) = fw.rollback() {
+                    log::error!("rollback: {e:?}");
+                }
+            }
+        })?;
+        Ok(())
+    }
+}
+
+/// Versiunea din `esp_app_desc_t`, dacă primii octeţi o conţin.
+pub fn image_version(image: &[u8]) -> Option<String> {
+    let desc = image.get(APP_DESC..HEAD)?;
+    if u32::from_le_bytes(desc[..4].try_into().ok()?) != APP_DESC_MAGIC {
+        return None;
+    }
+    let version = &desc[16..48];
+    let end = version.iter().position(|&b| b == 0).unwrap_or(version.len());
+    Some(String::from_utf8_lossy(&version[..end]).into_owned())
+}
+
+/// `X-Sha256`: 64 de caractere hex
+pub fn parse_digest(hex: &str) -> Option<[u8; 32]> {
+    util::unhex(hex)?.try_into().ok()
+}
+
+/// `X-Signature`: `r‖s` în hex, 128 de caractere
+pub fn parse_signature(hex: &str) -> Option<Signature> {
+    Signature::from_slice(&util::unhex(hex)?).ok()
+}
+
+/// `OTA_PUBKEY`: punctul P-256 SEC1 în hex, comprimat (33 B) sau nu (65 B)
+pub fn public_key(hex: &str) -> Result<VerifyingKey> {
+    let sec1 = util::unhex(hex).ok_or_else(|| anyhow!("OTA_PUBKEY: nu e hex"))?;
+    VerifyingKey::from_sec1_bytes(&sec1).map_err(|_| anyhow!("OTA_PUBKEY: nu e o cheie P-256"))
+}
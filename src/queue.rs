@@ -0,0 +1,84 @@
+//! Canale `mpsc` cu numărătoare: câte mesaje aşteaptă în fiecare coadă
+//! (TTS, audio, evenimente /ws) apare în `/api/status`.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvError, RecvTimeoutError, SendError, TryRecvError},
+        Arc, Mutex, Weak,
+    },
+    time::Duration,
+};
+
+static QUEUES: Mutex<Vec<(&'static str, Weak<AtomicUsize>)>> = Mutex::new(Vec::new());
+
+pub struct Tx<T> {
+    tx:    mpsc::Sender<T>,
+    depth: Arc<AtomicUsize>,
+}
+
+pub struct Rx<T> {
+    rx:    mpsc::Receiver<T>,
+    depth: Arc<AtomicUsize>,
+}
+
+/// Ca `mpsc::channel`, cu adâncimea raportată sub `name`.
+pub fn channel<T>(name: &'static str) -> (Tx<T>, Rx<T>) {
+    let (tx, rx) = mpsc::channel();
+    let depth = Arc::new(AtomicUsize::new(0));
+    let mut queues = QUEUES.lock().unwrap();
+    queues.retain(|(_, d)| d.strong_count() > 0);
+    queues.push((name, Arc::downgrade(&depth)));
+    (Tx { tx, depth: depth.clone() }, Rx { rx, depth })
+}
+
+/// (nume, mesaje în aşteptare) pentru cozile încă în viaţă
+pub fn depths() -> Vec<(&'static str, usize)> {
+    QUEUES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|(name, d)| d.upgrade().map(|d| (*name, d.load(Ordering::Relaxed))))
+        .collect()
+}
+
+impl<T> Tx<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        self.tx.send(value).inspect_err(|_| {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+        })
+    }
+}
+
+impl<T> Clone for Tx<T> {
+    fn clone(&self) -> Self {
+        Self { tx: self.tx.clone(), depth: self.depth.clone() }
+    }
+}
+
+impl<T> Rx<T> {
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.rx.recv().inspect(|_| self.taken())
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.rx.recv_timeout(timeout).inspect(|_| self.taken())
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.rx.try_recv().inspect(|_| self.taken())
+    }
+
+    pub fn len(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn taken(&self) {
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+    }
+}
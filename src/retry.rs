@@ -0,0 +1,315 @@
+//! Politica comună pentru apelurile cloud (OpenAI, Azure TTS):
+//! timeout-uri, reîncercări cu backoff exponenţial + jitter, `Retry-After`
+//! pe 429/503 şi un circuit breaker per serviciu.
+//!
+//! Un POST care costă (Whisper, ChatGPT, sinteza) se reia doar dacă n-a
+//! plecat deloc ([`NotSent`]) sau dacă serverul l-a refuzat explicit
+//! (429, 503) – un timeout după trimitere ar putea fi facturat de două ori.
+
+use anyhow::{Error, Result};
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    io,
+    sync::{Mutex, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::cloud_error::CloudError;
+use crate::hal::{HttpClient, NotSent};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Policy {
+    pub connect_timeout: Duration,
+    pub read_timeout:    Duration,
+    /// încercări în total (1 = fără reîncercare)
+    pub attempts:        u32,
+    pub base_delay:      Duration,
+    pub max_delay:       Duration,
+    /// `Retry-After` mai mare de atât → renunţăm imediat
+    pub max_retry_after: Duration,
+    /// eşecuri consecutive după care circuitul se deschide
+    pub breaker_after:   u32,
+    pub breaker_open:    Duration,
+}
+
+impl Policy {
+    pub const DEFAULT: Policy = Policy {
+        connect_timeout: Duration::from_secs(5),
+        read_timeout:    Duration::from_secs(15),
+        attempts:        3,
+        base_delay:      Duration::from_millis(500),
+        max_delay:       Duration::from_secs(4),
+        max_retry_after: Duration::from_secs(10),
+        breaker_after:   3,
+        breaker_open:    Duration::from_secs(30),
+    };
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static POLICY: RwLock<Policy> = RwLock::new(Policy::DEFAULT);
+
+pub fn set_policy(p: Policy) {
+    *POLICY.write().unwrap() = p;
+}
+
+pub fn policy() -> Policy {
+    *POLICY.read().unwrap()
+}
+
+/* ------------ erori ------------------------------------------------- */
+
+/// Eroare care nu se reîncearcă, indiferent de tip (ex. TTS deja pornit).
+#[derive(Debug)]
+pub struct Abort(pub Error);
+
+impl fmt::Display for Abort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for Abort {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+enum Verdict {
+    /// reîncercăm; `Some` = aşteptare impusă de server
+    Retry(Option<Duration>),
+    /// serviciul are o problemă, dar cererea poate să fi fost executată –
+    /// nu o repetăm, doar o numărăm pentru breaker
+    GiveUp,
+    /// serverul a răspuns, dar cererea e greşită – circuitul rămâne închis
+    Fatal,
+}
+
+fn classify(e: &Error, idempotent: bool) -> Verdict {
+    if e.is::<Abort>() {
+        return Verdict::Fatal;
+    }
+    if e.chain().any(|c| c.is::<NotSent>()) {
+        return Verdict::Retry(None);
+    }
+    if let Some(c) = e.downcast_ref::<CloudError>() {
+        let refused = matches!(c, CloudError::RateLimited { .. } | CloudError::Server { status: 503, .. });
+        return match c.is_transient() {
+            false => Verdict::Fatal,
+            true if idempotent || refused => Verdict::Retry(c.retry_after()),
+            true => Verdict::GiveUp,
+        };
+    }
+    // reţea / TLS / timeout – pe placă vin ca `EspIOError`
+    #[cfg(target_os = "espidf")]
+    let transport = e.chain().any(|c| c.is::<esp_idf_svc::io::EspIOError>() || c.is::<esp_idf_svc::sys::EspError>());
+    #[cfg(not(target_os = "espidf"))]
+    let transport = false;
+    if transport || e.chain().any(|c| c.is::<io::Error>()) {
+        return if idempotent { Verdict::Retry(None) } else { Verdict::GiveUp };
+    }
+    Verdict::Fatal
+}
+
+/* ------------ circuit breaker ---------------------------------------- */
+
+struct Breaker {
+    failures:   u32,
+    open_until: Option<Instant>,
+}
+
+/// Ultimul apel al unui serviciu (pentru `/api/status`).
+#[derive(Clone, Debug)]
+pub struct LastCall {
+    pub api:     &'static str,
+    /// cu tot cu reîncercări
+    pub latency: Duration,
+    pub error:   Option<String>,
+    pub at:      Instant,
+}
+
+pub struct Service {
+    pub name: &'static str,
+    breaker:  Mutex<Breaker>,
+    last:     Mutex<Option<LastCall>>,
+}
+
+pub static OPENAI: Service = Service::new("OpenAI");
+pub static AZURE:  Service = Service::new("Azure TTS");
+
+impl Service {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            breaker: Mutex::new(Breaker { failures: 0, open_until: None }),
+            last:    Mutex::new(None),
+        }
+    }
+
+    pub fn last_call(&self) -> Option<LastCall> {
+        self.last.lock().unwrap().clone()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.breaker
+            .lock()
+            .unwrap()
+            .open_until
+            .is_some_and(|t| Instant::now() < t)
+    }
+
+    /// închide circuitul (teste, reconectare Wi-Fi)
+    pub fn reset(&self) {
+        *self.breaker.lock().unwrap() = Breaker { failures: 0, open_until: None };
+    }
+
+    fn check(&self, api: &'static str) -> Result<(), CloudError> {
+        let b = self.breaker.lock().unwrap();
+        match b.open_until {
+            // după expirare lăsăm o cerere să treacă („half-open”)
+            Some(t) if Instant::now() < t => Err(CloudError::Offline {
+                service:  api,
+                retry_in: t - Instant::now(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn success(&self) {
+        let mut b = self.breaker.lock().unwrap();
+        b.failures = 0;
+        b.open_until = None;
+    }
+
+    fn failure(&self, p: &Policy) {
+        let mut b = self.breaker.lock().unwrap();
+        b.failures += 1;
+        if b.failures >= p.breaker_after {
+            log::warn!("🔌 {} indisponibil ({} eşecuri) – circuit deschis {} s",
+                self.name, b.failures, p.breaker_open.as_secs());
+            b.open_until = Some(Instant::now() + p.breaker_open);
+        }
+    }
+
+    /// Rulează `f` după politica curentă; `f` e reapelat cu acelaşi client
+    /// doar când cererea n-a plecat sau a fost refuzată (429, 503). Orice
+    /// eroare iese ca [`CloudError`] cu numele `api` („Whisper”, „ChatGPT”…).
+    pub fn call<T>(
+        &self,
+        api: &'static str,
+        http: &mut dyn HttpClient,
+        f: impl FnMut(&mut dyn HttpClient) -> Result<T>,
+    ) -> Result<T> {
+        self.call_as(api, http, false, f)
+    }
+
+    /// Ca `call`, pentru cereri care pot fi repetate fără efecte (GET,
+    /// `issueToken`): reluate şi după timeout sau 5xx.
+    pub fn call_idempotent<T>(
+        &self,
+        api: &'static str,
+        http: &mut dyn HttpClient,
+        f: impl FnMut(&mut dyn HttpClient) -> Result<T>,
+    ) -> Result<T> {
+        self.call_as(api, http, true, f)
+    }
+
+    fn call_as<T>(
+        &self,
+        api: &'static str,
+        http: &mut dyn HttpClient,
+        idempotent: bool,
+        f: impl FnMut(&mut dyn HttpClient) -> Result<T>,
+    ) -> Result<T> {
+        let t0 = Instant::now();
+        let res = self.run(api, http, idempotent, f).map_err(|e| CloudError::classify(api, e));
+        *self.last.lock().unwrap() = Some(LastCall {
+            api,
+            latency: t0.elapsed(),
+            error:   res.as_ref().err().map(|e| e.to_string()),
+            at:      Instant::now(),
+        });
+        res.map_err(Into::into)
+    }
+
+    fn run<T>(
+        &self,
+        api: &'static str,
+        http: &mut dyn HttpClient,
+        idempotent: bool,
+        mut f: impl FnMut(&mut dyn HttpClient) -> Result<T>,
+    ) -> Result<T> {
+        let p = policy();
+        self.check(api)?;
+        http.set_timeouts(p.connect_timeout, p.read_timeout);
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let err = match f(http) {
+                Ok(v) => {
+                    self.success();
+                    return Ok(v);
+                }
+                Err(e) => e,
+            };
+
+            let wait = match classify(&err, idempotent) {
+                Verdict::Fatal => {
+                    // serverul a răspuns → serviciul e în viaţă
+                    if !err.is::<Abort>() { self.success(); }
+                    return Err(unwrap_abort(err));
+                }
+                Verdict::GiveUp => {
+                    self.failure(&p);
+                    return Err(err);
+                }
+                Verdict::Retry(after) => after,
+            };
+
+            let delay = match wait {
+                Some(d) if d > p.max_retry_after => None,
+                Some(d) => Some(d),
+                None    => Some(backoff(&p, attempt)),
+            };
+            match delay {
+                Some(d) if attempt < p.attempts => {
+                    log::warn!("↻ {} încercarea {attempt}/{}: {err:#} – aştept {} ms",
+                        self.name, p.attempts, d.as_millis());
+                    thread::sleep(d);
+                }
+                _ => {
+                    self.failure(&p);
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+fn unwrap_abort(e: Error) -> Error {
+    match e.downcast::<Abort>() {
+        Ok(Abort(inner)) => inner,
+        Err(e)           => e,
+    }
+}
+
+/// `base · 2^(n-1)`, plafonat la `max_delay`, apoi jitter în [d/2, d]
+fn backoff(p: &Policy, attempt: u32) -> Duration {
+    let exp = p.base_delay.saturating_mul(1 << (attempt - 1).min(16));
+    let d = exp.min(p.max_delay);
+    let half = d / 2;
+    half + Duration::from_micros(random() % (half.as_micros() as u64 + 1))
+}
+
+fn random() -> u64 {
+    // `RandomState` e seed-uit din RNG-ul sistemului (esp_fill_random pe placă)
+    RandomState::new().build_hasher().finish()
+}
@@ -1,25 +1,30 @@
+#[cfg(target_os = "espidf")]
 use esp_idf_hal::{
-    ledc::{config::TimerConfig, LedcDriver, LedcTimerDriver, Resolution, LEDC},
-    gpio::{Gpio16, Gpio17},
+    ledc::{config::TimerConfig, LedcDriver, LedcTimerDriver, Resolution, LEDC, TIMER0},
+    gpio::AnyOutputPin,
     prelude::*,
 };
+#[cfg(target_os = "espidf")]
 use core::{mem, ptr}; 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ServoId {
     Left,
     Right,
 }
 
+#[cfg(target_os = "espidf")]
 pub struct DualServo<'d> {
+    _timer: LedcTimerDriver<'d, TIMER0>,   // drop → reset timer, PWM moare
     ch1: LedcDriver<'d>,
     ch2: LedcDriver<'d>,
 }
 
+#[cfg(target_os = "espidf")]
 impl<'d> DualServo<'d> {
     pub fn new(
         ledc: &mut LEDC,
-        gpio16: Gpio16,
-        gpio17: Gpio17,
+        left: AnyOutputPin,
+        right: AnyOutputPin,
     ) -> anyhow::Result<Self> {
         // timer de 50 Hz
         let timer = LedcTimerDriver::new(
@@ -29,10 +34,10 @@ impl<'d> DualServo<'d> {
                 .resolution(Resolution::Bits15),
         )?;
 
-        let ch1 = LedcDriver::new(unsafe { ptr::read(&ledc.channel0) }, &timer, gpio16)?;
-        let ch2 = LedcDriver::new(unsafe { ptr::read(&ledc.channel1) }, &timer, gpio17)?;
+        let ch1 = LedcDriver::new(unsafe { ptr::read(&ledc.channel0) }, &timer, left)?;
+        let ch2 = LedcDriver::new(unsafe { ptr::read(&ledc.channel1) }, &timer, right)?;
 
-        Ok(Self { ch1, ch2 })
+        Ok(Self { _timer: timer, ch1, ch2 })
     }
 
     /// setează unghiul servo (0-180°)
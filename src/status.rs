@@ -0,0 +1,153 @@
+//! `GET /api/status`: versiune, uptime, memorie, stivele thread-urilor,
+//! Wi-Fi, ultimul apel cloud, cozi, motoare, actualizări şi ceasul – pentru
+//! dashboard şi pentru depanare pe teren.
+
+use serde_json::{json, Value};
+use std::{sync::Mutex, time::Duration};
+
+use crate::clock;
+use crate::motion::Motion;
+use crate::queue;
+use crate::retry::{self, Service};
+use crate::updater::Report;
+use crate::ws::Hub;
+
+/* ------------ Wi-Fi (completat de bucla principală) ------------------- */
+
+#[derive(Clone, Debug)]
+pub struct WifiInfo {
+    pub ssid: String,
+    pub rssi: i32,
+    pub ip:   String,
+}
+
+static WIFI: Mutex<Option<WifiInfo>> = Mutex::new(None);
+
+pub fn set_wifi(info: Option<WifiInfo>) {
+    *WIFI.lock().unwrap() = info;
+}
+
+/* ------------ actualizări (completat de `updater`) ------------------- */
+
+static UPDATE: Mutex<Option<Report>> = Mutex::new(None);
+
+pub fn set_update(report: Option<Report>) {
+    *UPDATE.lock().unwrap() = report;
+}
+
+/* ------------ stive --------------------------------------------------- */
+
+/// (thread, task-ul FreeRTOS ca adresă)
+static STACKS: Mutex<Vec<(&'static str, usize)>> = Mutex::new(Vec::new());
+
+/// Apelată din thread-ul urmărit; o a doua înregistrare cu acelaşi nume
+/// o înlocuieşte pe prima.
+pub fn watch_stack(name: &'static str) {
+    let task = current_task();
+    let mut stacks = STACKS.lock().unwrap();
+    stacks.retain(|(n, _)| *n != name);
+    stacks.push((name, task));
+}
+
+#[cfg(target_os = "espidf")]
+fn current_task() -> usize {
+    unsafe { esp_idf_svc::sys::xTaskGetCurrentTaskHandle() as usize }
+}
+
+#[cfg(not(target_os = "espidf"))]
+fn current_task() -> usize {
+    0
+}
+
+/// cei mai puţini octeţi de stivă rămaşi liberi de la pornire
+#[cfg(target_os = "espidf")]
+fn stack_free_min(task: usize) -> Option<u32> {
+    // în ESP-IDF `StackType_t` e un octet, deci rezultatul e deja în octeţi
+    Some(unsafe { esp_idf_svc::sys::uxTaskGetStackHighWaterMark(task as _) })
+}
+
+#[cfg(not(target_os = "espidf"))]
+fn stack_free_min(_task: usize) -> Option<u32> {
+    None
+}
+
+/* ------------ uptime / heap ------------------------------------------- */
+
+#[cfg(target_os = "espidf")]
+pub fn uptime() -> Duration {
+    Duration::from_micros(unsafe { esp_idf_svc::sys::esp_timer_get_time() } as u64)
+}
+
+/// pe PC: de la primul apel
+#[cfg(not(target_os = "espidf"))]
+pub fn uptime() -> Duration {
+    use std::{sync::OnceLock, time::Instant};
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed()
+}
+
+#[cfg(target_os = "espidf")]
+fn heap() -> Value {
+    use esp_idf_svc::sys::*;
+    unsafe {
+        let psram = heap_caps_get_total_size(MALLOC_CAP_SPIRAM) > 0;
+        json!({
+            "free":          esp_get_free_heap_size(),
+            "min_free":      esp_get_minimum_free_heap_size(),
+            "largest_block": heap_caps_get_largest_free_block(MALLOC_CAP_8BIT),
+            "psram_free":    psram.then(|| heap_caps_get_free_size(MALLOC_CAP_SPIRAM)),
+        })
+    }
+}
+
+#[cfg(not(target_os = "espidf"))]
+fn heap() -> Value {
+    Value::Null
+}
+
+/* ------------ raportul ------------------------------------------------- */
+
+fn cloud(service: &Service) -> Value {
+    let last = service.last_call().map(|c| json!({
+        "api":        c.api,
+        "latency_ms": c.latency.as_millis() as u64,
+        "error":      c.error,
+        "ago_s":      c.at.elapsed().as_secs(),
+    }));
+    json!({ "circuit_open": service.is_open(), "last_call": last })
+}
+
+pub fn snapshot(motion: &Mutex<Motion>, hub: &Hub) -> Value {
+    let (speeds, angles) = {
+        let m = motion.lock().unwrap();
+        (m.speeds(), m.angles())
+    };
+    let stacks: serde_json::Map<String, Value> = STACKS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|&(name, task)| (name.to_owned(), json!(stack_free_min(task))))
+        .collect();
+    let queues: serde_json::Map<String, Value> =
+        queue::depths().into_iter().map(|(name, n)| (name.to_owned(), json!(n))).collect();
+    let wifi = WIFI.lock().unwrap().clone().map(|w| json!({ "ssid": w.ssid, "rssi": w.rssi, "ip": w.ip }));
+
+    json!({
+        "version":  crate::VERSION,
+        "uptime_s": uptime().as_secs(),
+        "heap":     heap(),
+        "stack_free_min": stacks,
+        "wifi":     wifi,
+        "cloud":    { "openai": cloud(&retry::OPENAI), "azure": cloud(&retry::AZURE) },
+        "queues":   queues,
+        "motion":   {
+            "left":        speeds.0,
+            "right":       speeds.1,
+            "servo_left":  angles.0,
+            "servo_right": angles.1,
+        },
+        "ws_clients": hub.clients(),
+        "update":   UPDATE.lock().unwrap().clone(),
+        "clock":    { "synced": clock::synced(), "now": clock::now().map(|t| t.iso8601()) },
+    })
+}
@@ -0,0 +1,357 @@
+//! Textul unei replici pregătit pentru vorbire: fără Markdown (ChatGPT
+//! răspunde cu liste, **bold** şi blocuri de cod), iar în română cu
+//! numerele, unităţile şi abrevierile scrise în litere. Escaparea XML se
+//! face la construirea SSML-ului (`azure_tts::ssml`).
+
+use crate::lang;
+
+/// Markdown scos; numerele şi abrevierile expandate dacă `locale` e română.
+pub fn prepare(text: &str, locale: &str) -> String {
+    let plain = strip_markdown(text);
+    if lang::language_of(locale) == "ro" {
+        expand_ro(&plain)
+    } else {
+        plain
+    }
+}
+
+/// `&`, `<`, `>` şi ghilimelele ca entităţi XML.
+pub fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 16);
+    for c in s.chars() {
+        match c {
+            '&'  => out.push_str("&amp;"),
+            '<'  => out.push_str("&lt;"),
+            '>'  => out.push_str("&gt;"),
+            '"'  => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c    => out.push(c),
+        }
+    }
+    out
+}
+
+/* ------------ Markdown ------------------------------------------------ */
+
+/// Titluri, citate, liste, tabele, accente, link-uri şi cod → text simplu.
+/// Conţinutul blocurilor de cod rămâne, fără marcaje.
+pub fn strip_markdown(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut fence = false;
+    for line in text.lines() {
+        let mut t = line.trim();
+        if t.starts_with("```") || t.starts_with("~~~") {
+            fence = !fence;
+            continue;
+        }
+        if fence {
+            out.push(line.trim_end().to_owned());
+            continue;
+        }
+        // linie orizontală: `---`, `***`, `___`
+        if t.len() >= 3 && t.chars().all(|c| matches!(c, '-' | '*' | '_' | ' ')) {
+            continue;
+        }
+
+        let heading = t.starts_with('#');
+        t = t.trim_start_matches('#').trim_start();
+        while let Some(r) = t.strip_prefix('>') {
+            t = r.trim_start();
+        }
+        let mut item = false;
+        if let Some(r) = ["- ", "* ", "+ ", "• "].iter().find_map(|b| t.strip_prefix(b)) {
+            t = r.trim_start();
+            item = true;
+        } else if let Some(dot) = t.find(['.', ')']) {
+            item = dot > 0 && dot <= 3 && t[..dot].bytes().all(|b| b.is_ascii_digit()) && t[dot + 1..].starts_with(' ');
+        }
+
+        let mut s = if t.starts_with('|') && t.ends_with('|') {
+            let cells: Vec<&str> = t.trim_matches('|').split('|').map(str::trim).collect();
+            // rândul `|---|:---:|` de sub capul de tabel
+            if cells.iter().all(|c| c.chars().all(|c| matches!(c, '-' | ':'))) {
+                continue;
+            }
+            item = true;
+            inline(&cells.join(", "))
+        } else {
+            inline(t)
+        };
+        // o pauză după titluri şi elementele de listă
+        if (heading || item) && !s.is_empty() && !s.ends_with(['.', '!', '?', ':', ';', ',']) {
+            s.push('.');
+        }
+        out.push(s);
+    }
+    out.join("\n").trim().to_owned()
+}
+
+/// Accente, cod inline, link-uri şi imagini dintr-o linie.
+fn inline(s: &str) -> String {
+    let s = links(s);
+    let s = s.replace("**", "").replace("__", "").replace("~~", "").replace('`', "");
+    // `*`/`_` simple doar lângă un cuvânt: `2 * 3` şi `snake_case` rămân
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '*' || c == '_' {
+            let before = i.checked_sub(1).map(|j| chars[j]);
+            let after = chars.get(i + 1).copied();
+            let word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+            let marker = if c == '*' { word(before) != word(after) } else { !(word(before) && word(after)) };
+            if marker {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// `[text](url)` → `text`, `![alt](url)` → `alt`
+fn links(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|i| open + i) else { break };
+        let Some(end) = rest[close..].find(')').map(|i| close + i) else { break };
+        out.push_str(rest[..open].strip_suffix('!').unwrap_or(&rest[..open]));
+        out.push_str(&rest[open + 1..close]);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/* ------------ română: numere, unităţi, abrevieri ---------------------- */
+
+/// genul substantivului de după număr
+#[derive(Clone, Copy, PartialEq)]
+enum Gender {
+    /// număr citit singur: „unu”, „doi”
+    Count,
+    Masculine,
+    Neuter,
+    Feminine,
+}
+
+use Gender::*;
+
+/// simbol → (singular, plural, gen); cele mai lungi întâi
+const UNITS: [(&str, &str, &str, Gender); 10] = [
+    ("%",    "la sută",          "la sută",          Count),
+    ("°C",   "grad Celsius",     "grade Celsius",    Neuter),
+    ("°",    "grad",             "grade",            Neuter),
+    ("km/h", "kilometru pe oră", "kilometri pe oră", Masculine),
+    ("km",   "kilometru",        "kilometri",        Masculine),
+    ("cm",   "centimetru",       "centimetri",       Masculine),
+    ("mm",   "milimetru",        "milimetri",        Masculine),
+    ("kg",   "kilogram",         "kilograme",        Neuter),
+    ("ms",   "milisecundă",      "milisecunde",      Feminine),
+    ("lei",  "leu",              "lei",              Masculine),
+];
+
+/// cele mai lungi întâi, ca `ş.a.` să nu taie `ş.a.m.d.`
+const ABBREVIATIONS: [(&str, &str); 16] = [
+    ("ş.a.m.d.", "şi aşa mai departe"),
+    ("ș.a.m.d.", "și așa mai departe"),
+    ("ş.a.",     "şi altele"),
+    ("ș.a.",     "și altele"),
+    ("aprox.",   "aproximativ"),
+    ("prof.",    "profesorul"),
+    ("etc.",     "etcetera"),
+    ("d-na",     "doamna"),
+    ("dna.",     "doamna"),
+    ("str.",     "strada"),
+    ("dl.",      "domnul"),
+    ("dr.",      "doctorul"),
+    ("nr.",      "numărul"),
+    ("ex.",      "exemplu"),
+    ("&",        "şi"),
+    ("=",        "egal"),
+];
+
+/// Numerele (cu unitatea de după ele) şi abrevierile în litere. Cifrele
+/// lipite de litere (`ESP32`, `mp3`, `GPT-4`) rămân cum sunt.
+pub fn expand_ro(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 2);
+    let mut rest = text;
+    let mut prev: Option<char> = None;
+    while let Some(c) = rest.chars().next() {
+        // `GPT-4`: cratima după o literă leagă cifra de cuvânt
+        let before = &text[..text.len() - rest.len()];
+        let hyphenated = before.strip_suffix('-').is_some_and(|b| b.ends_with(char::is_alphabetic));
+        let boundary = !prev.is_some_and(|p| p.is_alphanumeric() || p == '_') && !hyphenated;
+        if boundary {
+            if let Some((abbr, full)) = ABBREVIATIONS.iter().find(|(a, _)| rest.starts_with(a)) {
+                let after = rest[abbr.len()..].chars().next();
+                // `&`/`=` doar între spaţii; abrevierile doar ca cuvinte întregi
+                let standalone = if abbr.len() == 1 { after.is_some_and(char::is_whitespace) } else { !after.is_some_and(char::is_alphanumeric) };
+                if standalone {
+                    out.push_str(full);
+                    rest = &rest[abbr.len()..];
+                    // la sfârşit de frază punctul abrevierii era şi al frazei
+                    let next = rest.trim_start_matches(' ');
+                    if abbr.ends_with('.') && (next.is_empty() || next.starts_with('\n')) {
+                        out.push('.');
+                    }
+                    prev = full.chars().last();
+                    continue;
+                }
+            }
+            if c.is_ascii_digit() || (c == '-' && rest[1..].starts_with(|d: char| d.is_ascii_digit())) {
+                if let Some((spoken, len)) = number(rest) {
+                    out.push_str(&spoken);
+                    rest = &rest[len..];
+                    prev = spoken.chars().last();
+                    continue;
+                }
+            }
+        }
+        out.push(c);
+        prev = Some(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Numărul de la începutul lui `s` (cu semn, separatori de mii `.`,
+/// zecimale după `,` sau `.` şi unitate) → (în litere, octeţi consumaţi).
+fn number(s: &str) -> Option<(String, usize)> {
+    let b = s.as_bytes();
+    let digits = |from: usize| from + b[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let negative = b[0] == b'-';
+    let start = negative as usize;
+    let mut end = digits(start);
+    let mut int = s[start..end].to_owned();
+
+    // 1.000.000 – doar grupuri de exact trei cifre
+    let mut grouped = false;
+    let first = end - start;
+    while first <= 3 && int.len() <= 12 && b.get(end) == Some(&b'.') && digits(end + 1) == end + 4 {
+        int.push_str(&s[end + 1..end + 4]);
+        end += 4;
+        grouped = true;
+    }
+    let mut frac = None;
+    if matches!(b.get(end), Some(b',')) || (!grouped && b.get(end) == Some(&b'.')) {
+        let last = digits(end + 1);
+        if last > end + 1 {
+            frac = Some(&s[end + 1..last]);
+            end = last;
+        }
+    }
+    if int.len() > 12 {
+        return None;
+    }
+
+    // unitatea, lipită sau după un spaţiu
+    let gap = usize::from(b.get(end) == Some(&b' '));
+    let unit = UNITS.iter().find(|(sym, ..)| {
+        let tail = &s[end + gap..];
+        tail.starts_with(sym) && !tail[sym.len()..].starts_with(char::is_alphanumeric)
+    });
+    // `3D`, `5G`, `4K` – nu e un număr de citit
+    if unit.is_none() && s[end..].starts_with(char::is_alphabetic) {
+        return None;
+    }
+
+    let n: u64 = int.parse().ok()?;
+    let gender = unit.map_or(Count, |u| u.3);
+    let mut spoken = String::new();
+    if negative {
+        spoken.push_str("minus ");
+    }
+    match (frac, unit) {
+        (Some(frac), _) => {
+            spoken += &cardinal(n, gender);
+            spoken += " virgulă ";
+            let zeros = frac.len() - frac.trim_start_matches('0').len();
+            spoken += &vec!["zero"; zeros].join(" ");
+            if let Ok(rest) = frac[zeros..].parse::<u64>() {
+                spoken += &format!("{}{}", if zeros > 0 { " " } else { "" }, cardinal(rest, Count));
+            }
+            if let Some((_, _, plural, _)) = unit {
+                spoken += &format!(" {plural}");
+            }
+        }
+        (None, Some(&(_, singular, plural, gender))) => spoken += &with_noun(n, singular, plural, gender),
+        (None, None) => spoken += &cardinal(n, Count),
+    }
+    let used = end + unit.map_or(0, |u| gap + u.0.len());
+    Some((spoken, used))
+}
+
+/// `n` urmat de substantiv: „un kilometru”, „două grade”, „douăzeci de lei”.
+fn with_noun(n: u64, singular: &str, plural: &str, gender: Gender) -> String {
+    if gender == Count {
+        return format!("{} {plural}", cardinal(n, Count));
+    }
+    if n == 1 {
+        return format!("{} {singular}", if gender == Feminine { "o" } else { "un" });
+    }
+    let de = n >= 20 && (n % 100 == 0 || n % 100 >= 20);
+    format!("{}{} {plural}", cardinal(n, gender), if de { " de" } else { "" })
+}
+
+/// Numeral cardinal până la 999 999 999 999.
+fn cardinal(n: u64, gender: Gender) -> String {
+    if n == 0 {
+        return "zero".into();
+    }
+    let mut parts = Vec::new();
+    for (scale, singular, plural, g) in [
+        (1_000_000_000, "miliard", "miliarde", Neuter),
+        (1_000_000, "milion", "milioane", Neuter),
+        (1_000, "mie", "mii", Feminine),
+    ] {
+        let count = n / scale % 1000;
+        if count > 0 {
+            parts.push(with_noun(count, singular, plural, g));
+        }
+    }
+    if n % 1000 > 0 {
+        parts.push(below_1000(n % 1000, gender));
+    }
+    parts.join(" ")
+}
+
+fn below_1000(n: u64, gender: Gender) -> String {
+    let mut parts = Vec::new();
+    match n / 100 {
+        0 => {}
+        1 => parts.push("o sută".to_owned()),
+        h => parts.push(format!("{} sute", digit(h, Feminine))),
+    }
+    let rest = n % 100;
+    if rest > 0 {
+        parts.push(below_100(rest, gender));
+    }
+    parts.join(" ")
+}
+
+fn below_100(n: u64, gender: Gender) -> String {
+    const TEENS: [&str; 10] = [
+        "zece", "unsprezece", "doisprezece", "treisprezece", "paisprezece",
+        "cincisprezece", "şaisprezece", "şaptesprezece", "optsprezece", "nouăsprezece",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "douăzeci", "treizeci", "patruzeci", "cincizeci", "şaizeci", "şaptezeci", "optzeci", "nouăzeci",
+    ];
+    match n {
+        0..=9 => digit(n, gender).into(),
+        12 if matches!(gender, Neuter | Feminine) => "douăsprezece".into(),
+        10..=19 => TEENS[n as usize - 10].into(),
+        _ if n % 10 == 0 => TENS[n as usize / 10].into(),
+        _ => format!("{} şi {}", TENS[n as usize / 10], digit(n % 10, gender)),
+    }
+}
+
+fn digit(n: u64, gender: Gender) -> &'static str {
+    match (n, gender) {
+        (1, Feminine) => "una",
+        (1, _) => "unu",
+        (2, Neuter | Feminine) => "două",
+        _ => ["zero", "unu", "doi", "trei", "patru", "cinci", "şase", "şapte", "opt", "nouă"][n as usize],
+    }
+}
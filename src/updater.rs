@@ -0,0 +1,399 @@
+//! Actualizări trase de robot (flota): la fiecare `interval` citeşte un
+//! manifest JSON de pe serverul de actualizări şi, dacă versiunea e mai
+//! nouă, descarcă imaginea şi o scrie prin `ota` – doar în fereastra de
+//! mentenanţă, ca robotul să nu repornească în mijlocul unei conversaţii.
+//!
+//! Manifest: `{"version": "0.3.0", "url": "https://…/fw.bin", "sha256": "…",
+//! "min_version": "0.2.0", "signature": "…"}`. `url` poate fi relativ la
+//! manifest; `signature` (ECDSA-P256, vezi `ota`) doar pentru firmware cu
+//! `OTA_PUBKEY`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use embedded_svc::http::Method;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex, RwLock, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::clock;
+use crate::hal::{HttpClient, KeyValueStore};
+use crate::ota::{self, Ota};
+
+const KEY_URL:      &str = "ota_url";
+const KEY_INTERVAL: &str = "ota_every";
+const KEY_WINDOW:   &str = "ota_window";
+
+/// verificare la 6 ore, dacă nu e configurat altfel
+pub const DEFAULT_INTERVAL_MIN: u32 = 6 * 60;
+/// prima verificare – după ce imaginea curentă s-a declarat sănătoasă
+const FIRST_CHECK: Duration = Duration::from_secs(90);
+/// în afara ferestrei se reîncearcă mai des, ca să nu fie ratată
+const WINDOW_RETRY: Duration = Duration::from_secs(15 * 60);
+const MAX_MANIFEST: usize = 2048;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Settings {
+    /// URL-ul manifestului; `None` = verificarea automată e oprită
+    pub url:          Option<String>,
+    pub interval_min: u32,
+    /// `"02:00-05:00"` (ora locală, vezi `clock`); `None` = oricând
+    pub window:       Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Manifest {
+    pub version:     String,
+    pub url:         String,
+    pub sha256:      String,
+    #[serde(default)]
+    pub min_version: Option<String>,
+    #[serde(default)]
+    pub signature:   Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullState {
+    /// fără URL de manifest
+    Disabled,
+    Idle,
+    Checking,
+    UpToDate,
+    /// versiune nouă, dar suntem în afara ferestrei de mentenanţă
+    Waiting,
+    /// versiunea curentă e sub `min_version` – trebuie încărcată manual
+    Blocked,
+    Downloading,
+    /// imaginea scrisă, robotul reporneşte
+    Ready,
+    Failed,
+}
+
+/// Starea raportată în `/api/status` şi `/api/ota/pull`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    pub state:        PullState,
+    /// secunde de la pornire la ultima verificare
+    pub checked_at_s: Option<u64>,
+    /// versiunea din manifest
+    pub available:    Option<String>,
+    pub received:     usize,
+    pub total:        Option<usize>,
+    pub error:        Option<String>,
+}
+
+enum Wake {
+    /// verificare imediată, ignoră fereastra
+    CheckNow,
+    /// setări noi – recalculează următoarea verificare
+    Reschedule,
+}
+
+pub struct Updater {
+    ota:      Arc<Ota>,
+    http:     Mutex<Box<dyn HttpClient + Send>>,
+    kv:       Mutex<Box<dyn KeyValueStore + Send>>,
+    settings: RwLock<Settings>,
+    report:   Mutex<Report>,
+    wake:     Mutex<Option<Sender<Wake>>>,
+}
+
+impl Updater {
+    /// Setările din NVS; fără thread – vezi `start`.
+    pub fn new(
+        ota: Arc<Ota>,
+        http: impl HttpClient + Send + 'static,
+        kv: impl KeyValueStore + Send + 'static,
+    ) -> Result<Self> {
+        let settings = Settings {
+            url:          kv.get_str(KEY_URL)?.filter(|u| !u.is_empty()),
+            interval_min: kv
+                .get_str(KEY_INTERVAL)?
+                .and_then(|m| m.parse().ok())
+                .unwrap_or(DEFAULT_INTERVAL_MIN),
+            window:       kv.get_str(KEY_WINDOW)?.filter(|w| parse_window(w).is_some()),
+        };
+        let state = if settings.url.is_some() { PullState::Idle } else { PullState::Disabled };
+        let updater = Self {
+            ota,
+            http:     Mutex::new(Box::new(http)),
+            kv:       Mutex::new(Box::new(kv)),
+            settings: RwLock::new(settings),
+            report:   Mutex::new(Report {
+                state,
+                checked_at_s: None,
+                available:    None,
+                received:     0,
+                total:        None,
+                error:        None,
+            }),
+            wake:     Mutex::new(None),
+        };
+        updater.publish();
+        Ok(updater)
+    }
+
+    /// Thread-ul de verificare periodică; se opreşte când `Updater` dispare.
+    pub fn start(self) -> Result<Arc<Self>> {
+        let (tx, rx) = mpsc::channel();
+        *self.wake.lock().unwrap() = Some(tx);
+        let updater = Arc::new(self);
+        let weak: Weak<Self> = Arc::downgrade(&updater);
+
+        thread::Builder::new()
+            .name("ota_pull".into())
+            .stack_size(24 * 1024)          // TLS
+            .spawn(move || {
+                crate::status::watch_stack("ota_pull");
+                let mut next = Instant::now() + FIRST_CHECK;
+                loop {
+                    let wake = rx.recv_timeout(next.saturating_duration_since(Instant::now()));
+                    let Some(up) = weak.upgrade() else { break };
+                    match wake {
+                        Ok(Wake::CheckNow) => up.check(true),
+                        Ok(Wake::Reschedule) => {
+                            next = Instant::now() + FIRST_CHECK;
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Timeout) => up.check(false),
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+                    let every = Duration::from_secs(60 * up.settings().interval_min.max(1) as u64);
+                    next = Instant::now() + match up.report().state {
+                        PullState::Waiting | PullState::Failed => every.min(WINDOW_RETRY),
+                        _ => every,
+                    };
+                }
+            })?;
+        Ok(updater)
+    }
+
+    pub fn settings(&self) -> Settings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// Salvează în NVS; `window` se validează (`HH:MM-HH:MM`).
+    pub fn set_settings(&self, new: Settings) -> Result<()> {
+        if let Some(w) = &new.window {
+            if parse_window(w).is_none() {
+                bail!("fereastră invalidă: {w:?} (ex. \"02:00-05:00\")");
+            }
+        }
+        if let Some(u) = &new.url {
+            self.check_url(u)?;
+        }
+        if new.interval_min == 0 {
+            bail!("interval_min trebuie să fie ≥ 1");
+        }
+        {
+            let mut kv = self.kv.lock().unwrap();
+            kv.set_str(KEY_URL, new.url.as_deref().unwrap_or(""))?;
+            kv.set_str(KEY_INTERVAL, &new.interval_min.to_string())?;
+            kv.set_str(KEY_WINDOW, new.window.as_deref().unwrap_or(""))?;
+        }
+        let enabled = new.url.is_some();
+        *self.settings.write().unwrap() = new;
+        self.update_report(|r| {
+            if !enabled {
+                r.state = PullState::Disabled;
+            } else if r.state == PullState::Disabled {
+                r.state = PullState::Idle;
+            }
+        });
+        self.send(Wake::Reschedule);
+        Ok(())
+    }
+
+    pub fn report(&self) -> Report {
+        self.report.lock().unwrap().clone()
+    }
+
+    /// Cere thread-ului o verificare acum (în afara ferestrei). `false`
+    /// dacă nu e configurat un manifest.
+    pub fn check_now(&self) -> bool {
+        self.settings.read().unwrap().url.is_some() && self.send(Wake::CheckNow)
+    }
+
+    /// O verificare completă: manifest → versiune → fereastră → imagine.
+    /// `force` ignoră fereastra de mentenanţă.
+    pub fn check(&self, force: bool) -> PullState {
+        let settings = self.settings();
+        let Some(url) = settings.url else {
+            self.update_report(|r| r.state = PullState::Disabled);
+            return PullState::Disabled;
+        };
+        self.update_report(|r| {
+            r.state = PullState::Checking;
+            r.checked_at_s = Some(crate::status::uptime().as_secs());
+            r.error = None;
+        });
+
+        let res = self.run(&url, settings.window.as_deref(), force);
+        let state = match res {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("⬆️ actualizare: {e:#}");
+                self.update_report(|r| r.error = Some(format!("{e:#}")));
+                PullState::Failed
+            }
+        };
+        self.update_report(|r| r.state = state);
+        state
+    }
+
+    /// Fără semnătură doar TLS apără manifestul – şi deci `sha256` din el;
+    /// `http://` merge numai cu firmware compilat cu `OTA_PUBKEY`.
+    fn check_url(&self, url: &str) -> Result<()> {
+        if url.starts_with("https://") || (url.starts_with("http://") && self.ota.signed()) {
+            Ok(())
+        } else if url.starts_with("http://") {
+            bail!("URL-ul manifestului trebuie să fie https:// (http:// doar cu imagini semnate, OTA_PUBKEY)")
+        } else {
+            bail!("URL-ul manifestului trebuie să înceapă cu https://")
+        }
+    }
+
+    fn run(&self, url: &str, window: Option<&str>, force: bool) -> Result<PullState> {
+        self.check_url(url)?;
+        let mut http = self.http.lock().unwrap();
+        http.set_timeouts(Duration::from_secs(10), Duration::from_secs(30));
+
+        let manifest = {
+            let mut resp = http.request(Method::Get, url, &[("Accept", "application/json")], &[])?;
+            if resp.status() != 200 {
+                bail!("manifest: HTTP {}", resp.status());
+            }
+            let body = resp.read_up_to(MAX_MANIFEST + 1)?;
+            if body.len() > MAX_MANIFEST {
+                bail!("manifest prea mare (peste {MAX_MANIFEST} B)");
+            }
+            serde_json::from_slice::<Manifest>(&body).context("manifest invalid")?
+        };
+        self.update_report(|r| r.available = Some(manifest.version.clone()));
+
+        if !newer(&manifest.version, crate::VERSION) {
+            return Ok(PullState::UpToDate);
+        }
+        if let Some(min) = &manifest.min_version {
+            if newer(min, crate::VERSION) {
+                warn!("⬆️ {} cere minim {min}, rulează {}", manifest.version, crate::VERSION);
+                return Ok(PullState::Blocked);
+            }
+        }
+        if !force {
+            if let Some(w) = window {
+                if !now_in_window(w) {
+                    info!("⬆️ {} disponibil – aştept fereastra {w}", manifest.version);
+                    return Ok(PullState::Waiting);
+                }
+            }
+        }
+
+        let sha256 = ota::parse_digest(&manifest.sha256).ok_or_else(|| anyhow!("sha256 invalid în manifest"))?;
+        let signature = match &manifest.signature {
+            Some(s) => Some(ota::parse_signature(s).ok_or_else(|| anyhow!("signature invalid în manifest"))?),
+            None => None,
+        };
+        let image_url = resolve(url, &manifest.url);
+        info!("⬆️ descarc {} de la {image_url}", manifest.version);
+        self.update_report(|r| r.state = PullState::Downloading);
+
+        let mut resp = http.request(Method::Get, &image_url, &[], &[])?;
+        if resp.status() != 200 {
+            bail!("imagine: HTTP {}", resp.status());
+        }
+        let total = resp.header("Content-Length").and_then(|n| n.trim().parse().ok());
+        self.ota
+            .update(
+                |buf| resp.read(buf),
+                total,
+                Some(sha256),
+                signature,
+                |p| self.update_report(|r| (r.received, r.total) = (p.received, p.total)),
+            )
+            .map_err(|e| anyhow!("{e}"))?;
+
+        self.ota.restart_later(Duration::from_secs(1));
+        Ok(PullState::Ready)
+    }
+
+    fn send(&self, wake: Wake) -> bool {
+        self.wake.lock().unwrap().as_ref().is_some_and(|tx| tx.send(wake).is_ok())
+    }
+
+    fn update_report(&self, f: impl FnOnce(&mut Report)) {
+        f(&mut self.report.lock().unwrap());
+        self.publish();
+    }
+
+    fn publish(&self) {
+        crate::status::set_update(Some(self.report()));
+    }
+}
+
+/// `"1.2.3"` (opţional `v` în faţă, sufixul `-…` ignorat) → (1, 2, 3)
+pub fn parse_version(v: &str) -> Option<(u32, u32, u32)> {
+    let v = v.trim().trim_start_matches('v');
+    let v = v.split(['-', '+']).next()?;
+    let mut parts = v.split('.').map(|p| p.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    parts.next().is_none().then_some((major, minor, patch))
+}
+
+/// `a` e strict mai nouă decât `b`? Versiunile necitibile nu sunt mai noi.
+pub fn newer(a: &str, b: &str) -> bool {
+    matches!((parse_version(a), parse_version(b)), (Some(a), Some(b)) if a > b)
+}
+
+/// `"22:00-04:00"` → (minutul de început, minutul de sfârşit)
+fn parse_window(w: &str) -> Option<(u16, u16)> {
+    let minute = |hm: &str| -> Option<u16> {
+        let (h, m) = hm.trim().split_once(':')?;
+        let (h, m) = (h.parse::<u16>().ok()?, m.parse::<u16>().ok()?);
+        (h < 24 && m < 60).then_some(h * 60 + m)
+    };
+    let (start, end) = w.split_once('-')?;
+    Some((minute(start)?, minute(end)?))
+}
+
+/// Minutul zilei (ora locală) e în fereastră? Fereastra poate trece de miezul nopţii.
+pub fn in_window(window: &str, minute: u16) -> bool {
+    match parse_window(window) {
+        Some((start, end)) if start <= end => (start..end).contains(&minute),
+        Some((start, end)) => minute >= start || minute < end,
+        None => false,
+    }
+}
+
+/// Fără ceas sincronizat fereastra nu poate fi respectată – aşteptăm.
+fn now_in_window(window: &str) -> bool {
+    let Some(now) = clock::now() else {
+        warn!("⬆️ ceasul nu e sincronizat – fereastra de mentenanţă nu poate fi verificată");
+        return false;
+    };
+    in_window(window, now.minute_of_day())
+}
+
+/// URL-ul imaginii relativ la manifest: `fw.bin`, `/fw/x.bin` sau absolut.
+fn resolve(manifest: &str, url: &str) -> String {
+    if url.contains("://") {
+        return url.to_owned();
+    }
+    let scheme_end = manifest.find("://").map_or(0, |i| i + 3);
+    if url.starts_with('/') {
+        let host_end = manifest[scheme_end..].find('/').map_or(manifest.len(), |i| scheme_end + i);
+        format!("{}{url}", &manifest[..host_end])
+    } else {
+        let dir_end = manifest.rfind('/').filter(|&i| i >= scheme_end).map_or(manifest.len(), |i| i + 1);
+        let base = &manifest[..dir_end];
+        if base.ends_with('/') { format!("{base}{url}") } else { format!("{base}/{url}") }
+    }
+}
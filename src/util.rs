@@ -1,4 +1,4 @@
-//! Funcţii ajutătoare (deocamdată doar PCM → WAV)
+//! Funcţii ajutătoare: PCM → WAV, octeţi aleatori, hex, SHA-256.
 
 pub fn pcm_to_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
     let mut wav = Vec::with_capacity(44 + samples.len() * 2);
@@ -20,3 +20,46 @@ pub fn pcm_to_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
     for s in samples { wav.extend_from_slice(&s.to_le_bytes()); }
     wav
 }
+
+/// Octeţi din RNG-ul hardware (`esp_fill_random`); pe PC din `/dev/urandom`.
+pub fn random_bytes(buf: &mut [u8]) {
+    #[cfg(target_os = "espidf")]
+    unsafe {
+        esp_idf_svc::sys::esp_fill_random(buf.as_mut_ptr().cast(), buf.len());
+    }
+    #[cfg(not(target_os = "espidf"))]
+    {
+        use std::io::Read;
+        std::fs::File::open("/dev/urandom")
+            .and_then(|mut f| f.read_exact(buf))
+            .expect("/dev/urandom");
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// comparaţie în timp constant (nu scurge prefixul corect prin timp)
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// `"a1b2…"` → octeţi; `None` pentru lungime impară sau caractere non-hex.
+pub fn unhex(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/* ------------ SHA-256 ------------------------------------------------- */
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    use sha2::Digest;
+    sha2::Sha256::digest(data).into()
+}
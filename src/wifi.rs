@@ -0,0 +1,454 @@
+//! Wi-Fi în mod staţie: o listă de reţele cunoscute (prioritate + ultima
+//! conectare reuşită), alegerea după RSSI, backoff exponenţial între
+//! runde eşuate şi reconectare pe evenimentul de deconectare, nu pe
+//! sondarea dintr-o buclă.
+//!
+//! Cine depinde de reţea (mDNS, serverul HTTP, cloud-ul, MQTT când va
+//! exista) se abonează cu `subscribe` şi e anunţat la fiecare schimbare
+//! de conectivitate.
+
+use anyhow::{bail, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::Ipv4Addr,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Condvar, Mutex, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::hal::{ApInfo, IpConfig, KeyValueStore, StaticIp, WifiRadio};
+use crate::mdns;
+
+const KEY_NETWORKS: &str = "wifi_nets";
+const KEY_IP:       &str = "wifi_ip";
+
+pub const MAX_NETWORKS: usize = 8;
+/// `DHCPClientSettings` ţine hostname-ul în 30 de octeţi
+pub const MAX_DHCP_HOSTNAME: usize = 30;
+/// sub acest prag o reţea vizibilă se încearcă abia după celelalte
+pub const WEAK_RSSI: i32 = -85;
+/// cât de des se citeşte RSSI-ul cât suntem conectaţi
+const TICK: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Network {
+    pub ssid:     String,
+    /// gol = reţea deschisă
+    #[serde(default)]
+    pub pass:     String,
+    /// mai mare = încercată prima
+    #[serde(default)]
+    pub priority: u8,
+    /// numărul de ordine al ultimei conectări reuşite (0 = niciodată);
+    /// nu e timp – ceasul nu e setat înainte de Wi-Fi
+    #[serde(default)]
+    pub last_ok:  u32,
+}
+
+impl Network {
+    pub fn new(ssid: &str, pass: &str, priority: u8) -> Self {
+        Self { ssid: ssid.into(), pass: pass.into(), priority, last_ok: 0 }
+    }
+}
+
+/// Pauza după a n-a rundă eşuată: `min`, `2·min`, `4·min` … până la `max`.
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    pub const DEFAULT: Self = Self { min: Duration::from_secs(2), max: Duration::from_secs(5 * 60) };
+
+    pub fn delay(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return Duration::ZERO;
+        }
+        self.min.saturating_mul(1 << (failures - 1).min(16)).min(self.max)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Link {
+    Down,
+    Connecting { ssid: String },
+    Up { ssid: String, ip: Ipv4Addr, rssi: i32 },
+}
+
+impl Link {
+    pub fn is_up(&self) -> bool {
+        matches!(self, Self::Up { .. })
+    }
+
+    /// ce contează pentru abonaţi: reţeaua şi IP-ul, nu RSSI-ul
+    fn key(&self) -> Option<(&str, Ipv4Addr)> {
+        match self {
+            Self::Up { ssid, ip, .. } => Some((ssid, *ip)),
+            _ => None,
+        }
+    }
+}
+
+/// Ordinea încercărilor: reţelele văzute la scanare înaintea celor
+/// nevăzute (pot fi ascunse), cele slabe (`WEAK_RSSI`) după cele bune;
+/// apoi prioritatea, ultima reuşită şi semnalul.
+pub fn rank<'a>(known: &'a [Network], seen: &[ApInfo]) -> Vec<&'a Network> {
+    let rssi = |n: &Network| seen.iter().filter(|ap| ap.ssid == n.ssid).map(|ap| ap.rssi).max();
+    let mut order: Vec<_> = known.iter().map(|n| (n, rssi(n))).collect();
+    order.sort_by_key(|&(n, rssi)| {
+        std::cmp::Reverse((
+            rssi.is_some(),
+            rssi.is_some_and(|r| r >= WEAK_RSSI),
+            n.priority,
+            n.last_ok,
+            rssi.unwrap_or(i32::MIN),
+        ))
+    });
+    order.into_iter().map(|(n, _)| n).collect()
+}
+
+/// DHCP, cu numele implicit al robotului.
+pub fn dhcp() -> IpConfig {
+    IpConfig { hostname: mdns::DEFAULT_HOSTNAME.into(), static_ip: None }
+}
+
+/// Hostname valid pentru DHCP; adresa fixă în reţeaua gateway-ului, fără
+/// să fie adresa reţelei sau de broadcast.
+pub fn validate_ip(cfg: &IpConfig) -> Result<()> {
+    if !mdns::valid_hostname(&cfg.hostname) || cfg.hostname.len() > MAX_DHCP_HOSTNAME {
+        bail!("hostname invalid: {:?} (1–{MAX_DHCP_HOSTNAME} caractere a-z, 0-9, '-')", cfg.hostname);
+    }
+    let Some(StaticIp { ip, gateway, netmask, dns }) = &cfg.static_ip else { return Ok(()) };
+    let mask = u32::from(*netmask);
+    if mask.leading_ones() + mask.trailing_zeros() != 32 || !(8..=30).contains(&mask.leading_ones()) {
+        bail!("mască invalidă: {netmask} (ex. 255.255.255.0)");
+    }
+    let unicast = |a: &Ipv4Addr| !(a.is_unspecified() || a.is_broadcast() || a.is_multicast() || a.is_loopback());
+    if !unicast(ip) || !unicast(gateway) {
+        bail!("adresă sau gateway invalid");
+    }
+    let (ip_n, gw_n) = (u32::from(*ip), u32::from(*gateway));
+    if ip_n & mask != gw_n & mask {
+        bail!("{ip} şi gateway-ul {gateway} nu sunt în aceeaşi reţea /{}", mask.leading_ones());
+    }
+    if ip == gateway || ip_n & !mask == 0 || ip_n & !mask == !mask {
+        bail!("{ip} nu poate fi adresa robotului în reţeaua /{}", mask.leading_ones());
+    }
+    if dns.len() > 2 || !dns.iter().all(unicast) {
+        bail!("cel mult două servere DNS valide");
+    }
+    Ok(())
+}
+
+enum Wake {
+    /// legătura a căzut (eveniment sau RSSI dispărut) – reîncercare imediată
+    Lost,
+    /// lista de reţele s-a schimbat
+    Networks,
+    /// interfaţa STA refăcută (`set_ip_config`) – conexiune nouă
+    Reconnect,
+}
+
+type Listener = Box<dyn Fn(&Link) + Send>;
+
+pub struct WifiManager {
+    radio:     Mutex<Box<dyn WifiRadio + Send>>,
+    kv:        Mutex<Box<dyn KeyValueStore + Send>>,
+    networks:  Mutex<Vec<Network>>,
+    ip:        Mutex<IpConfig>,
+    backoff:   Backoff,
+    link:      Mutex<Link>,
+    changed:   Condvar,
+    listeners: Mutex<Vec<Listener>>,
+    wake:      Mutex<Option<Sender<Wake>>>,
+}
+
+impl WifiManager {
+    /// Reţelele şi configuraţia IP din NVS, aplicată pe interfaţă; nu se
+    /// conectează – vezi `start`.
+    pub fn new(
+        mut radio: impl WifiRadio + Send + 'static,
+        kv: impl KeyValueStore + Send + 'static,
+        backoff: Backoff,
+    ) -> Result<Self> {
+        let networks = match kv.get(KEY_NETWORKS)? {
+            Some(raw) => serde_json::from_slice(&raw).unwrap_or_else(|e| {
+                warn!("📶 lista de reţele din NVS e stricată ({e}) – o ignor");
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        let mut ip = kv
+            .get(KEY_IP)?
+            .and_then(|raw| serde_json::from_slice::<IpConfig>(&raw).ok())
+            .filter(|c| validate_ip(c).is_ok())
+            .unwrap_or_else(dhcp);
+        if let Err(e) = radio.configure_ip(&ip) {
+            // mai bine DHCP decât deloc
+            warn!("📶 configuraţia IP: {e:#} – revin la DHCP");
+            ip = dhcp();
+            radio.configure_ip(&ip)?;
+        }
+        Ok(Self {
+            radio:     Mutex::new(Box::new(radio)),
+            kv:        Mutex::new(Box::new(kv)),
+            networks:  Mutex::new(networks),
+            ip:        Mutex::new(ip),
+            backoff,
+            link:      Mutex::new(Link::Down),
+            changed:   Condvar::new(),
+            listeners: Mutex::new(Vec::new()),
+            wake:      Mutex::new(None),
+        })
+    }
+
+    /// Thread-ul care se conectează, urmăreşte RSSI-ul şi reconectează
+    /// cu backoff; se opreşte când `WifiManager` dispare.
+    pub fn start(self) -> Result<Arc<Self>> {
+        let (tx, rx) = mpsc::channel();
+        *self.wake.lock().unwrap() = Some(tx);
+        let mgr = Arc::new(self);
+        let weak: Weak<Self> = Arc::downgrade(&mgr);
+
+        thread::Builder::new().name("wifi".into()).stack_size(6 * 1024).spawn(move || {
+            crate::status::watch_stack("wifi");
+            let mut failures = 0;
+            let mut retry_at = Instant::now();
+            loop {
+                let up = weak.upgrade().map(|m| m.link().is_up());
+                let timeout = match up {
+                    None => break,
+                    Some(true) => TICK,
+                    Some(false) => retry_at.saturating_duration_since(Instant::now()),
+                };
+                let wake = rx.recv_timeout(timeout);
+                let Some(mgr) = weak.upgrade() else { break };
+                match wake {
+                    Ok(Wake::Lost | Wake::Reconnect) => {
+                        mgr.drop_link();
+                        failures = 0;
+                        retry_at = Instant::now();
+                    }
+                    Ok(Wake::Networks) => {
+                        failures = 0;
+                        retry_at = Instant::now();
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if mgr.link().is_up() {
+                    mgr.poll_rssi();
+                    continue;
+                }
+                if Instant::now() < retry_at {
+                    continue;
+                }
+                match mgr.connect_best() {
+                    Ok(_) => failures = 0,
+                    Err(e) => {
+                        failures += 1;
+                        let delay = mgr.backoff.delay(failures);
+                        warn!("📶 {e:#} – reîncerc în {} s", delay.as_secs());
+                        retry_at = Instant::now() + delay;
+                    }
+                }
+            }
+        })?;
+        Ok(mgr)
+    }
+
+    /* ------------ reţele cunoscute --------------------------------- */
+
+    pub fn networks(&self) -> Vec<Network> {
+        self.networks.lock().unwrap().clone()
+    }
+
+    /// Înlocuieşte lista (salvată în NVS) şi reîncearcă imediat dacă
+    /// robotul e fără reţea.
+    pub fn set_networks(&self, mut list: Vec<Network>) -> Result<()> {
+        if list.len() > MAX_NETWORKS {
+            bail!("cel mult {MAX_NETWORKS} reţele");
+        }
+        for (i, n) in list.iter().enumerate() {
+            if n.ssid.is_empty() || n.ssid.len() > 32 {
+                bail!("SSID invalid: {:?} (1–32 octeţi)", n.ssid);
+            }
+            if !n.pass.is_empty() && !(8..=63).contains(&n.pass.len()) {
+                bail!("{}: parola WPA2 are 8–63 caractere", n.ssid);
+            }
+            if list[..i].iter().any(|m| m.ssid == n.ssid) {
+                bail!("{}: apare de două ori", n.ssid);
+            }
+        }
+        // istoria reuşitelor rămâne la reţelele păstrate
+        {
+            let old = self.networks.lock().unwrap();
+            for n in list.iter_mut().filter(|n| n.last_ok == 0) {
+                n.last_ok = old.iter().find(|m| m.ssid == n.ssid).map_or(0, |m| m.last_ok);
+            }
+        }
+        self.save(&list)?;
+        *self.networks.lock().unwrap() = list;
+        self.send(Wake::Networks);
+        Ok(())
+    }
+
+    /// Adaugă sau actualizează o reţea.
+    pub fn add_network(&self, ssid: &str, pass: &str, priority: u8) -> Result<()> {
+        let mut list = self.networks();
+        match list.iter_mut().find(|n| n.ssid == ssid) {
+            Some(n) => {
+                n.pass = pass.into();
+                n.priority = priority;
+            }
+            None => list.push(Network::new(ssid, pass, priority)),
+        }
+        self.set_networks(list)
+    }
+
+    fn save(&self, list: &[Network]) -> Result<()> {
+        self.kv.lock().unwrap().set(KEY_NETWORKS, &serde_json::to_vec(list)?)
+    }
+
+    /* ------------ configuraţia IP ----------------------------------- */
+
+    pub fn ip_config(&self) -> IpConfig {
+        self.ip.lock().unwrap().clone()
+    }
+
+    /// Validează, reface interfaţa STA, salvează în NVS şi reconectează.
+    pub fn set_ip_config(&self, cfg: IpConfig) -> Result<()> {
+        validate_ip(&cfg)?;
+        self.radio.lock().unwrap().configure_ip(&cfg)?;
+        self.kv.lock().unwrap().set(KEY_IP, &serde_json::to_vec(&cfg)?)?;
+        match &cfg.static_ip {
+            Some(st) => info!("📶 adresă fixă {} (gateway {})", st.ip, st.gateway),
+            None => info!("📶 DHCP ca {}", cfg.hostname),
+        }
+        *self.ip.lock().unwrap() = cfg;
+        self.send(Wake::Reconnect);
+        Ok(())
+    }
+
+    /* ------------ legătura ------------------------------------------ */
+
+    pub fn link(&self) -> Link {
+        self.link.lock().unwrap().clone()
+    }
+
+    /// `f` e apelat din thread-ul `wifi` când robotul câştigă sau pierde
+    /// reţeaua ori primeşte alt IP. Nu are voie să apeleze `subscribe`.
+    pub fn subscribe(&self, f: impl Fn(&Link) + Send + 'static) {
+        self.listeners.lock().unwrap().push(Box::new(f));
+    }
+
+    /// Aşteaptă o conexiune; `false` dacă nu a venit în `timeout`.
+    pub fn wait_up(&self, timeout: Duration) -> bool {
+        let link = self.link.lock().unwrap();
+        let (link, _) = self.changed.wait_timeout_while(link, timeout, |l| !l.is_up()).unwrap();
+        link.is_up()
+    }
+
+    /// Apelată din handler-ul evenimentului de deconectare (sau când
+    /// RSSI-ul dispare). Contează doar dacă eram conectaţi – în timpul unei
+    /// încercări deconectările sunt ale noastre. Doar trezeşte thread-ul.
+    pub fn link_lost(&self) {
+        if self.link().is_up() {
+            self.send(Wake::Lost);
+        }
+    }
+
+    fn drop_link(&self) {
+        if let Link::Up { ssid, .. } = self.link() {
+            warn!("📶 {ssid}: legătura a căzut");
+            self.set_link(Link::Down);
+        }
+    }
+
+    /// O rundă: scanare, apoi reţelele cunoscute în ordinea `rank` până
+    /// reuşeşte una.
+    pub fn connect_best(&self) -> Result<Link> {
+        let known = self.networks();
+        if known.is_empty() {
+            self.set_link(Link::Down);
+            bail!("nicio reţea Wi-Fi configurată");
+        }
+        let mut radio = self.radio.lock().unwrap();
+        let seen = radio.scan().unwrap_or_else(|e| {
+            warn!("📶 scanare: {e:#}");
+            Vec::new()
+        });
+
+        let mut last_err = None;
+        for net in rank(&known, &seen) {
+            self.set_link(Link::Connecting { ssid: net.ssid.clone() });
+            match radio.connect(&net.ssid, &net.pass) {
+                Ok(ip) => {
+                    let rssi = radio.rssi().unwrap_or(0);
+                    drop(radio);
+                    info!("📶 conectat la {} – IP {ip}, {rssi} dBm", net.ssid);
+                    self.mark_ok(&net.ssid);
+                    let link = Link::Up { ssid: net.ssid.clone(), ip, rssi };
+                    self.set_link(link.clone());
+                    return Ok(link);
+                }
+                Err(e) => {
+                    warn!("📶 {}: {e:#}", net.ssid);
+                    last_err = Some(e);
+                }
+            }
+        }
+        self.set_link(Link::Down);
+        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("nicio reţea disponibilă")))
+    }
+
+    /// Cât suntem conectaţi: RSSI-ul nou; dacă a dispărut, legătura a căzut
+    /// fără eveniment.
+    fn poll_rssi(&self) {
+        let rssi = self.radio.lock().unwrap().rssi();
+        match rssi {
+            Some(dbm) => {
+                if let Link::Up { rssi, .. } = &mut *self.link.lock().unwrap() {
+                    *rssi = dbm;
+                }
+            }
+            None => self.drop_link(),
+        }
+    }
+
+    fn mark_ok(&self, ssid: &str) {
+        let mut list = self.networks.lock().unwrap();
+        let seq = list.iter().map(|n| n.last_ok).max().unwrap_or(0) + 1;
+        if let Some(n) = list.iter_mut().find(|n| n.ssid == ssid) {
+            n.last_ok = seq;
+        }
+        if let Err(e) = self.save(&list) {
+            warn!("📶 NVS: {e:#}");
+        }
+    }
+
+    fn set_link(&self, new: Link) {
+        let changed = {
+            let mut link = self.link.lock().unwrap();
+            let changed = link.key() != new.key();
+            *link = new.clone();
+            changed
+        };
+        self.changed.notify_all();
+        if changed {
+            for f in self.listeners.lock().unwrap().iter() {
+                f(&new);
+            }
+        }
+    }
+
+    fn send(&self, wake: Wake) -> bool {
+        self.wake.lock().unwrap().as_ref().is_some_and(|tx| tx.send(wake).is_ok())
+    }
+}
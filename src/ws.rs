@@ -0,0 +1,240 @@
+//! WebSocket `/ws`: comenzi JSON de la pagini (mers, servo, vorbire) şi
+//! evenimente trimise tuturor clienţilor conectaţi.
+//!
+//! Comenzi: `{"cmd":"drive","linear":0.5,"angular":0}`, `{"cmd":"stop"}`,
+//! `{"cmd":"servo","left":90}`, `{"cmd":"say","text":"Salut"}` (opţional cu
+//! `"voice": {"voice": "ro-RO-EmilNeural", "style": "cheerful"}`, sau cu
+//! `"ssml": "<speak …>…</speak>"` în locul textului), `{"cmd":"ping"}`.
+//!
+//! Browserul nu poate trimite `Authorization` la upgrade, aşa că primul
+//! mesaj e `{"cmd":"auth","token":"..."}`; până atunci sesiunea nu
+//! primeşte evenimente şi comenzile ei sunt refuzate.
+
+use anyhow::{anyhow, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::auth::Auth;
+use crate::azure_tts::{self, Speech, VoiceOptions};
+use crate::config::Section;
+use crate::drive::Drive;
+use crate::hal::{HttpServer, WsEvent, WsSender};
+use crate::motion::Motion;
+use crate::ota;
+use crate::queue::{self, Tx};
+use crate::servo::ServoId;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Command {
+    Auth { token: String },
+    /// joystick, trimis la ≥ 5 Hz cât timp e ţinut (vezi `drive`)
+    Drive { linear: f32, angular: f32 },
+    /// oprire imediată, fără rampă
+    Stop,
+    Servo { left: Option<f32>, right: Option<f32> },
+    /// `voice` – vezi `azure_tts::VoiceOptions`; `ssml` în locul lui `text`
+    /// = un document `<speak>` trimis neatins
+    Say {
+        #[serde(default)]
+        text: String,
+        #[serde(default)]
+        voice: VoiceOptions,
+        ssml: Option<String>,
+    },
+    /// ţine în viaţă comanda de mers curentă
+    Ping,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Transcript { text: String },
+    Reply { text: String },
+    TtsStart { text: String },
+    TtsStop,
+    Motors { left: i8, right: i8 },
+    Rssi { dbm: i32 },
+    Ota { state: ota::State, received: usize, total: Option<usize> },
+    /// setări salvate – paginile deschise le recitesc
+    Config { changed: Vec<Section> },
+    Pong,
+    Error { message: String },
+}
+
+struct Client {
+    session: i32,
+    authed:  bool,
+    sender:  Arc<Mutex<Box<dyn WsSender>>>,
+}
+
+type Clients = Arc<Mutex<Vec<Client>>>;
+
+/// Clienţii conectaţi. Trimiterea se face pe un thread separat: pe ESP
+/// `EspHttpWsDetachedSender::send` aşteaptă task-ul httpd, deci apelată
+/// dintr-un handler ar bloca serverul. Din acelaşi motiv lista nu e ţinută
+/// blocată cât se trimite – handlerele `/ws` şi `/api/status` o blochează
+/// şi ele, de pe task-ul httpd.
+pub struct Hub {
+    clients: Clients,
+    tx:      Tx<(Option<i32>, String)>,
+}
+
+impl Hub {
+    pub fn start() -> Arc<Self> {
+        let clients: Clients = Arc::default();
+        let (tx, rx) = queue::channel::<(Option<i32>, String)>("ws_events");
+
+        let out = clients.clone();
+        thread::Builder::new()
+            .name("ws_events".into())
+            .stack_size(6 * 1024)
+            .spawn(move || {
+                crate::status::watch_stack("ws_events");
+                while let Ok((to, json)) = rx.recv() {
+                    let targets: Vec<_> = out
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .filter(|c| match to {
+                            Some(to) => to == c.session,
+                            None     => c.authed,
+                        })
+                        .map(|c| (c.session, c.sender.clone()))
+                        .collect();
+
+                    let dead: Vec<i32> = targets
+                        .into_iter()
+                        .filter(|(_, sender)| {
+                            let mut sender = sender.lock().unwrap();
+                            sender.send_text(&json).is_err() || sender.is_closed()
+                        })
+                        .map(|(session, _)| session)
+                        .collect();
+                    if !dead.is_empty() {
+                        out.lock().unwrap().retain(|c| !dead.contains(&c.session));
+                    }
+                }
+            })
+            .expect("thread ws_events");
+
+        Arc::new(Self { clients, tx })
+    }
+
+    /// către toţi clienţii autentificaţi
+    pub fn publish(&self, event: &Event) {
+        self.queue(None, event);
+    }
+
+    pub fn send_to(&self, session: i32, event: &Event) {
+        self.queue(Some(session), event);
+    }
+
+    pub fn clients(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    fn authed(&self, session: i32) -> bool {
+        self.clients.lock().unwrap().iter().any(|c| c.session == session && c.authed)
+    }
+
+    fn queue(&self, to: Option<i32>, event: &Event) {
+        if let Ok(json) = serde_json::to_string(event) {
+            let _ = self.tx.send((to, json));
+        }
+    }
+}
+
+/// Înregistrează `/ws`; mersul trece prin `drive` (rampă + dead-man).
+pub fn register<S: HttpServer>(
+    srv: &mut S,
+    hub: Arc<Hub>,
+    motion: Arc<Mutex<Motion>>,
+    drive: Arc<Drive>,
+    auth: Arc<Auth>,
+    tx_tts: Tx<Speech>,
+) -> Result<()> {
+    srv.ws_handler("/ws", move |session, event| -> Result<()> {
+        let text = match event {
+            WsEvent::Open(sender) => {
+                info!("🔌 ws#{session} conectat");
+                hub.clients.lock().unwrap().push(Client {
+                    session,
+                    authed: false,
+                    sender: Arc::new(Mutex::new(sender)),
+                });
+                return Ok(());
+            }
+            WsEvent::Closed => {
+                info!("🔌 ws#{session} închis");
+                hub.clients.lock().unwrap().retain(|c| c.session != session);
+                return drive.release(session);
+            }
+            WsEvent::Text(text) => text,
+        };
+
+        let cmd = match serde_json::from_str::<Command>(text) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                hub.send_to(session, &Event::Error { message: format!("comandă invalidă: {e}") });
+                return Ok(());
+            }
+        };
+
+        match cmd {
+            Command::Auth { token } => {
+                if !auth.check_token(Some(&format!("Bearer {token}"))) {
+                    hub.send_to(session, &Event::Error { message: "token greşit".into() });
+                    return Ok(());
+                }
+                for c in hub.clients.lock().unwrap().iter_mut().filter(|c| c.session == session) {
+                    c.authed = true;
+                }
+                // starea motoarelor, ca pagina să pornească sincronizată
+                let (left, right) = motion.lock().unwrap().speeds();
+                hub.send_to(session, &Event::Motors { left, right });
+            }
+            _ if !hub.authed(session) => {
+                hub.send_to(session, &Event::Error { message: "neautentificat – trimite întâi cmd auth".into() });
+            }
+            Command::Drive { linear, angular } => {
+                drive.set(session, linear, angular);
+            }
+            Command::Stop => drive.stop()?,
+            Command::Servo { left, right } => {
+                let mut motion = motion.lock().unwrap();
+                if let Some(deg) = left {
+                    motion.servo(ServoId::Left, deg)?;
+                }
+                if let Some(deg) = right {
+                    motion.servo(ServoId::Right, deg)?;
+                }
+            }
+            Command::Say { text, voice, ssml } => {
+                let text = text.trim();
+                let checked = match &ssml {
+                    // ca la `/send_text`: vocea e în document
+                    Some(_) if voice != VoiceOptions::default() => Err(anyhow!("ssml conţine deja vocea – fără voice")),
+                    Some(doc) => azure_tts::check_ssml(doc),
+                    None => voice.validate(),
+                };
+                if let Err(e) = checked {
+                    hub.send_to(session, &Event::Error { message: format!("{e:#}") });
+                } else if let Some(doc) = ssml {
+                    tx_tts.send(Speech::ssml(doc))?;
+                } else if !text.is_empty() {
+                    tx_tts.send(Speech { text: text.to_owned(), voice, ssml: false })?;
+                }
+            }
+            Command::Ping => {
+                drive.heartbeat(session);
+                hub.send_to(session, &Event::Pong);
+            }
+        }
+        Ok(())
+    })
+}
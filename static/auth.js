@@ -0,0 +1,29 @@
+// Tokenul API al robotului, primit la asociere (pair.html) şi ţinut în
+// localStorage. Toate cererile care modifică ceva trec prin `api()`.
+
+const KEY = "myrobo.token";
+
+export const token = () => localStorage.getItem(KEY) || "";
+export const saveToken = t => localStorage.setItem(KEY, t);
+export const forget = () => localStorage.removeItem(KEY);
+
+// la 401 pagina trebuie (re)asociată
+function pair(){
+  forget();
+  location.href = "pair.html?next=" + encodeURIComponent(location.pathname);
+}
+
+// fetch cu `Authorization: Bearer …`
+export async function api(path, opts = {}){
+  const headers = {...opts.headers, Authorization: `Bearer ${token()}`};
+  const r = await fetch(path, {...opts, headers});
+  if (r.status === 401) pair();
+  return r;
+}
+
+// primul mesaj pe /ws; fără el robotul nu trimite evenimente
+export function wsAuth(sock){
+  sock.send(JSON.stringify({cmd:"auth", token:token()}));
+}
+
+if (!token() && !location.pathname.endsWith("pair.html")) pair();
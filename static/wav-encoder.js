@@ -1,6 +1,8 @@
 export default async function encodeWav(webmBuf, sampleRate) {
-  const ctx = new AudioContext();
+  // decodeAudioData re-eşantionează la rata contextului → antetul WAV e corect
+  const ctx = new AudioContext({ sampleRate });
   const audioBuf = await ctx.decodeAudioData(webmBuf);
+  ctx.close();
   const chan = audioBuf.getChannelData(0); 
   const pcm16 = new Int16Array(chan.length);
   for (let i=0;i<chan.length;i++) pcm16[i] = Math.max(-1,Math.min(1,chan[i]))*32767;
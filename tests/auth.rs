@@ -0,0 +1,177 @@
+//! `auth`: token bearer, asociere cu PIN, allowlist CORS, 401/403.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    auth::{Access, Auth, PairError, PAIR_ATTEMPTS, PAIR_LOCKOUT},
+    hal::{KeyValueStore, MemoryKv, RecordingMotors, RecordingServos},
+    motion::Motion,
+};
+use serde_json::{json, Value};
+use std::{thread, time::Duration};
+
+mod common;
+
+use common::Robot;
+
+fn start() -> Robot {
+    common::robot(Motion::new(RecordingMotors::default(), RecordingServos::default()), |_, _| {})
+}
+
+/// (status, `Access-Control-Allow-Origin`, corp JSON sau `null`)
+fn call(robot: &Robot, method: Method, path: &str, headers: &[(&str, &str)], body: &[u8]) -> (u16, Option<String>, Value) {
+    let reply = robot.request(method, path, headers, body);
+    (reply.status, reply.header("Access-Control-Allow-Origin").map(str::to_owned), reply.json())
+}
+
+#[test]
+fn token_survives_reboot_and_rotates() {
+    // prima pornire: 128 biţi aleatori, în hex
+    let fresh = Auth::load(MemoryKv::default()).unwrap().token();
+    assert_eq!(fresh.len(), 32);
+    assert_ne!(Auth::load(MemoryKv::default()).unwrap().token(), fresh);
+
+    let mut kv = MemoryKv::default();
+    let first = "0123456789abcdef0123456789abcdef";
+    kv.set_str("api_token", first).unwrap();
+    let auth = Auth::load(kv).unwrap();
+    assert_eq!(auth.token(), first);
+
+    let second = auth.rotate().unwrap();
+    assert_ne!(second, first);
+    assert!(!auth.check_token(Some(&format!("Bearer {first}"))));
+    assert!(auth.check_token(Some(&format!("Bearer {second}"))));
+}
+
+#[test]
+fn mutating_routes_need_the_token() {
+    let robot = start();
+    let bearer = format!("Bearer {}", robot.auth.token());
+
+    for (method, path) in [
+        (Method::Post,   "/send_text"),
+        (Method::Post,   "/transcribe"),
+        (Method::Post,   "/api/chat"),
+        (Method::Delete, "/api/drive"),
+        (Method::Get,    "/move/inainte"),
+        (Method::Get,    "/action/salut"),
+        (Method::Delete, "/api/pair"),
+        (Method::Get,    "/api/cors"),
+    ] {
+        let (status, _, body) = call(&robot, method, path, &[], b"x");
+        assert_eq!((status, body["error"]["kind"].as_str()), (401, Some("unauthorized")), "{path}");
+        assert_eq!(call(&robot, method, path, &[("Authorization", "Bearer gresit")], b"x").0, 401, "{path}");
+    }
+    assert_eq!(call(&robot, Method::Delete, "/api/drive", &[("Authorization", &bearer)], b"").0, 204);
+
+    // rutele care doar citesc rămân deschise
+    assert_eq!(call(&robot, Method::Get, "/hello", &[], b"").0, 200);
+    assert_eq!(call(&robot, Method::Get, "/pair.html", &[], b"").0, 200);
+}
+
+#[test]
+fn foreign_origins_are_forbidden_until_allowed() {
+    let robot = start();
+    let bearer = format!("Bearer {}", robot.auth.token());
+    let host = robot.addr.to_string();
+    let own = format!("http://{host}");
+    let evil = "http://evil.example";
+
+    // aceeaşi origine: fără CORS
+    let same = [("Authorization", bearer.as_str()), ("Origin", own.as_str())];
+    assert_eq!(call(&robot, Method::Delete, "/api/drive", &same, b"").0, 204);
+
+    let foreign = [("Authorization", bearer.as_str()), ("Origin", evil)];
+    let (status, acao, body) = call(&robot, Method::Delete, "/api/drive", &foreign, b"");
+    assert_eq!((status, acao, body["error"]["kind"].as_str()), (403, None, Some("forbidden")));
+    assert_eq!(call(&robot, Method::Options, "/api/drive", &[("Origin", evil)], b"").0, 403);
+
+    let put = json!({"origins": [evil]}).to_string();
+    let (status, _, body) = call(&robot, Method::Put, "/api/cors", &[("Authorization", &bearer)], put.as_bytes());
+    assert_eq!((status, body), (200, json!({"origins": [evil]})));
+
+    let (status, acao, _) = call(&robot, Method::Options, "/api/drive", &[("Origin", evil)], b"");
+    assert_eq!((status, acao.as_deref()), (204, Some(evil)));
+    let (status, acao, _) = call(&robot, Method::Delete, "/api/drive", &foreign, b"");
+    assert_eq!((status, acao.as_deref()), (204, Some(evil)));
+    // fără token rămâne 401, dar browserul poate citi răspunsul
+    let (status, acao, _) = call(&robot, Method::Delete, "/api/drive", &[("Origin", evil)], b"");
+    assert_eq!((status, acao.as_deref()), (401, Some(evil)));
+}
+
+#[test]
+fn pairing_with_the_spoken_pin_returns_the_token() {
+    let robot = start();
+    let pair = |pin: &str| call(&robot, Method::Post, "/api/pair", &[], json!({"pin": pin}).to_string().as_bytes());
+
+    assert_eq!(pair("123456").0, 409);
+
+    let (status, _, body) = call(&robot, Method::Post, "/api/pair/start", &[], b"");
+    assert_eq!((status, body), (202, json!({"expires_in": 120})));
+    let spoken = robot.tts.recv_timeout(Duration::from_secs(1)).unwrap().text;
+    let pin: String = spoken.chars().filter(char::is_ascii_digit).collect();
+    assert_eq!(pin.len(), 6, "{spoken}");
+
+    let wrong = if pin == "000000" { "111111" } else { "000000" };
+    assert_eq!(pair(wrong).0, 401);
+    let (status, _, body) = pair(&pin);
+    assert_eq!((status, body["token"].as_str()), (200, Some(robot.auth.token().as_str())));
+    // PIN-ul se foloseşte o singură dată
+    assert_eq!(pair(&pin).0, 409);
+
+    // prea multe greşeli: nici PIN nou nu se mai dă
+    assert_eq!(call(&robot, Method::Post, "/api/pair/start", &[], b"").0, 202);
+    let statuses: Vec<u16> = (0..PAIR_ATTEMPTS).map(|_| pair(wrong).0).collect();
+    assert_eq!(statuses.last(), Some(&429));
+    assert_eq!(call(&robot, Method::Post, "/api/pair/start", &[], b"").0, 429);
+}
+
+#[test]
+fn too_many_wrong_pins_cancel_the_pairing() {
+    let auth = Auth::load(MemoryKv::default()).unwrap();
+    let pin = auth.start_pairing().unwrap();
+    let wrong = if pin == "000000" { "111111" } else { "000000" };
+    for _ in 1..PAIR_ATTEMPTS {
+        assert_eq!(auth.pair(wrong), Err(PairError::WrongPin));
+    }
+    assert_eq!(auth.pair(wrong), Err(PairError::TooManyAttempts));
+    assert!(matches!(auth.pair(&pin), Err(PairError::Locked(d)) if d <= PAIR_LOCKOUT));
+    assert!(matches!(auth.start_pairing(), Err(PairError::Locked(_))));
+
+    assert_eq!(auth.check(None, None, None), Access::Unauthorized);
+    assert_eq!(auth.check(None, Some("http://x"), Some("y")), Access::Forbidden);
+}
+
+#[test]
+fn new_pins_do_not_reset_the_attempts_and_lockouts_grow() {
+    let first = Duration::from_millis(100);
+    let auth = Auth::load(MemoryKv::default()).unwrap().with_pair_lockout(first, Duration::from_millis(250));
+    let guess_wrong = |auth: &Auth| {
+        let pin = auth.start_pairing().unwrap();
+        auth.pair(if pin == "000000" { "111111" } else { "000000" })
+    };
+
+    // un PIN nou la fiecare încercare nu dă încercări în plus
+    for _ in 1..PAIR_ATTEMPTS {
+        assert_eq!(guess_wrong(&auth), Err(PairError::WrongPin));
+    }
+    assert_eq!(guess_wrong(&auth), Err(PairError::TooManyAttempts));
+
+    for lockout in [first, first * 2, Duration::from_millis(250)] {
+        assert!(matches!(auth.start_pairing(), Err(PairError::Locked(d)) if d > lockout - Duration::from_millis(50) && d <= lockout));
+        thread::sleep(lockout);
+        for _ in 1..PAIR_ATTEMPTS {
+            assert_eq!(guess_wrong(&auth), Err(PairError::WrongPin));
+        }
+        assert_eq!(guess_wrong(&auth), Err(PairError::TooManyAttempts));
+    }
+
+    // o asociere reuşită şterge istoricul
+    thread::sleep(Duration::from_millis(250));
+    let pin = auth.start_pairing().unwrap();
+    auth.pair(&pin).unwrap();
+    for _ in 1..PAIR_ATTEMPTS {
+        assert_eq!(guess_wrong(&auth), Err(PairError::WrongPin));
+    }
+    assert_eq!(guess_wrong(&auth), Err(PairError::TooManyAttempts));
+    assert!(matches!(auth.pair("123456"), Err(PairError::Locked(d)) if d <= first));
+}
@@ -0,0 +1,150 @@
+//! `backup`: export/import al configuraţiei, cu sumă de control sau secrete criptate (AEAD).
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    backup::{self, BackupError, Change},
+    config::{Config, Section, Store, MASK, SCHEMA},
+    hal::MemoryKv,
+    http,
+    util::{hex, sha256},
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+mod common;
+
+/// robotul de pe care se exportă
+fn ours() -> Config {
+    let mut cfg = Config::default();
+    cfg.wifi.pass = "parola-acasa".into();
+    cfg.openai.key = "sk-robot-1".into();
+    cfg.azure.key = "az-robot-1".into();
+    cfg
+}
+
+/// alt robot: alte chei, altă voce
+fn other() -> Config {
+    let mut cfg = Config::default();
+    cfg.wifi.pass = "parola-atelier".into();
+    cfg.openai.key = "sk-robot-2".into();
+    cfg.azure.key = "az-robot-2".into();
+    cfg.azure.voice = "ro-RO-EmilNeural".into();
+    cfg
+}
+
+#[test]
+fn plain_exports_keep_secrets_on_the_robot() {
+    let mut source = ours();
+    source.openai.chat_model = "gpt-4o-mini".into();
+    let doc = backup::export(&source, None).unwrap();
+    let text = doc.to_string();
+    assert_eq!((doc["format"].as_str(), doc["schema"].as_u64()), (Some(backup::FORMAT), Some(SCHEMA as u64)));
+    assert!(doc["checksum"].is_string() && doc.get("secrets").is_none());
+    assert!(!text.contains(&source.azure.key) && !text.contains(&source.wifi.pass), "{text}");
+
+    // pe alt robot: setările vin din fişier, cheile rămân ale lui
+    let (cfg, schema, secrets) = backup::restore(&other(), &doc, None).unwrap();
+    assert_eq!((schema, secrets), (SCHEMA, false));
+    assert_eq!((cfg.openai.chat_model.as_str(), cfg.azure.voice.as_str()), ("gpt-4o-mini", "ro-RO-AlinaNeural"));
+    assert_eq!((cfg.azure.key.as_str(), cfg.wifi.pass.as_str()), ("az-robot-2", "parola-atelier"));
+
+    let mut tampered = doc.clone();
+    tampered["config"]["azure"]["gain"] = 8.into();
+    assert!(matches!(backup::restore(&other(), &tampered, None), Err(BackupError::Invalid(e)) if e.contains("suma de control")));
+    let mut newer = doc.clone();
+    newer["schema"] = (SCHEMA + 1).into();
+    assert!(matches!(backup::restore(&other(), &newer, None), Err(BackupError::Newer(s)) if s == SCHEMA + 1));
+    assert!(matches!(backup::restore(&other(), &json!({"wifi": {}}), None), Err(BackupError::Invalid(_))));
+}
+
+#[test]
+fn passphrase_encrypts_secrets_and_authenticates_the_file() {
+    let source = other();
+    let doc = backup::export(&source, Some("ceai de tei")).unwrap();
+    let text = doc.to_string();
+    assert_eq!(doc["secrets"]["cipher"], "chacha20poly1305");
+    assert!(doc["secrets"]["data"].is_string() && doc.get("checksum").is_none());
+    assert!(!text.contains("az-robot-2") && !text.contains("parola-atelier"), "{text}");
+
+    let (cfg, _, secrets) = backup::restore(&Config::default(), &doc, Some("ceai de tei")).unwrap();
+    assert!(secrets);
+    assert_eq!(cfg, source);
+
+    assert!(matches!(backup::restore(&Config::default(), &doc, None), Err(BackupError::NeedsPassphrase)));
+    assert!(matches!(backup::restore(&Config::default(), &doc, Some("cafea")), Err(BackupError::WrongPassphrase)));
+    let mut tampered = doc.clone();
+    tampered["config"]["openai"]["url"] = "https://proxy.example".into();
+    assert!(matches!(backup::restore(&Config::default(), &tampered, Some("ceai de tei")), Err(BackupError::WrongPassphrase)));
+    let mut other_nonce = doc.clone();
+    other_nonce["secrets"]["nonce"] = "00".repeat(12).into();
+    assert!(matches!(backup::restore(&Config::default(), &other_nonce, Some("ceai de tei")), Err(BackupError::WrongPassphrase)));
+    // fără blocul de secrete fişierul nu devine „necriptat”: nu are sumă de control
+    let mut stripped = doc.clone();
+    stripped.as_object_mut().unwrap().remove("secrets");
+    assert!(matches!(backup::restore(&Config::default(), &stripped, None), Err(BackupError::Invalid(_))));
+
+    // fişier valid, configuraţie invalidă
+    let mut bad = Config::default();
+    bad.pins.servo_left = 18;
+    let mut doc = backup::export(&Config::default(), None).unwrap();
+    doc["config"]["pins"] = serde_json::to_value(&bad.pins).unwrap();
+    doc.as_object_mut().unwrap().remove("checksum");
+    let sum = sha256(&serde_json::to_vec(&doc).unwrap());
+    doc["checksum"] = hex(&sum).into();
+    assert!(matches!(backup::restore(&Config::default(), &doc, None), Err(BackupError::Invalid(e)) if e.contains("pins.servo_left")));
+}
+
+#[test]
+fn import_reports_changes_and_serves_the_api() {
+    let store = Arc::new(Store::load(MemoryKv::default()).unwrap());
+    let file = backup::export(&other(), Some("tei")).unwrap();
+
+    // dry-run: raportul, nimic salvat
+    let report = backup::import(&store, &file, Some("tei"), true).unwrap();
+    assert!(report.dry_run && report.secrets && !report.restart);
+    assert_eq!(report.changed, [Section::Wifi, Section::Openai, Section::Azure]);
+    assert!(report.diff.contains(&Change { path: "azure.voice".into(), from: "ro-RO-AlinaNeural".into(), to: "ro-RO-EmilNeural".into() }));
+    assert!(report.diff.contains(&Change { path: "azure.key".into(), from: "".into(), to: MASK.into() }));
+    assert_eq!(*store.get(), Config::default());
+
+    let report = backup::import(&store, &file, Some("tei"), false).unwrap();
+    assert!(!report.dry_run);
+    assert_eq!(*store.get(), other());
+    assert!(backup::import(&store, &file, Some("tei"), false).unwrap().diff.is_empty());
+
+    // HTTP
+    let api = common::api(|srv, auth| http::register_backup(srv, auth, store.clone()));
+    let call = |method, path: &str, pass: Option<&str>, body: &str| {
+        let headers: Vec<_> = pass.map(|p| ("X-Passphrase", p)).into_iter().collect();
+        let reply = api.send(method, path, &headers, body.as_bytes());
+        (reply.status, reply.json(), reply.header("Content-Disposition").map(str::to_owned))
+    };
+
+    let (status, exported, disposition) = call(Method::Get, "/api/config/export", Some("tuica-veche"), "");
+    assert_eq!(status, 200);
+    assert!(disposition.unwrap().contains("myrobo-config.json"));
+    assert!(!exported.to_string().contains("sk-robot-2"));
+
+    // pe un robot nou: întâi proba
+    let fresh = Arc::new(Store::load(MemoryKv::default()).unwrap());
+    let api = common::api(|srv, auth| http::register_backup(srv, auth, fresh.clone()));
+    let import = |query: &str, pass: Option<&str>, body: &Value| {
+        let headers: Vec<_> = pass.map(|p| ("X-Passphrase", p)).into_iter().collect();
+        let reply = api.send(Method::Post, &format!("/api/config/import{query}"), &headers, body.to_string().as_bytes());
+        (reply.status, reply.json())
+    };
+
+    let (status, body) = import("?dry_run=1", Some("tuica-veche"), &exported);
+    assert_eq!((status, body["dry_run"].as_bool()), (200, Some(true)), "{body}");
+    assert_eq!(*fresh.get(), Config::default());
+    assert_eq!(import("", None, &exported).1["error"]["kind"], "passphrase_required");
+    assert_eq!(import("", Some("rachiu"), &exported).0, 422);
+    let mut newer = backup::export(&Config::default(), None).unwrap();
+    newer["schema"] = (SCHEMA + 1).into();
+    assert_eq!(import("", None, &newer).0, 409);
+    assert_eq!(import("", None, &json!({"format": "altceva"})).0, 400);
+
+    let (status, body) = import("", Some("tuica-veche"), &exported);
+    assert_eq!((status, body["dry_run"].as_bool()), (200, Some(false)), "{body}");
+    assert_eq!(*fresh.get(), other());
+}
@@ -0,0 +1,133 @@
+//! `clock`: fusuri orare POSIX, ora de vară, SNTP şi ora în prompt-ul de chat.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    clock::{self, Clock, Settings, Tz},
+    hal::{HostTimeSync, KeyValueStore, MemoryKv},
+    http,
+    openai::{self, Conversation},
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+mod common;
+
+/// `(an, lună, zi, oră, minut, secundă, vară?)`
+fn at(tz: &Tz, utc: i64) -> (i32, u8, u8, u8, u8, u8, bool) {
+    let t = tz.local(utc);
+    (t.year, t.month, t.day, t.hour, t.minute, t.second, t.dst)
+}
+
+#[test]
+fn romanian_summer_time_switches_on_the_last_sundays() {
+    let tz = Tz::parse(clock::DEFAULT_TZ).unwrap();
+    // 29 martie 2026, 01:00 UTC: 03:00 EET → 04:00 EEST
+    assert_eq!(at(&tz, 1_774_746_000 - 1), (2026, 3, 29, 2, 59, 59, false));
+    assert_eq!(at(&tz, 1_774_746_000), (2026, 3, 29, 4, 0, 0, true));
+    // 25 octombrie 2026, 01:00 UTC: 04:00 EEST → 03:00 EET
+    assert_eq!(at(&tz, 1_792_890_000 - 1), (2026, 10, 25, 3, 59, 59, true));
+    assert_eq!(at(&tz, 1_792_890_000), (2026, 10, 25, 3, 0, 0, false));
+
+    let t = tz.local(1_792_407_900);
+    assert_eq!(t.iso8601(), "2026-10-19T14:05:00+03:00");
+    assert_eq!(t.describe(), "luni, 19 octombrie 2026, ora 14:05");
+    assert_eq!((t.abbr.as_str(), t.minute_of_day()), ("EEST", 14 * 60 + 5));
+
+    // 29 februarie
+    assert_eq!(at(&tz, 1_709_208_000), (2024, 2, 29, 14, 0, 0, false));
+}
+
+#[test]
+fn other_zones_parse() {
+    // emisfera sudică: vara peste Anul Nou
+    let sydney = Tz::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+    assert_eq!(at(&sydney, 1_768_478_400), (2026, 1, 15, 23, 0, 0, true));
+    assert_eq!(at(&sydney, 1_775_318_400 - 1), (2026, 4, 5, 2, 59, 59, true));
+    assert_eq!(at(&sydney, 1_775_318_400), (2026, 4, 5, 2, 0, 0, false));
+
+    // fără oră de vară, nume între <>, minute, vest de Greenwich
+    assert_eq!(Tz::parse("<+03>-3").unwrap().local(0).iso8601(), "1970-01-01T03:00:00+03:00");
+    assert_eq!(Tz::parse("IST-5:30").unwrap().local(0).iso8601(), "1970-01-01T05:30:00+05:30");
+    assert_eq!(Tz::parse("UTC0").unwrap().local(1_792_407_900).hour, 11);
+    let ny = Tz::parse("EST5EDT").unwrap();
+    assert_eq!(ny.local(1_792_407_900).iso8601(), "2026-10-19T07:05:00-04:00");
+
+    for bad in ["", "EET", "E-2", "EET-2EEST,M3.5.0", "EET-2EEST,M13.5.0,M10.5.0", "EET-2 ceva", "<+03-3"] {
+        assert_eq!(Tz::parse(bad), None, "{bad:?}");
+    }
+}
+
+/// Starea globală (sincronizat, fusul orar) – un singur test o atinge.
+#[test]
+fn settings_sync_and_the_chat_prompt() {
+    assert!(clock::now().is_none());
+
+    // setările din NVS; un fus orar stricat → cel implicit
+    let mut kv = MemoryKv::default();
+    kv.set_str("ntp_servers", "ro.pool.ntp.org, time.cloudflare.com").unwrap();
+    kv.set_str("tz", "nu-e-fus-orar").unwrap();
+    let clock = Clock::new(HostTimeSync::default(), kv).unwrap();
+    assert_eq!(clock.settings(), Settings {
+        servers: vec!["ro.pool.ntp.org".into(), "time.cloudflare.com".into()],
+        tz:      clock::DEFAULT_TZ.into(),
+    });
+
+    let mut bad = clock.settings();
+    bad.tz = "EET-2EEST,M3.5.0".into();
+    assert!(clock.set_settings(bad).is_err());
+    let mut bad = clock.settings();
+    bad.servers = vec![];
+    assert!(clock.set_settings(bad).is_err());
+    let mut bad = clock.settings();
+    bad.servers = vec!["a.ro".into(); clock::MAX_SERVERS + 1];
+    assert!(clock.set_settings(bad).is_err());
+    let mut bad = clock.settings();
+    bad.servers = vec!["http://ntp".into()];
+    assert!(clock.set_settings(bad).is_err());
+    assert_eq!(clock.settings().servers.len(), 2);
+
+    // SNTP porneşte o singură dată
+
+    let sync = HostTimeSync::default();
+    let clock = Clock::new(sync.clone(), MemoryKv::default()).unwrap();
+    assert!(!clock.state().synced);
+    clock.start().unwrap();
+    clock.start().unwrap();
+    assert_eq!(sync.0.lock().unwrap().len(), 1);
+    let state = clock.state();
+    assert!(state.synced && state.last_sync_s.is_some());
+    assert!(state.now.unwrap().ends_with(":00"));
+
+    // servere noi → SNTP repornit; doar fusul orar → nu
+    let mut new = clock.settings();
+    new.tz = "UTC0".into();
+    clock.set_settings(new.clone()).unwrap();
+    assert_eq!(sync.0.lock().unwrap().len(), 1);
+    new.servers = vec!["ro.pool.ntp.org".into()];
+    clock.set_settings(new).unwrap();
+    assert_eq!(sync.0.lock().unwrap().last().unwrap(), &["ro.pool.ntp.org"]);
+    assert_eq!(clock::now().unwrap().offset_s, 0);
+
+    // „cât e ceasul?” – data intră în mesajul de sistem
+    let (_retry, cloud) = common::mock_cloud();
+    let mut http = common::cloud_client(&cloud);
+    let mut conv = Conversation::new("Eşti un robot.", 2);
+    openai::chat_in(&mut http, &mut conv, "Cât e ceasul?").unwrap();
+    let msgs = cloud.requests()[0].json().unwrap()["messages"].clone();
+    let system = msgs[0]["content"].as_str().unwrap().to_owned();
+    let now = clock::now().unwrap();
+    assert!(system.starts_with("Eşti un robot. Acum este "), "{system}");
+    assert!(system.contains(&now.year.to_string()) && system.ends_with("(UTC)."), "{system}");
+
+    // `/api/clock`
+    let api = common::api(|srv, auth| http::register_clock(srv, auth, Arc::new(clock)));
+    let call = |method, body: Value| api.call(method, "/api/clock", &body);
+    let (status, body) = call(Method::Get, json!(null));
+    assert_eq!((status, body["tz"].as_str(), body["synced"].as_bool()), (200, Some("UTC0"), Some(true)));
+    assert_eq!(body["servers"], json!(["ro.pool.ntp.org"]));
+    assert_eq!(call(Method::Put, json!({"tz": "Europa/Bucuresti"})).0, 400);
+    assert_eq!(call(Method::Put, json!({"servers": "pool.ntp.org"})).0, 400);
+    let (status, body) = call(Method::Put, json!({"tz": clock::DEFAULT_TZ}));
+    assert_eq!(status, 200, "{body}");
+    assert!(body["now"].as_str().is_some_and(|t| t.ends_with("+02:00") || t.ends_with("+03:00")), "{body}");
+}
@@ -0,0 +1,375 @@
+//! Clienţii OpenAI şi Azure rulaţi contra `mock-cloud`.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    audio, azure_auth::AzureAuth, azure_tts,
+    cloud_error::{lang_from, CloudError},
+    hal::{HttpClient, MemorySink},
+    openai::{self, Conversation},
+    util::pcm_to_wav,
+};
+use mock_cloud::{validate_ssml, Fault, MockCloud, Reply};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+mod common;
+
+use common::{cloud_client as client, mock_cloud};
+
+fn post_json(cloud: &MockCloud, path: &str, body: &Value) -> (u16, Option<String>, String) {
+    let mut http = client(cloud);
+    let url = format!("{}{path}", cloud.url());
+    let body = body.to_string();
+    let mut resp = http
+        .post(&url, &[("Authorization", "Bearer k"), ("Content-Type", "application/json")], body.as_bytes())
+        .unwrap();
+    let status = resp.status();
+    let ctype = resp.header("Content-Type").map(str::to_owned);
+    let out = resp.read_to_end().unwrap();
+    (status, ctype, String::from_utf8(out).unwrap())
+}
+
+/* ------------ Whisper ------------------------------------------------ */
+
+#[test]
+fn whisper_returns_canned_transcript() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.push_transcript("Ce faci, robotule?");
+
+    let text = openai::whisper_transcribe(&mut client(&cloud), &[0i16; 1600], "ro").unwrap();
+    assert_eq!(text, "Ce faci, robotule?");
+
+    let req = &cloud.requests()[0];
+    assert_eq!(req.path, "/v1/audio/transcriptions");
+    assert!(req.header("Content-Type").unwrap().starts_with("multipart/form-data; boundary="));
+}
+
+#[test]
+fn whisper_rejects_non_wav_upload() {
+    let (_retry, cloud) = mock_cloud();
+    let err = openai::whisper_wav(&mut client(&cloud), b"nu e wav", "ro").unwrap_err();
+    assert!(err.to_string().contains("400"));
+}
+
+#[test]
+fn whisper_checks_api_key() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.require_openai_key("sk-corect");
+    let wav = pcm_to_wav(&[0i16; 160], 16_000);
+    let err = openai::whisper_wav(&mut client(&cloud), &wav, "ro").unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(CloudError::Auth { service: "Whisper", .. })));
+}
+
+/* ------------ Chat --------------------------------------------------- */
+
+#[test]
+fn chat_returns_queued_reply_then_echo() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.push_reply(Reply::text("  Bine, mersi!  "));
+
+    let mut http = client(&cloud);
+    assert_eq!(openai::chat(&mut http, "Ce faci?").unwrap(), "Bine, mersi!");
+    assert_eq!(openai::chat(&mut http, "Salut").unwrap(), "Ai spus: Salut");
+
+    let body = cloud.requests()[0].json().unwrap();
+    assert_eq!(body["messages"][0], json!({"role": "user", "content": "Ce faci?"}));
+}
+
+#[test]
+fn chat_streams_server_sent_events() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.push_reply(Reply::text("Salut de la robot"));
+
+    let (status, ctype, body) = post_json(&cloud, "/v1/chat/completions", &json!({
+        "model": "gpt-3.5-turbo",
+        "stream": true,
+        "messages": [{"role": "user", "content": "hei"}]
+    }));
+    assert_eq!(status, 200);
+    assert_eq!(ctype.as_deref(), Some("text/event-stream"));
+
+    let events: Vec<&str> = body.lines().filter_map(|l| l.strip_prefix("data: ")).collect();
+    assert_eq!(events.last(), Some(&"[DONE]"));
+    let text: String = events[..events.len() - 1]
+        .iter()
+        .map(|e| serde_json::from_str::<Value>(e).unwrap())
+        .filter_map(|v| v["choices"][0]["delta"]["content"].as_str().map(str::to_owned))
+        .collect();
+    assert_eq!(text, "Salut de la robot");
+}
+
+#[test]
+fn chat_returns_tool_calls_only_for_offered_tools() {
+    let (_retry, cloud) = mock_cloud();
+    let req = json!({
+        "model": "gpt-3.5-turbo",
+        "messages": [{"role": "user", "content": "fă-mi cu mâna"}],
+        "tools": [{"type": "function", "function": {"name": "gesture", "parameters": {}}}]
+    });
+
+    cloud.push_reply(Reply::tool_call("gesture", json!({"name": "salut"})));
+    let (status, _, body) = post_json(&cloud, "/v1/chat/completions", &req);
+    assert_eq!(status, 200);
+    let v: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(v["choices"][0]["finish_reason"], "tool_calls");
+    let call = &v["choices"][0]["message"]["tool_calls"][0]["function"];
+    assert_eq!(call["name"], "gesture");
+    assert_eq!(call["arguments"], r#"{"name":"salut"}"#);
+
+    cloud.push_reply(Reply::tool_call("drive", json!({})));
+    assert_eq!(post_json(&cloud, "/v1/chat/completions", &req).0, 400);
+}
+
+#[test]
+fn chat_rejects_malformed_body() {
+    let (_retry, cloud) = mock_cloud();
+    let (status, _, body) = post_json(&cloud, "/v1/chat/completions", &json!({"model": "x"}));
+    assert_eq!(status, 400);
+    let v: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(v["error"]["type"], "invalid_request_error");
+}
+
+/* ------------ Azure TTS ---------------------------------------------- */
+
+#[test]
+fn tts_plays_pcm_proportional_to_text() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.tts_ms_per_char(10);
+
+    let mut sink = MemorySink::default();
+    azure_tts::tts_and_play(&mut client(&cloud), &AzureAuth::new(client(&cloud)), &mut sink, "Salut").unwrap();
+
+    // 5 caractere × 10 ms × 16 kHz × 2 B
+    assert_eq!(sink.pcm.len(), 5 * 10 * 16 * 2);
+    assert_eq!(sink.utterances, 1);
+
+    let req = cloud.requests().pop().unwrap();
+    assert_eq!(req.header("X-Microsoft-OutputFormat"), Some("raw-16khz-16bit-mono-pcm"));
+}
+
+#[test]
+fn tts_requires_subscription_key() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.require_azure_key("alta-cheie");
+    let err = azure_tts::tts_and_play(&mut client(&cloud), &AzureAuth::new(client(&cloud)), &mut MemorySink::default(), "x").unwrap_err();
+    let e = err.downcast_ref::<CloudError>().unwrap();
+    assert!(matches!(e, CloudError::Auth { service: "Azure token", message } if message.contains("invalid subscription key")), "{e:?}");
+    // cheia nu ajunge la sinteză
+    assert!(cloud.requests().iter().all(|r| r.path == "/sts/v1.0/issueToken"));
+}
+
+#[test]
+fn ssml_validation() {
+    let ok = r#"<speak version="1.0" xml:lang="ro-RO"><voice name="ro-RO-AlinaNeural">A &amp; B</voice></speak>"#;
+    assert_eq!(validate_ssml(ok).unwrap(), "A & B");
+
+    for bad in [
+        r#"<speak version="1.0" xml:lang="ro-RO"><voice name="v">A & B</voice></speak>"#,
+        r#"<speak version="1.0" xml:lang="ro-RO"><voice name="v">x</speak>"#,
+        r#"<speak version="1.0"><voice name="v">x</voice></speak>"#,
+        r#"<speak version="1.0" xml:lang="ro-RO">fără voce</speak>"#,
+        r#"<voice name="v">x</voice>"#,
+    ] {
+        assert!(validate_ssml(bad).is_err(), "{bad}");
+    }
+}
+
+/* ------------ erori injectate ---------------------------------------- */
+
+#[test]
+fn injected_status_errors_surface_in_clients() {
+    let (_retry, cloud) = mock_cloud();
+    cloud
+        .fail_next_on("/v1/chat", Fault::rate_limited(2))
+        .fail_next_on("/cognitiveservices", Fault::status(500));
+
+    let err = openai::chat(&mut client(&cloud), "hei").unwrap_err();
+    assert!(err.to_string().contains("429"));
+    assert_eq!(
+        err.downcast_ref::<CloudError>().unwrap().retry_after(),
+        Some(Duration::from_secs(2)),
+    );
+    let err = azure_tts::tts_and_play(&mut client(&cloud), &AzureAuth::new(client(&cloud)), &mut MemorySink::default(), "x").unwrap_err();
+    assert!(err.to_string().contains("500"));
+
+    // următoarea cerere merge normal
+    assert!(openai::chat(&mut client(&cloud), "hei").is_ok());
+}
+
+#[test]
+fn rate_limit_carries_retry_after_and_openai_error_body() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.fail_next(Fault::rate_limited(7));
+
+    let mut http = client(&cloud);
+    let mut resp = http
+        .post(&format!("{}/v1/chat/completions", cloud.url()), &[], b"{}")
+        .unwrap();
+    assert_eq!(resp.status(), 429);
+    assert_eq!(resp.header("Retry-After"), Some("7"));
+    let v: Value = serde_json::from_slice(&resp.read_to_end().unwrap()).unwrap();
+    assert_eq!(v["error"]["code"], "rate_limit_exceeded");
+}
+
+#[test]
+fn truncated_tts_body_is_an_error() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.fail_next_on("/cognitiveservices", Fault::Truncate(100));
+
+    let mut sink = MemorySink::default();
+    let err = azure_tts::tts_and_play(&mut client(&cloud), &AzureAuth::new(client(&cloud)), &mut sink, "Salut robot").unwrap_err();
+    assert!(err.to_string().contains("trunchiat"), "{err}");
+}
+
+#[test]
+fn slow_response_hits_client_timeout() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.fail_next(Fault::Delay(Duration::from_millis(1500)));
+
+    // `read_timeout` din politică: 1 s
+    assert!(openai::chat(&mut client(&cloud), "hei").is_err());
+}
+
+#[test]
+fn dropped_connection_is_an_error() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.fail_next(Fault::Drop);
+    assert!(openai::chat(&mut client(&cloud), "hei").is_err());
+}
+
+/* ------------ pipeline complet --------------------------------------- */
+
+#[test]
+fn transcribe_and_chat_against_mock() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.push_transcript("Cât e ceasul?").push_reply(Reply::text("E târziu."));
+
+    let wav = pcm_to_wav(&[0i16; 1600], 16_000);
+    let http = client(&cloud);
+    let mut conv = Conversation::default();
+    let (text, reply) = audio::transcribe_and_chat(&|| Ok(http.clone()), &mut conv, &wav).unwrap();
+    assert_eq!((text.as_str(), reply.as_str()), ("Cât e ceasul?", "E târziu."));
+
+    let paths: Vec<_> = cloud.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(paths, ["/v1/audio/transcriptions", "/v1/chat/completions"]);
+}
+
+#[test]
+fn conversation_keeps_recent_turns() {
+    let (_retry, cloud) = mock_cloud();
+    let mut http = client(&cloud);
+    let mut conv = Conversation::new("Eşti un robot.", 2);
+
+    for q in ["unu", "doi", "trei"] {
+        openai::chat_in(&mut http, &mut conv, q).unwrap();
+    }
+    assert_eq!(conv.len(), 2);
+
+    let msgs = cloud.requests()[2].json().unwrap()["messages"].clone();
+    let msgs: Vec<(&str, &str)> = msgs
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["role"].as_str().unwrap(), m["content"].as_str().unwrap()))
+        .collect();
+    assert_eq!(msgs, [
+        ("system", "Eşti un robot."),
+        ("user", "unu"), ("assistant", "Ai spus: unu"),
+        ("user", "doi"), ("assistant", "Ai spus: doi"),
+        ("user", "trei"),
+    ]);
+
+    // o eroare nu intră în istoric
+    cloud.fail_next(Fault::status(400));
+    assert!(openai::chat_in(&mut http, &mut conv, "patru").is_err());
+    conv.clear();
+    assert!(conv.is_empty());
+}
+
+#[test]
+fn unknown_route_is_404() {
+    let (_retry, cloud) = mock_cloud();
+    let mut http = client(&cloud);
+    let resp = http.request(Method::Get, &format!("{}/v2/nimic", cloud.url()), &[], b"").unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+/* ------------ erori tipizate ----------------------------------------- */
+
+fn chat_err(cloud: &MockCloud) -> CloudError {
+    let err = openai::chat(&mut client(cloud), "hei").unwrap_err();
+    err.downcast::<CloudError>().expect("CloudError")
+}
+
+#[test]
+fn openai_errors_are_typed() {
+    let (_retry, cloud) = mock_cloud();
+
+    cloud.fail_next(Fault::status(401));
+    let e = chat_err(&cloud);
+    assert!(matches!(&e, CloudError::Auth { message, .. } if message.contains("Incorrect API key")));
+    assert_eq!((e.kind(), e.http_status()), ("auth", 502));
+
+    cloud.fail_next(Fault::QuotaExceeded);
+    assert!(matches!(chat_err(&cloud), CloudError::Quota { .. }));
+
+    cloud.fail_next(Fault::rate_limited(3));
+    let e = chat_err(&cloud);
+    assert!(matches!(e, CloudError::RateLimited { .. }));
+    assert_eq!((e.retry_after(), e.http_status()), (Some(Duration::from_secs(3)), 429));
+
+    cloud.fail_next(Fault::status(500));
+    assert!(matches!(chat_err(&cloud), CloudError::Server { status: 500, .. }));
+
+    cloud.fail_next(Fault::Drop);
+    assert!(matches!(chat_err(&cloud), CloudError::Network { .. }));
+
+    cloud.fail_next(Fault::Delay(Duration::from_millis(1500)));
+    assert!(matches!(chat_err(&cloud), CloudError::Timeout { .. }));
+
+    cloud.fail_next(Fault::Body { status: 200, body: "<html>".into() });
+    assert!(matches!(chat_err(&cloud), CloudError::Malformed { service: "ChatGPT", .. }));
+}
+
+#[test]
+fn bad_request_keeps_provider_message() {
+    let (_retry, cloud) = mock_cloud();
+    let err = openai::whisper_wav(&mut client(&cloud), b"nu e wav", "ro").unwrap_err();
+    let e = err.downcast_ref::<CloudError>().unwrap();
+    match e {
+        CloudError::BadRequest { service, status, message } => {
+            assert_eq!((*service, *status), ("Whisper", 400));
+            assert!(message.contains("Invalid file format"), "{message}");
+        }
+        other => panic!("{other:?}"),
+    }
+    assert_eq!(e.http_status(), 422);
+    assert_eq!(e.to_json()["error"]["kind"], "bad_request");
+}
+
+#[test]
+fn azure_errors_are_typed() {
+    let (_retry, cloud) = mock_cloud();
+    let (auth, mut sink) = (AzureAuth::new(client(&cloud)), MemorySink::default());
+
+    cloud.fail_next_on("/cognitiveservices", Fault::QuotaExceeded);
+    let err = azure_tts::tts_and_play(&mut client(&cloud), &auth, &mut sink, "x").unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(CloudError::Quota { service: "Azure TTS", .. })));
+
+    cloud.fail_next_on("/cognitiveservices", Fault::status(400));
+    let err = azure_tts::tts_and_play(&mut client(&cloud), &auth, &mut sink, "x").unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(CloudError::BadRequest { status: 400, .. })));
+}
+
+#[test]
+fn spoken_fallback_follows_accept_language() {
+    assert_eq!(lang_from(Some("en-US,en;q=0.9,ro;q=0.8")), "en");
+    assert_eq!(lang_from(Some("de-DE, ru;q=0.5")), "ru");
+    assert_eq!(lang_from(Some("fr")), "ro");
+    assert_eq!(lang_from(None), "ro");
+
+    let e = CloudError::Timeout { service: "ChatGPT" };
+    assert!(e.spoken("ro").contains("prea încet"));
+    assert!(e.spoken("en").contains("too long"));
+    assert_ne!(e.spoken("ru"), e.spoken("ro"));
+}
@@ -0,0 +1,196 @@
+//! Ajutoare comune testelor de integrare (`mod common;` în fiecare fişier).
+
+#![allow(dead_code)]
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    audio::{Exchange, Job},
+    auth::Auth,
+    azure_tts::{Speech, VoiceCatalog, VoiceInfo},
+    config,
+    hal::{HostHttp, HostHttpServer, HttpClient, MemoryKv},
+    http,
+    motion::Motion,
+    queue,
+    retry::{self, Policy},
+    ws::Hub,
+};
+use mock_cloud::MockCloud;
+use serde_json::Value;
+use std::{
+    net::SocketAddr,
+    ops::Deref,
+    sync::{mpsc, Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+/* ------------ politica de retry -------------------------------------- */
+
+/// Fără reîncercări şi fără circuit breaker: testul vede exact răspunsul
+/// mock-ului.
+pub const ONE_SHOT: Policy = Policy {
+    connect_timeout: Duration::from_secs(1),
+    read_timeout:    Duration::from_secs(1),
+    attempts:        1,
+    breaker_after:   u32::MAX,
+    ..Policy::DEFAULT
+};
+
+static RETRY: Mutex<()> = Mutex::new(());
+
+/// Politica şi breaker-ele `OPENAI`/`AZURE` sunt globale: testele care le
+/// folosesc le iau pe rând, pornesc cu circuitele închise şi lasă în urmă
+/// `Policy::DEFAULT`.
+pub struct RetryScope(MutexGuard<'static, ()>);
+
+pub fn retry_scope(policy: Policy) -> RetryScope {
+    let guard = RETRY.lock().unwrap_or_else(|e| e.into_inner());
+    retry::set_policy(policy);
+    retry::OPENAI.reset();
+    retry::AZURE.reset();
+    RetryScope(guard)
+}
+
+impl Drop for RetryScope {
+    fn drop(&mut self) {
+        retry::set_policy(Policy::DEFAULT);
+        retry::OPENAI.reset();
+        retry::AZURE.reset();
+    }
+}
+
+/* ------------ mock-cloud --------------------------------------------- */
+
+/// `mock-cloud` pornit, cu `ONE_SHOT` până la sfârşitul testului.
+pub fn mock_cloud() -> (RetryScope, MockCloud) {
+    (retry_scope(ONE_SHOT), MockCloud::start())
+}
+
+/// OpenAI şi Azure (TTS + `issueToken`) redirecţionate spre `cloud`.
+pub fn cloud_client(cloud: &MockCloud) -> HostHttp {
+    let region = &config::get().azure.region;
+    HostHttp::new()
+        .redirect("https://api.openai.com", &cloud.url())
+        .redirect(&format!("https://{region}.tts.speech.microsoft.com"), &cloud.url())
+        .redirect(&format!("https://{region}.api.cognitive.microsoft.com"), &cloud.url())
+}
+
+/// Lista vocilor, după ce descărcarea din fundal a lui `catalog` s-a
+/// terminat (sau eroarea ei).
+pub fn voices(catalog: &Arc<VoiceCatalog>) -> anyhow::Result<Arc<Vec<VoiceInfo>>> {
+    for _ in 0..300 {
+        if let Some((voices, _)) = catalog.get()? {
+            return Ok(voices);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    anyhow::bail!("lista vocilor nu a sosit în 3 s")
+}
+
+/* ------------ HTTP către robot ---------------------------------------- */
+
+/// Headerele de răspuns pe care le verifică testele.
+const KEPT: [&str; 9] = [
+    "Access-Control-Allow-Origin", "Cache-Control", "Content-Disposition", "Content-Encoding",
+    "Content-Type", "ETag", "Location", "Retry-After", "Vary",
+];
+
+/// Răspunsul citit tot, cu headerele din `KEPT`.
+pub struct Reply {
+    pub status: u16,
+    pub body:   Vec<u8>,
+    headers:    Vec<(&'static str, String)>,
+}
+
+impl Reply {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        assert!(KEPT.contains(&name), "{name} lipseşte din KEPT");
+        self.headers.iter().find(|(k, _)| *k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// `null` dacă nu e JSON
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or_default()
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+pub fn request(addr: SocketAddr, method: Method, path: &str, headers: &[(&str, &str)], body: &[u8]) -> Reply {
+    let mut http = HostHttp::new();
+    let mut resp = http.request(method, &format!("http://{addr}{path}"), headers, body).unwrap();
+    let headers = KEPT.iter().filter_map(|&h| resp.header(h).map(|v| (h, v.to_owned()))).collect();
+    Reply { status: resp.status(), body: resp.read_to_end().unwrap(), headers }
+}
+
+/// `HostHttpServer` pe un port liber, cu un `Auth` nou.
+pub struct Api {
+    pub addr:   SocketAddr,
+    pub auth:   Arc<Auth>,
+    /// `Bearer <token>`, ca o pagină deja asociată
+    pub bearer: String,
+}
+
+/// `register` pune rutele testate.
+pub fn api(register: impl FnOnce(&mut HostHttpServer, Arc<Auth>) -> anyhow::Result<()>) -> Api {
+    let auth = Arc::new(Auth::load(MemoryKv::default()).unwrap());
+    let mut srv = HostHttpServer::new();
+    register(&mut srv, auth.clone()).unwrap();
+    let bearer = format!("Bearer {}", auth.token());
+    Api { addr: srv.start("127.0.0.1:0").unwrap(), auth, bearer }
+}
+
+impl Api {
+    /// Fără token (sau cu cel din `headers`).
+    pub fn request(&self, method: Method, path: &str, headers: &[(&str, &str)], body: &[u8]) -> Reply {
+        request(self.addr, method, path, headers, body)
+    }
+
+    /// Cu tokenul robotului.
+    pub fn send(&self, method: Method, path: &str, headers: &[(&str, &str)], body: &[u8]) -> Reply {
+        let mut all = vec![("Authorization", self.bearer.as_str())];
+        all.extend_from_slice(headers);
+        self.request(method, path, &all, body)
+    }
+
+    /// JSON dus-întors, cu tokenul robotului.
+    pub fn call(&self, method: Method, path: &str, body: &Value) -> (u16, Value) {
+        let reply = self.send(method, path, &[], body.to_string().as_bytes());
+        (reply.status, reply.json())
+    }
+}
+
+/* ------------ rutele din `register_handlers` --------------------------- */
+
+/// Robotul din simulator: `http::register_handlers` şi paginile statice.
+pub struct Robot {
+    pub api:    Api,
+    pub motion: Arc<Mutex<Motion>>,
+    pub tts:    queue::Rx<Speech>,
+}
+
+impl Deref for Robot {
+    type Target = Api;
+
+    fn deref(&self) -> &Api {
+        &self.api
+    }
+}
+
+/// `audio` ţine locul task-ului audio: primeşte joburile şi răspunde.
+pub fn robot(motion: Motion, audio: impl FnOnce(queue::Rx<Job>, mpsc::Sender<Exchange>) + Send + 'static) -> Robot {
+    let (tx_audio, rx_audio) = queue::channel::<Job>("audio");
+    let (tx_reply, rx_reply) = mpsc::channel();
+    let (tx_tts, rx_tts) = queue::channel::<Speech>("tts");
+    thread::spawn(move || audio(rx_audio, tx_reply));
+
+    let motion = Arc::new(Mutex::new(motion));
+    let api = api(|srv, auth| {
+        http::register_handlers(srv, tx_audio, Arc::new(Mutex::new(rx_reply)), motion.clone(), tx_tts, Hub::start(), auth)?;
+        http::register_static(srv)
+    });
+    Robot { api, motion, tts: rx_tts }
+}
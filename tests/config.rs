@@ -0,0 +1,168 @@
+//! `config`: schema, validare, JSON Merge Patch, migrare, notificări şi `/api/config`.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    azure_auth::AzureAuth,
+    azure_tts,
+    config::{self, Config, Section, Store, MASK, SCHEMA},
+    hal::{HostHttp, KeyValueStore, MemoryKv, MemorySink},
+    http,
+    openai::{self, Conversation},
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+#[test]
+fn defaults_are_valid_and_round_trip() {
+    let cfg = Config::default();
+    cfg.validate().unwrap();
+    assert_eq!(cfg.version, SCHEMA);
+    assert_eq!((cfg.azure.region.as_str(), cfg.azure.voice.as_str()), ("eastus", "ro-RO-AlinaNeural"));
+    assert_eq!((cfg.pins.motor_left, cfg.pins.servo_right), ([18, 19], 17));
+
+    let json = serde_json::to_value(&cfg).unwrap();
+    assert_eq!(serde_json::from_value::<Config>(json).unwrap(), cfg);
+
+    // secretele setate apar mascate, cele goale rămân goale
+    let mut cfg = cfg;
+    cfg.wifi.pass = "parola-acasa".into();
+    cfg.azure.key = "az-robot".into();
+    cfg.openai.key = String::new();
+    let masked = cfg.masked();
+    assert_eq!((masked.wifi.pass.as_str(), masked.azure.key.as_str(), masked.openai.key.as_str()), (MASK, MASK, ""));
+}
+
+#[test]
+fn patches_merge_keep_secrets_and_reject_mistakes() {
+    let cfg = Config::default();
+
+    let new = cfg.patched(&json!({"azure": {"voice": "ro-RO-EmilNeural", "key": MASK}, "version": 99})).unwrap();
+    assert_eq!(new.azure.voice, "ro-RO-EmilNeural");
+    assert_eq!((new.azure.key.as_str(), new.version), (cfg.azure.key.as_str(), SCHEMA));
+    assert_eq!(cfg.changed(&new), [Section::Azure]);
+
+    // `null` = valoarea din firmware
+    let reset = new.patched(&json!({"azure": {"voice": null}, "pins": {"motor_left": [4, 5]}})).unwrap();
+    assert_eq!(reset.azure.voice, cfg.azure.voice);
+    assert_eq!(new.changed(&reset), [Section::Azure, Section::Pins]);
+    assert!(Section::Pins.needs_restart() && !Section::Azure.needs_restart());
+
+    for (patch, error) in [
+        (json!({"openai": {"modle": "gpt-4o"}}), "openai.modle"),
+        (json!({"camera": {}}), "camera"),
+        (json!({"openai": {"turns": "şase"}}), "invalid type"),
+        (json!({"openai": {"turns": 21}}), "openai.turns"),
+        (json!({"openai": {"language": "rom"}}), "openai.language"),
+        (json!({"openai": {"url": "ftp://openai"}}), "openai.url"),
+        (json!({"openai": {"url": "http://api.openai.com"}}), "openai.url: trebuie să înceapă cu https://"),
+        (json!({"azure": {"voice": "ro\"/><x"}}), "azure.voice"),
+        (json!({"azure": {"region": "west.europe"}}), "azure.region"),
+        (json!({"azure": {"gain": 0}}), "azure.gain"),
+        (json!({"wifi": {"pass": "scurt"}}), "wifi.pass"),
+        (json!({"pins": {"servo_left": 18}}), "pins.servo_left: GPIO18 e deja folosit de pins.motor_left"),
+        (json!({"pins": {"i2s_dout": 7}}), "pins.i2s_dout: GPIO7 nu poate fi ieşire"),
+        (json!({"pins": {"i2s_ws": 35}}), "GPIO35"),
+        (json!({"pins": {"servo_right": 1}}), "pins.servo_right: GPIO1 nu poate fi ieşire"),
+        (json!({"pins": {"motor_right": [20, 22]}}), "GPIO20"),
+        (json!({"pins": {"i2s_bclk": 28}}), "GPIO28"),
+        (json!([1, 2]), "obiect"),
+    ] {
+        let err = cfg.patched(&patch).unwrap_err().to_string();
+        assert!(err.contains(error), "{patch} → {err}");
+    }
+}
+
+#[test]
+fn documents_are_migrated() {
+    // fără `version` (scris de mână) → v1
+    let mut doc = json!({"azure": {"voice": "ro-RO-EmilNeural"}});
+    assert_eq!(config::migrate(&mut doc).unwrap(), 0);
+    assert_eq!(doc["version"], SCHEMA);
+
+    // mai nou (rollback): neatins
+    let mut doc = json!({"version": SCHEMA + 1, "camera": {"fps": 10}});
+    assert_eq!(config::migrate(&mut doc).unwrap(), SCHEMA + 1);
+    assert_eq!(doc["version"], SCHEMA + 1);
+
+    assert!(config::migrate(&mut json!("config")).is_err());
+    assert!(config::migrate(&mut json!({"version": -1})).is_err());
+}
+
+/// Configuraţia curentă e globală – un singur test porneşte `Store`-uri.
+#[test]
+fn store_loads_saves_notifies_and_serves_the_api() {
+    // din NVS: document vechi, unul de la un firmware mai nou, unul stricat
+    let load = |raw: &str| {
+        let mut kv = MemoryKv::default();
+        kv.set_str("config", raw).unwrap();
+        Store::load(kv).unwrap().get()
+    };
+    assert_eq!(load(r#"{"openai": {"turns": 2}}"#).openai.turns, 2);
+    let newer = load(&json!({"version": SCHEMA + 1, "azure": {"region": "westeurope"}, "camera": {}}).to_string());
+    assert_eq!((newer.azure.region.as_str(), newer.version), ("westeurope", SCHEMA));
+    assert_eq!(*load("{nu e JSON"), Config::default());
+    assert_eq!(*load(r#"{"version": 1, "pins": {"servo_left": 18}}"#), Config::default());
+
+    // notificări doar la schimbări, cu secţiunile atinse
+    let store = Store::load(MemoryKv::default()).unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    store.subscribe({
+        let seen = seen.clone();
+        move |cfg, changed| seen.lock().unwrap().push((cfg.azure.voice.clone(), changed.to_vec()))
+    });
+    assert_eq!(store.patch(&json!({"azure": {"voice": "ro-RO-EmilNeural"}})).unwrap(), [Section::Azure]);
+    assert_eq!(store.patch(&json!({"azure": {"voice": "ro-RO-EmilNeural"}})).unwrap(), []);
+    assert!(store.patch(&json!({"azure": {"gain": 100}})).is_err());
+    assert_eq!(*seen.lock().unwrap(), [("ro-RO-EmilNeural".to_owned(), vec![Section::Azure])]);
+    assert_eq!(config::get().azure.voice, "ro-RO-EmilNeural");
+    assert!(!store.restart_pending());
+
+    // clienţii cloud citesc setările la fiecare cerere
+    let (_retry, cloud) = common::mock_cloud();
+    store.patch(&json!({
+        "openai": {"url": "https://openai.test", "key": "sk-test", "chat_model": "gpt-4o-mini", "system_prompt": "Eşti Robo.", "turns": 1},
+        "azure":  {"region": "westeurope", "key": "az-test", "gain": 1.0},
+    }))
+    .unwrap();
+    let mut conv = Conversation::default();
+    openai::chat_in(&mut HostHttp::new().redirect("https://openai.test", &cloud.url()), &mut conv, "Salut").unwrap();
+    let req = &cloud.requests()[0];
+    assert_eq!((req.path.as_str(), req.header("Authorization")), ("/v1/chat/completions", Some("Bearer sk-test")));
+    let body = req.json().unwrap();
+    assert_eq!((body["model"].as_str(), body["messages"][0]["content"].as_str().map(|s| s.starts_with("Eşti Robo."))), (Some("gpt-4o-mini"), Some(true)));
+
+    let mut http = common::cloud_client(&cloud);
+    azure_tts::tts_and_play(&mut http, &AzureAuth::new(common::cloud_client(&cloud)), &mut MemorySink::default(), "Bună").unwrap();
+    let mut requests = cloud.requests();
+    let (tts, token) = (requests.pop().unwrap(), requests.pop().unwrap());
+    assert_eq!((token.path.as_str(), token.header("Ocp-Apim-Subscription-Key")), ("/sts/v1.0/issueToken", Some("az-test")));
+    assert!(String::from_utf8_lossy(&tts.body).contains(r#"name="ro-RO-EmilNeural""#));
+
+    // `/api/config`
+    let store = Arc::new(store);
+    let api = common::api(|srv, auth| http::register_config(srv, auth, store.clone()));
+    let call = |method, token: &str, body: &str| {
+        let reply = api.request(method, "/api/config", &[("Authorization", token)], body.as_bytes());
+        (reply.status, reply.json())
+    };
+
+    assert_eq!(call(Method::Get, "Bearer x", "").0, 401);
+    let (status, body) = call(Method::Get, &api.bearer, "");
+    assert_eq!((status, body["restart"].as_bool()), (200, Some(false)));
+    assert_eq!((body["config"]["openai"]["key"].as_str(), body["config"]["version"].as_u64()), (Some(MASK), Some(SCHEMA as u64)));
+
+    // formularul trimite înapoi şi cheile mascate
+    let mut form = body["config"].clone();
+    form["pins"]["servo_left"] = 4.into();
+    let (status, body) = call(Method::Patch, &api.bearer, &form.to_string());
+    assert_eq!(status, 200, "{body}");
+    assert_eq!((body["changed"].clone(), body["restart"].as_bool()), (json!(["pins"]), Some(true)));
+    assert_eq!((store.get().openai.key.as_str(), store.get().pins.servo_left), ("sk-test", 4));
+
+    let (status, body) = call(Method::Patch, &api.bearer, r#"{"openai": {"modle": "x"}}"#);
+    assert_eq!((status, body["error"]["message"].as_str()), (400, Some("cheie necunoscută: openai.modle")));
+    assert_eq!(call(Method::Patch, &api.bearer, "{").0, 400);
+    assert_eq!(call(Method::Patch, &api.bearer, &" ".repeat(config::MAX_JSON + 1)).0, 413);
+}
@@ -0,0 +1,104 @@
+//! `drive`: rampă de acceleraţie, heartbeat şi oprire.
+
+use anyhow::Result;
+use esp32_hello_world::{
+    drive::{self, Drive},
+    hal::{MotorDriver, RecordingServos},
+    motion::Motion,
+    motors::MotorId,
+    ws::Hub,
+};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Default)]
+struct SharedMotors(Arc<Mutex<Vec<(MotorId, i8)>>>);
+
+impl MotorDriver for SharedMotors {
+    fn drive(&mut self, id: MotorId, speed: i8) -> Result<()> {
+        self.0.lock().unwrap().push((id, speed));
+        Ok(())
+    }
+}
+
+fn start() -> (Arc<Drive>, Arc<Mutex<Motion>>, SharedMotors) {
+    let motors = SharedMotors::default();
+    let motion = Arc::new(Mutex::new(Motion::new(motors.clone(), RecordingServos::default())));
+    let drive = Drive::start(motion.clone(), Hub::start()).unwrap();
+    (drive, motion, motors)
+}
+
+fn left_history(motors: &SharedMotors) -> Vec<i8> {
+    let history = motors.0.lock().unwrap();
+    history.iter().filter(|(id, _)| *id == MotorId::Left).map(|&(_, s)| s).collect()
+}
+
+/// Ţine comanda cu heartbeat la 10 Hz până la `until` sau 2 s.
+fn hold(drive: &Drive, motion: &Mutex<Motion>, until: (i8, i8)) -> Duration {
+    let t0 = Instant::now();
+    while motion.lock().unwrap().speeds() != until {
+        assert!(t0.elapsed() < Duration::from_secs(2), "ţinta nu a fost atinsă");
+        drive.heartbeat(drive::HTTP);
+        thread::sleep(Duration::from_millis(10));
+    }
+    t0.elapsed()
+}
+
+#[test]
+fn speed_ramps_up_and_down() {
+    let (drive, motion, motors) = start();
+
+    assert_eq!(drive.set(drive::HTTP, 1.0, 0.0), (100, 100));
+    let took = hold(&drive, &motion, (100, 100));
+    assert!(took >= Duration::from_millis(150), "{took:?}");
+
+    // eliberarea joystick-ului coboară tot în rampă, fără heartbeat
+    drive.set(drive::HTTP, 0.0, 0.0);
+    thread::sleep(drive::DEADMAN * 2);
+    assert_eq!(motion.lock().unwrap().speeds(), (0, 0));
+
+    let left = left_history(&motors);
+    assert!(left.len() > 20);
+    assert!(left.windows(2).all(|w| (w[1] as i16 - w[0] as i16).abs() <= 8), "{left:?}");
+}
+
+#[test]
+fn missing_heartbeat_stops_at_once() {
+    let (drive, motion, motors) = start();
+    drive.set(drive::HTTP, -0.3, 0.0);
+    hold(&drive, &motion, (-30, -30));
+
+    let t0 = Instant::now();
+    while motion.lock().unwrap().speeds() != (0, 0) {
+        assert!(t0.elapsed() < drive::DEADMAN * 2);
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(t0.elapsed() >= drive::DEADMAN / 2);
+    // oprire de siguranţă: direct la 0, fără rampă
+    assert_eq!(left_history(&motors).iter().rev().take(2).collect::<Vec<_>>(), [&0, &-30]);
+}
+
+#[test]
+fn only_the_driver_keeps_it_alive_or_releases_it() {
+    let (drive, motion, _) = start();
+    drive.set(7, 0.2, 0.0);
+    let t0 = Instant::now();
+    while motion.lock().unwrap().speeds() != (20, 20) {
+        assert!(t0.elapsed() < drive::DEADMAN);
+        drive.heartbeat(7);
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    drive.release(8).unwrap();
+    assert_eq!(motion.lock().unwrap().speeds(), (20, 20));
+    drive.release(7).unwrap();
+    assert_eq!(motion.lock().unwrap().speeds(), (0, 0));
+
+    drive.set(7, 0.2, 0.0);
+    drive.stop().unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(motion.lock().unwrap().speeds(), (0, 0));
+}
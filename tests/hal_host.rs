@@ -0,0 +1,111 @@
+//! Logica asistentului pe PC, cu implementările din `hal::host`.
+
+use esp32_hello_world::{
+    audio, azure_auth::AzureAuth, azure_tts,
+    cloud_error::CloudError,
+    hal::{
+        AudioSink, KeyValueStore, MemoryKv, MemorySink, MotorDriver, RecordingMotors,
+        ScriptedHttp, ScriptedResponse, WavFileSink,
+    },
+    motors::MotorId,
+    openai::Conversation,
+};
+
+#[test]
+fn tts_streams_amplified_pcm_into_sink() {
+    let http = ScriptedHttp::new();
+    http.push(ScriptedResponse::new(200, "tok-1"));
+    http.push(ScriptedResponse::new(200, [0x10u8, 0x00, 0xF0, 0xFF].to_vec()));
+
+    let mut sink = MemorySink::default();
+    azure_tts::tts_and_play(&mut http.clone(), &AzureAuth::new(http.clone()), &mut sink, "Salut").unwrap();
+
+    // câştig 2.0: 16 → 32, -16 → -32
+    assert_eq!(sink.pcm, [0x20, 0x00, 0xE0, 0xFF]);
+    assert_eq!(sink.utterances, 1);
+
+    let requests = http.requests();
+    assert!(requests[0].url.ends_with("/sts/v1.0/issueToken"));
+    let req = &requests[1];
+    assert!(req.url.ends_with("/cognitiveservices/v1"));
+    assert_eq!(req.header("Authorization"), Some("Bearer tok-1"));
+    assert_eq!(req.header("Ocp-Apim-Subscription-Key"), None);
+    assert_eq!(req.header("Content-Type"), Some("application/ssml+xml"));
+    assert!(String::from_utf8_lossy(&req.body).contains("Salut"));
+}
+
+#[test]
+fn tts_reports_http_errors() {
+    // un 401 cere alt token şi încă o încercare, nu mai mult
+    let http = ScriptedHttp::new();
+    for token in ["tok-1", "tok-2"] {
+        http.push(ScriptedResponse::new(200, token));
+        http.push(ScriptedResponse::new(401, "unauthorized"));
+    }
+
+    let mut sink = MemorySink::default();
+    let err = azure_tts::tts_and_play(&mut http.clone(), &AzureAuth::new(http.clone()), &mut sink, "x").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(CloudError::Auth { service: "Azure TTS", message }) if message == "unauthorized"
+    ));
+    assert!(sink.pcm.is_empty());
+    assert_eq!((http.requests().len(), http.pending()), (4, 0));
+    assert_eq!(http.requests()[3].header("Authorization"), Some("Bearer tok-2"));
+}
+
+#[test]
+fn transcribe_then_chat_uses_two_requests() {
+    let http = ScriptedHttp::new();
+    http.push(ScriptedResponse::new(200, "Ce faci?"));
+    http.push(ScriptedResponse::json(
+        200,
+        &serde_json::json!({"choices":[{"message":{"content":" Bine! "}}]}),
+    ));
+
+    let mut conv = Conversation::default();
+    let (text, reply) =
+        audio::transcribe_and_chat(&|| Ok(http.clone()), &mut conv, b"RIFF").unwrap();
+    assert_eq!(text, "Ce faci?");
+    assert_eq!(reply, "Bine!");
+
+    let reqs = http.requests();
+    assert_eq!(reqs.len(), 2);
+    assert!(reqs[0].url.ends_with("/v1/audio/transcriptions"));
+    assert!(reqs[1].url.ends_with("/v1/chat/completions"));
+}
+
+#[test]
+fn recording_motors_keep_last_speed() {
+    let mut m = RecordingMotors::default();
+    m.drive(MotorId::Left, 50).unwrap();
+    m.drive(MotorId::Right, -120).unwrap();
+    assert_eq!((m.left, m.right), (50, -100));
+    assert_eq!(m.history.len(), 2);
+}
+
+#[test]
+fn wav_sink_patches_header_sizes() {
+    let path = std::env::temp_dir().join("myrobo_hal_host.wav");
+    {
+        let mut sink = WavFileSink::create(&path, 16_000).unwrap();
+        sink.write(&[1, 0, 2, 0, 3, 0]).unwrap();
+        sink.flush().unwrap();
+    }
+    let wav = std::fs::read(&path).unwrap();
+    assert_eq!(wav.len(), 44 + 6);
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + 6);
+    assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 6);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn memory_kv_roundtrip() {
+    let mut kv = MemoryKv::default();
+    kv.set_str("token", "abc").unwrap();
+    assert_eq!(kv.get_str("token").unwrap().as_deref(), Some("abc"));
+    assert!(kv.remove("token").unwrap());
+    assert_eq!(kv.get("token").unwrap(), None);
+    assert!(kv.set("cheie_mult_prea_lunga", b"x").is_err());
+}
@@ -0,0 +1,345 @@
+//! Rutele din `http::register_handlers` servite de `HostHttpServer`.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    audio::Job,
+    auth::Auth,
+    azure_auth::AzureAuth,
+    azure_tts::VoiceCatalog,
+    certs::CertStore,
+    clock::Clock,
+    cloud_error::CloudError,
+    config::Store,
+    hal::{
+        HostHttp, HostHttpServer, HostTimeSync, HttpServer, MemoryFirmware, MemoryKv, RecordingAdvertiser,
+        RecordingMotors, RecordingServos, ScriptedRadio,
+    },
+    http::{self, StaticLookup},
+    mdns::Mdns,
+    queue,
+    motion::Motion,
+    ota::Ota,
+    updater::Updater,
+    wifi::{Backoff, WifiManager},
+    ws::Hub,
+};
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    time::Duration,
+};
+
+mod common;
+
+use common::{Reply, Robot as Sim};
+
+fn start() -> Sim {
+    // „audio task” fals: răspunde cu lungimea WAV-ului primit sau cu
+    // textul inversat; 429 de la ChatGPT pentru un WAV de 7 B
+    let motion = Motion::new(RecordingMotors::default(), RecordingServos::default());
+    common::robot(motion, |rx_audio, tx_reply| {
+        while let Ok(job) = rx_audio.recv() {
+            let reply = match job {
+                Job::Wav(wav) if wav.len() == 7 => Err(CloudError::RateLimited {
+                    service:     "ChatGPT",
+                    retry_after: Some(std::time::Duration::from_secs(20)),
+                    message:     "Rate limit reached".into(),
+                }),
+                Job::Wav(wav)   => Ok((format!("{} B", wav.len()), "ok".into())),
+                Job::Text(text) => Ok((text.clone(), text.chars().rev().collect())),
+                Job::Reset      => continue,
+            };
+            let _ = tx_reply.send(reply);
+        }
+    })
+}
+
+fn call(sim: &Sim, method: Method, path: &str, body: &[u8]) -> (u16, String) {
+    call_with(sim, method, path, &[], body)
+}
+
+/// Cu tokenul robotului, ca o pagină deja asociată.
+fn call_with(
+    sim: &Sim,
+    method: Method,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> (u16, String) {
+    let reply = sim.send(method, path, headers, body);
+    (reply.status, reply.text())
+}
+
+#[test]
+fn serves_index_and_hello() {
+    let sim = start();
+    let (status, body) = call(&sim, Method::Get, "/", b"");
+    assert_eq!(status, 200);
+    assert!(body.contains("MyRoboAssistant"));
+
+    assert_eq!(call(&sim, Method::Get, "/hello", b""), (200, "Hello received!".into()));
+    assert_eq!(call(&sim, Method::Get, "/nu-exista", b"").0, 404);
+}
+
+fn get(sim: &Sim, path: &str) -> (u16, String, String) {
+    let reply = sim.request(Method::Get, path, &[], b"");
+    (reply.status, reply.header("Content-Type").unwrap_or("").to_owned(), reply.text())
+}
+
+#[test]
+fn serves_every_static_file_with_its_mime() {
+    let sim = start();
+    for (path, mime) in [
+        ("/chat.html",      "text/html; charset=utf-8"),
+        ("/control.html",   "text/html; charset=utf-8"),
+        ("/style.css",      "text/css; charset=utf-8"),
+        ("/wav-encoder.js", "text/javascript; charset=utf-8"),
+        ("/index.html?v=2", "text/html; charset=utf-8"),
+    ] {
+        let (status, ctype, _) = get(&sim, path);
+        assert_eq!((status, ctype.as_str()), (200, mime), "{path}");
+    }
+}
+
+#[test]
+fn unknown_paths_get_the_404_page_and_traversal_is_rejected() {
+    let sim = start();
+    let (status, ctype, body) = get(&sim, "/lipseste.css");
+    assert_eq!((status, ctype.as_str()), (404, "text/html; charset=utf-8"));
+    assert!(body.contains("404"));
+
+    for path in ["/../Cargo.toml", "/%2e%2e/Cargo.toml", "/a/./index.html", "/x%zz"] {
+        assert_eq!(get(&sim, path).0, 400, "{path}");
+    }
+}
+
+fn get_with(sim: &Sim, path: &str, headers: &[(&str, &str)]) -> Reply {
+    sim.request(Method::Get, path, headers, b"")
+}
+
+#[test]
+fn static_files_are_gzipped_and_cached() {
+    let sim = start();
+    let disk = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/static/chat.html")).unwrap();
+
+    // gzip acceptat → corp comprimat, ETag „-gz”
+    let reply = get_with(&sim, "/chat.html", &[("Accept-Encoding", "br, gzip")]);
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.header("Content-Encoding"), Some("gzip"));
+    assert_eq!(reply.header("Cache-Control"), Some("no-cache"));
+    assert_eq!(reply.header("Vary"), Some("Accept-Encoding"));
+    let gz = &reply.body;
+    assert!(gz.len() < disk.len());
+    let inflated = miniz_oxide::inflate::decompress_to_vec(&gz[10..gz.len() - 8]).unwrap();
+    assert_eq!(inflated, disk);
+    let etag_gz = reply.header("ETag").unwrap().to_owned();
+    assert!(etag_gz.ends_with("-gz\""));
+
+    // fără gzip → acelaşi conţinut, dezarhivat pe placă
+    let reply = get_with(&sim, "/chat.html", &[("Accept-Encoding", "gzip;q=0")]);
+    assert_eq!(reply.header("Content-Encoding"), None);
+    assert_eq!(reply.body, disk);
+    let etag = reply.header("ETag").unwrap().to_owned();
+    assert_ne!(etag, etag_gz);
+
+    // revalidare → 304 fără corp, pentru oricare variantă
+    for tag in [&etag, &etag_gz] {
+        let reply = get_with(&sim, "/chat.html", &[("If-None-Match", tag)]);
+        assert_eq!((reply.status, reply.body.len()), (304, 0));
+    }
+    assert_eq!(get_with(&sim, "/chat.html", &[("If-None-Match", "\"altul\"")]).status, 200);
+
+    let reply = get_with(&sim, "/wav-encoder.js", &[]);
+    assert_eq!(reply.header("Cache-Control"), Some("public, max-age=3600"));
+}
+
+#[test]
+fn static_resolution() {
+    assert_eq!(http::resolve_static("/"), StaticLookup::Found("index.html"));
+    assert_eq!(http::resolve_static("/chat.html#top"), StaticLookup::Found("chat.html"));
+    assert_eq!(http::resolve_static("/nu/"), StaticLookup::NotFound);
+    assert_eq!(http::resolve_static("/..%5cCargo.toml"), StaticLookup::Rejected);
+    assert_eq!(http::mime_for("favicon.ICO"), "image/x-icon");
+    assert_eq!(http::mime_for("fara-extensie"), "application/octet-stream");
+}
+
+#[test]
+fn send_text_reaches_tts_queue() {
+    let sim = start();
+    let (status, _) = call(&sim, Method::Post, "/send_text", b"  Salut robot  ");
+    assert_eq!(status, 202);
+    assert_eq!(sim.tts.recv().unwrap().text, "Salut robot");
+
+    // JSON cu vocea aleasă pentru cererea asta
+    let body = br#"{"text": "Hello", "voice": {"voice": "en-US-JennyNeural", "style": "cheerful"}}"#;
+    assert_eq!(call(&sim, Method::Post, "/send_text", body).0, 202);
+    let speech = sim.tts.recv().unwrap();
+    assert_eq!((speech.text.as_str(), speech.voice.voice.as_deref(), speech.voice.style.as_deref()), ("Hello", Some("en-US-JennyNeural"), Some("cheerful")));
+    assert_eq!(call(&sim, Method::Post, "/send_text", br#"{"text": "x", "voice": {"rate": "repede"}}"#).0, 400);
+    assert!(sim.tts.try_recv().is_err());
+
+    // SSML scris de client – trimis neatins
+    let doc = r#"<speak version="1.0" xml:lang="ro-RO"><voice name="ro-RO-EmilNeural">Salut</voice></speak>"#;
+    let body = serde_json::json!({ "ssml": doc }).to_string();
+    assert_eq!(call(&sim, Method::Post, "/send_text", body.as_bytes()).0, 202);
+    let speech = sim.tts.recv().unwrap();
+    assert!(speech.ssml && speech.text == doc);
+    assert_eq!(call(&sim, Method::Post, "/send_text", br#"{"ssml": "Salut"}"#).0, 400);
+    let body = serde_json::json!({ "ssml": doc, "voice": {"style": "sad"} }).to_string();
+    assert_eq!(call(&sim, Method::Post, "/send_text", body.as_bytes()).0, 400);
+    assert!(sim.tts.try_recv().is_err());
+}
+
+#[test]
+fn transcribe_round_trips_through_audio_task_and_speaks() {
+    let sim = start();
+    let (status, body) = call(&sim, Method::Post, "/transcribe", &[0u8; 100]);
+    assert_eq!(status, 200);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(v, serde_json::json!({"transcript": "100 B", "reply": "ok"}));
+    assert_eq!(sim.tts.recv().unwrap().text, "ok");
+}
+
+#[test]
+fn transcribe_reports_cloud_errors_and_speaks_fallback() {
+    let sim = start();
+    let (status, body) = call_with(
+        &sim, Method::Post, "/transcribe", &[("Accept-Language", "en-GB,en;q=0.9")], &[0u8; 7],
+    );
+    assert_eq!(status, 429);
+
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(v["error"]["kind"], "rate_limited");
+    assert_eq!(v["error"]["service"], "ChatGPT");
+    assert_eq!(v["error"]["retry_after"], 20);
+    assert_eq!(v["reply"], sim.tts.recv().unwrap().text);
+    assert!(v["reply"].as_str().unwrap().starts_with("Too many requests"));
+}
+
+#[test]
+fn api_chat_replies_and_speaks() {
+    let sim = start();
+    let (status, body) = call(&sim, Method::Post, "/api/chat", br#"{"text":" salut "}"#);
+    assert_eq!(status, 200);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(v["reply"], "tulas");
+    assert_eq!(sim.tts.recv().unwrap().text, "tulas");
+
+    let (status, body) = call(&sim, Method::Post, "/api/chat", br#"{"text":"ab","speak":false}"#);
+    assert_eq!((status, body.as_str()), (200, r#"{"reply":"ba"}"#));
+    assert!(sim.tts.try_recv().is_err());
+
+    // text simplu în loc de JSON
+    assert_eq!(call(&sim, Method::Post, "/api/chat", b"xy").1, r#"{"reply":"yx"}"#);
+    assert_eq!(sim.tts.recv().unwrap().text, "yx");
+
+    let (status, _) = call(&sim, Method::Post, "/api/chat", br#"{"text":"ab","voice":{"pitch":"-5%","rate":"slow"}}"#);
+    assert_eq!(status, 200);
+    let voice = sim.tts.recv().unwrap().voice;
+    assert_eq!((voice.pitch.as_deref(), voice.rate.as_deref(), voice.voice), (Some("-5%"), Some("slow"), None));
+    let (status, body) = call(&sim, Method::Post, "/api/chat", br#"{"text":"ab","voice":{"speed":"fast"}}"#);
+    assert_eq!(status, 400, "{body}");
+
+    assert_eq!(call(&sim, Method::Post, "/api/chat", b"  ").0, 400);
+    assert_eq!(call(&sim, Method::Delete, "/api/chat", b"").0, 204);
+}
+
+#[test]
+fn api_drive_takes_a_joystick_vector() {
+    let sim = start();
+    let (status, body) = call(&sim, Method::Post, "/api/drive", br#"{"linear":-1,"angular":0}"#);
+    assert_eq!(status, 200);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(v, serde_json::json!({"left": -100, "right": -100}));
+
+    assert_eq!(call(&sim, Method::Post, "/api/drive", br#"{"linear":1}"#).0, 400);
+    assert_eq!(call(&sim, Method::Delete, "/api/drive", b"").0, 204);
+    assert_eq!(call(&sim, Method::Get, "/move/inapoi", b"").0, 200);
+}
+
+#[test]
+fn unknown_gesture_is_not_routed() {
+    let sim = start();
+    assert_eq!(call(&sim, Method::Get, "/action/zbor", b"").0, 404);
+}
+
+#[test]
+fn api_status_reports_motion_queues_and_version() {
+    let sim = start();
+    let status = || {
+        let (code, body) = call(&sim, Method::Get, "/api/status", b"");
+        assert_eq!(code, 200);
+        serde_json::from_str::<serde_json::Value>(&body).unwrap()
+    };
+    // rampa porneşte pe thread-ul drive – aşteptăm primul pas
+    let t0 = std::time::Instant::now();
+    let v = loop {
+        call(&sim, Method::Post, "/api/drive", br#"{"linear":0.5,"angular":0}"#);
+        let v = status();
+        if v["motion"]["left"].as_i64().unwrap() > 0 {
+            break v;
+        }
+        assert!(t0.elapsed() < std::time::Duration::from_secs(2));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    };
+    assert_eq!(v["version"], esp32_hello_world::VERSION);
+    assert_eq!(v["motion"]["servo_left"], serde_json::Value::Null);
+    assert!(v["stack_free_min"].get("httpd").is_some());
+    assert!(v["queues"].get("tts").is_some());
+    assert_eq!(v["cloud"]["openai"]["circuit_open"], false);
+
+    let mut sim = sim;
+    sim.api.bearer = "Bearer gresit".into();
+    assert_eq!(call(&sim, Method::Get, "/api/status", b"").0, 401);
+}
+
+#[test]
+fn queues_count_waiting_messages() {
+    let (tx, rx) = queue::channel::<u8>("test_depth");
+    tx.send(1).unwrap();
+    tx.clone().send(2).unwrap();
+    let depth = || queue::depths().into_iter().find(|(n, _)| *n == "test_depth").map(|(_, d)| d);
+    assert_eq!((depth(), rx.len()), (Some(2), 2));
+    rx.recv().unwrap();
+    assert_eq!(depth(), Some(1));
+    drop((tx, rx));
+    assert_eq!(depth(), None);
+}
+
+#[test]
+fn main_server_routes_fit_max_uri_handlers() {
+    // ca în main.rs – pe placă ruta în plus ar da `HANDLERS_FULL`
+    let (tx_audio, _rx_audio) = queue::channel::<Job>("audio");
+    let (_tx_reply, rx_reply) = mpsc::channel();
+    let (tx_tts, _rx_tts) = queue::channel("tts");
+    let motion = Arc::new(Mutex::new(Motion::new(RecordingMotors::default(), RecordingServos::default())));
+    let auth = Arc::new(Auth::load(MemoryKv::default()).unwrap());
+    let ota = Arc::new(Ota::new(MemoryFirmware::new(1024), None));
+    let backoff = Backoff { min: Duration::from_millis(20), max: Duration::from_millis(100) };
+    let config = Arc::new(Store::load(MemoryKv::default()).unwrap());
+
+    let mut srv = HostHttpServer::new();
+    http::register_handlers(&mut srv, tx_audio, Arc::new(Mutex::new(rx_reply)), motion, tx_tts, Hub::start(), auth.clone())
+        .unwrap();
+    http::register_tls(&mut srv, auth.clone(), Arc::new(CertStore::new(MemoryKv::default())), Vec::new).unwrap();
+    let mdns = Mdns::start(RecordingAdvertiser::default(), MemoryKv::default(), 80, false).unwrap();
+    http::register_mdns(&mut srv, auth.clone(), Arc::new(Mutex::new(mdns))).unwrap();
+    http::register_ota(&mut srv, auth.clone(), ota.clone(), Hub::start()).unwrap();
+    let updater = Updater::new(ota, HostHttp::new(), MemoryKv::default()).unwrap().start().unwrap();
+    http::register_updater(&mut srv, auth.clone(), updater).unwrap();
+    let wifi = WifiManager::new(ScriptedRadio::new(), MemoryKv::default(), backoff).unwrap().start().unwrap();
+    http::register_wifi(&mut srv, auth.clone(), wifi).unwrap();
+    let clock = Clock::new(HostTimeSync::default(), MemoryKv::default()).unwrap();
+    http::register_clock(&mut srv, auth.clone(), Arc::new(clock)).unwrap();
+    http::register_config(&mut srv, auth.clone(), config.clone()).unwrap();
+    http::register_backup(&mut srv, auth.clone(), config).unwrap();
+    let voices = VoiceCatalog::new(Arc::new(Mutex::new(HostHttp::new())), Arc::new(AzureAuth::new(HostHttp::new())));
+    http::register_tts(&mut srv, auth, Arc::new(voices)).unwrap();
+    http::register_static(&mut srv).unwrap();
+
+    // limita e aceeaşi pe PC
+    let mut full = HostHttpServer::new();
+    for i in 0..http::MAX_URI_HANDLERS {
+        full.handler(&format!("/r{i}"), Method::Get, |_| Ok(())).unwrap();
+    }
+    assert!(full.handler("/prea-multe", Method::Get, |_| Ok(())).is_err());
+}
@@ -0,0 +1,64 @@
+//! `mdns`: hostname configurabil, TXT, anunţ nou după reconectare.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    hal::{KeyValueStore, MemoryKv, RecordingAdvertiser},
+    http,
+    mdns::{self, Mdns},
+};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+#[test]
+fn advertises_default_hostname_with_txt() {
+    let adv = RecordingAdvertiser::default();
+    let mut mdns = Mdns::start(adv.clone(), MemoryKv::default(), 80, false).unwrap();
+    assert_eq!(mdns.hostname(), "myrobo");
+
+    let (host, port, txt) = adv.0.lock().unwrap()[0].clone();
+    assert_eq!((host.as_str(), port), ("myrobo", 80));
+    let get = |k: &str| txt.iter().find(|(key, _)| key == k).map(|(_, v)| v.clone());
+    assert_eq!(get("version").as_deref(), Some(esp32_hello_world::VERSION));
+    assert_eq!(get("caps").as_deref(), Some(mdns::CAPABILITIES));
+    assert_eq!(get("https").as_deref(), Some("0"));
+
+    // reconectare: acelaşi anunţ, încă o dată
+    mdns.refresh().unwrap();
+    assert_eq!(adv.0.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn hostname_comes_from_nvs_and_is_validated() {
+    let mut kv = MemoryKv::default();
+    kv.set_str("hostname", "robo-1").unwrap();
+    assert_eq!(Mdns::start(RecordingAdvertiser::default(), kv, 443, true).unwrap().hostname(), "robo-1");
+
+    kv = MemoryKv::default();
+    kv.set_str("hostname", "Nu Merge!").unwrap();
+    assert_eq!(Mdns::start(RecordingAdvertiser::default(), kv, 80, false).unwrap().hostname(), "myrobo");
+
+    for ok in ["a", "robo-2", "x1"] {
+        assert!(mdns::valid_hostname(ok), "{ok}");
+    }
+    for bad in ["", "-robo", "robo-", "Robo", "ro_bo", "robo.local", &"a".repeat(64)] {
+        assert!(!mdns::valid_hostname(bad), "{bad}");
+    }
+}
+
+#[test]
+fn api_renames_the_robot() {
+    let adv = RecordingAdvertiser::default();
+    let mdns = Arc::new(Mutex::new(Mdns::start(adv.clone(), MemoryKv::default(), 80, false).unwrap()));
+    let api = common::api(|srv, auth| http::register_mdns(srv, auth, mdns.clone()));
+    let call = |method, body: Value| api.call(method, "/api/mdns", &body);
+
+    assert_eq!(call(Method::Get, json!(null)), (200, json!({"hostname": "myrobo", "fqdn": "myrobo.local"})));
+    let (status, body) = call(Method::Put, json!({"hostname": "Bucatarie.local"}));
+    assert_eq!((status, body["fqdn"].as_str()), (200, Some("bucatarie.local")));
+    assert_eq!(adv.0.lock().unwrap().last().unwrap().0, "bucatarie");
+
+    assert_eq!(call(Method::Put, json!({"hostname": "fără diacritice"})).0, 400);
+    assert_eq!(mdns.lock().unwrap().hostname(), "bucatarie");
+}
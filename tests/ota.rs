@@ -0,0 +1,152 @@
+//! `ota`: imaginea în slotul inactiv, SHA-256, semnătură, rollback şi `/api/ota`.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    hal::MemoryFirmware,
+    http,
+    ota::{self, Ota, OtaError, State},
+    util::{self, sha256},
+    ws::Hub,
+};
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use std::{sync::Arc, thread, time::Duration};
+
+mod common;
+
+const SLOT: usize = 256 * 1024;
+
+/// Header ESP32 + `esp_app_desc_t` cu versiunea dată, apoi umplutură.
+fn image(version: &str, len: usize) -> Vec<u8> {
+    let mut img = vec![0u8; len];
+    img[0] = ota::IMAGE_MAGIC;
+    img[32..36].copy_from_slice(&0xABCD_5432u32.to_le_bytes());
+    img[48..48 + version.len()].copy_from_slice(version.as_bytes());
+    for (i, b) in img.iter_mut().enumerate().skip(80) {
+        *b = (i * 7) as u8;
+    }
+    img
+}
+
+/// Cititor care dă imaginea în bucăţi mici, ca reţeaua.
+fn reader(img: &[u8]) -> impl FnMut(&mut [u8]) -> anyhow::Result<usize> + '_ {
+    let mut pos = 0;
+    move |buf| {
+        let n = (img.len() - pos).min(buf.len()).min(1500);
+        buf[..n].copy_from_slice(&img[pos..pos + n]);
+        pos += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn image_goes_to_the_inactive_slot_after_checks() {
+    let fw = MemoryFirmware::new(SLOT);
+    let ota = Ota::new(fw.clone(), None);
+    let img = image("0.2.0", 150 * 1024);
+    let sha = sha256(&img);
+
+    let mut seen = Vec::new();
+    let done = ota.update(reader(&img), Some(img.len()), Some(sha), None, |p| seen.push((p.state, p.received))).unwrap();
+    assert_eq!(done.state, State::Ready);
+    assert_eq!(done.version.as_deref(), Some("0.2.0"));
+    assert_eq!(done.sha256, Some(util::hex(&sha)));
+    assert!(seen[0].0 == State::Receiving && seen[0].1 >= ota::PROGRESS_STEP, "{seen:?}");
+    assert_eq!(seen.last(), Some(&(State::Ready, img.len())));
+    assert!(seen.len() >= 3, "{seen:?}");
+
+    let state = fw.0.lock().unwrap();
+    assert!(state.activated);
+    assert_eq!(state.image, img);
+}
+
+#[test]
+fn bad_images_are_rejected_and_the_slot_stays_inactive() {
+    let fw = MemoryFirmware::new(SLOT);
+    let ota = Ota::new(fw.clone(), None);
+    let img = image("0.2.0", 4096);
+
+    let wrong = sha256(b"altceva");
+    assert!(matches!(ota.update(reader(&img), None, Some(wrong), None, |_| {}), Err(OtaError::Mismatch)));
+    assert_eq!(ota.progress().state, State::Failed);
+
+    let mut elf = img.clone();
+    elf[0] = 0x7f;
+    assert!(matches!(ota.update(reader(&elf), None, None, None, |_| {}), Err(OtaError::BadImage(_))));
+    assert!(matches!(ota.update(reader(&img), Some(img.len() + 1), None, None, |_| {}), Err(OtaError::BadImage(_))));
+    assert!(matches!(ota.update(reader(&img), Some(SLOT + 1), None, None, |_| {}), Err(OtaError::TooLarge(SLOT))));
+    assert!(matches!(ota.update(reader(&[]), None, None, None, |_| {}), Err(OtaError::BadImage(_))));
+
+    let state = fw.0.lock().unwrap();
+    assert!(!state.activated && !state.writing);
+}
+
+#[test]
+fn signed_firmware_needs_the_signature() {
+    let signer = SigningKey::from_slice(&[7; 32]).unwrap();
+    let public = util::hex(signer.verifying_key().to_encoded_point(true).as_bytes());
+    let fw = MemoryFirmware::new(SLOT);
+    let ota = Ota::new(fw.clone(), Some(ota::public_key(&public).unwrap()));
+    assert!(ota.signed());
+    assert!(ota::public_key("02abcd").is_err());
+    let img = image("0.3.0", 8000);
+
+    assert!(matches!(ota.update(reader(&img), None, None, None, |_| {}), Err(OtaError::BadSignature)));
+    let other: Signature = SigningKey::from_slice(&[8; 32]).unwrap().sign(&img);
+    assert!(matches!(ota.update(reader(&img), None, None, Some(other), |_| {}), Err(OtaError::BadSignature)));
+    let wrong_image: Signature = signer.sign(b"alta imagine");
+    assert!(matches!(ota.update(reader(&img), None, None, Some(wrong_image), |_| {}), Err(OtaError::BadSignature)));
+    assert!(!fw.0.lock().unwrap().activated);
+
+    let sig: Signature = signer.sign(&img);
+    assert_eq!(ota::parse_signature(&util::hex(&sig.to_bytes())), Some(sig));
+    ota.update(reader(&img), None, None, Some(sig), |_| {}).unwrap();
+    assert!(fw.0.lock().unwrap().activated);
+}
+
+#[test]
+fn unconfirmed_image_rolls_back() {
+    let fw = MemoryFirmware::new(SLOT);
+    fw.0.lock().unwrap().pending_verify = true;
+    let ota = Arc::new(Ota::new(fw.clone(), None));
+    ota.guard(Duration::from_millis(50)).unwrap();
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(fw.0.lock().unwrap().rollbacks, 1);
+
+    // confirmată la timp → rămâne
+    let fw = MemoryFirmware::new(SLOT);
+    fw.0.lock().unwrap().pending_verify = true;
+    let ota = Arc::new(Ota::new(fw.clone(), None));
+    ota.guard(Duration::from_millis(100)).unwrap();
+    assert!(ota.confirm().unwrap());
+    assert!(!ota.confirm().unwrap());
+    thread::sleep(Duration::from_millis(250));
+    assert_eq!(fw.0.lock().unwrap().rollbacks, 0);
+}
+
+#[test]
+fn api_uploads_and_restarts() {
+    let fw = MemoryFirmware::new(SLOT);
+    let ota = Arc::new(Ota::new(fw.clone(), None));
+    let api = common::api(|srv, auth| http::register_ota(srv, auth, ota, Hub::start()));
+    let call = |method, headers: &[(&str, &str)], body: &[u8]| {
+        let reply = api.send(method, "/api/ota", headers, body);
+        (reply.status, reply.json())
+    };
+
+    let (status, body) = call(Method::Get, &[], b"");
+    assert_eq!(status, 200);
+    assert_eq!((body["running"]["label"].as_str(), body["progress"]["state"].as_str()), (Some("ota_0"), Some("idle")));
+
+    let img = image("0.4.0", 20_000);
+    let sha = util::hex(&sha256(&img));
+    assert_eq!(call(Method::Post, &[("X-Sha256", "nu-e-hex")], &img).0, 400);
+    assert_eq!(call(Method::Post, &[("X-Sha256", &"0".repeat(64))], &img).0, 422);
+    assert_eq!(fw.0.lock().unwrap().restarts, 0);
+
+    let (status, body) = call(Method::Post, &[("X-Sha256", &sha)], &img);
+    assert_eq!(status, 200, "{body}");
+    assert_eq!((body["version"].as_str(), body["restarting"].as_bool()), (Some("0.4.0"), Some(true)));
+    thread::sleep(Duration::from_millis(1500));
+    assert_eq!(fw.0.lock().unwrap().restarts, 1);
+    assert_eq!(call(Method::Get, &[], b"").1["progress"]["state"], "ready");
+}
@@ -0,0 +1,195 @@
+//! `retry`: backoff, `Retry-After`, circuit breaker – contra `mock-cloud`.
+
+use esp32_hello_world::{
+    azure_auth::AzureAuth,
+    azure_tts::{self, VoiceCatalog},
+    cloud_error::CloudError,
+    earcon,
+    hal::{HostHttp, MemorySink, NotSent},
+    openai,
+    retry::{self, Policy},
+};
+use mock_cloud::{Fault, MockCloud, Reply};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+mod common;
+
+use common::{cloud_client, retry_scope, RetryScope};
+
+const FAST: Policy = Policy {
+    connect_timeout: Duration::from_secs(1),
+    read_timeout:    Duration::from_millis(300),
+    attempts:        3,
+    base_delay:      Duration::from_millis(10),
+    max_delay:       Duration::from_millis(40),
+    max_retry_after: Duration::from_secs(2),
+    breaker_after:   2,
+    breaker_open:    Duration::from_millis(300),
+};
+
+fn setup(policy: Policy) -> (RetryScope, MockCloud, HostHttp) {
+    let scope = retry_scope(policy);
+    let cloud = MockCloud::start();
+    let http = cloud_client(&cloud);
+    (scope, cloud, http)
+}
+
+#[test]
+fn refusals_are_retried() {
+    let (_g, cloud, mut http) = setup(FAST);
+    cloud
+        .fail_next(Fault::rate_limited(0))
+        .fail_next(Fault::status(503))
+        .push_reply(Reply::text("gata"));
+
+    assert_eq!(openai::chat(&mut http, "hei").unwrap(), "gata");
+    assert_eq!(cloud.requests().len(), 3);
+}
+
+#[test]
+fn billed_requests_are_not_repeated_once_sent() {
+    let (_g, cloud, mut http) = setup(FAST);
+    for fault in [Fault::status(500), Fault::Drop, Fault::Delay(Duration::from_millis(600))] {
+        retry::OPENAI.reset(); // două eşecuri deschid circuitul din `FAST`
+        let before = cloud.requests().len();
+        cloud.fail_next(fault);
+        assert!(openai::chat(&mut http, "hei").is_err());
+        assert_eq!(cloud.requests().len(), before + 1);
+    }
+
+    // lista vocilor (GET) se poate relua
+    cloud.fail_next_on("/cognitiveservices/voices", Fault::status(500));
+    let catalog = Arc::new(VoiceCatalog::new(Arc::new(Mutex::new(cloud_client(&cloud))), Arc::new(AzureAuth::new(cloud_client(&cloud)))));
+    assert_eq!(common::voices(&catalog).unwrap().len(), 6);
+    assert_eq!(cloud.requests().iter().filter(|r| r.path.ends_with("/voices/list")).count(), 2);
+}
+
+#[test]
+fn unsent_requests_are_retried() {
+    let (_g, _cloud, mut http) = setup(FAST);
+    let mut tries = 0;
+    let n = retry::OPENAI
+        .call("ChatGPT", &mut http, |_| {
+            tries += 1;
+            match tries {
+                1 => Err(NotSent(io::Error::from(io::ErrorKind::ConnectionRefused).into()).into()),
+                _ => Ok(tries),
+            }
+        })
+        .unwrap();
+    assert_eq!(n, 2);
+
+    // conexiune ruptă după trimitere: doar cererile idempotente se reiau
+    let reset = || io::Error::from(io::ErrorKind::ConnectionReset);
+    let mut tries = 0;
+    assert!(retry::OPENAI.call("ChatGPT", &mut http, |_| -> anyhow::Result<()> { tries += 1; Err(reset().into()) }).is_err());
+    assert_eq!(tries, 1);
+    let mut tries = 0;
+    assert!(retry::AZURE.call_idempotent("Azure voices", &mut http, |_| -> anyhow::Result<()> { tries += 1; Err(reset().into()) }).is_err());
+    assert_eq!(tries, FAST.attempts);
+}
+
+#[test]
+fn last_call_is_kept_for_status() {
+    let (_g, cloud, mut http) = setup(FAST);
+    cloud.fail_next(Fault::status(401)).push_reply(Reply::text("gata"));
+
+    openai::chat(&mut http, "hei").unwrap_err();
+    let last = retry::OPENAI.last_call().unwrap();
+    assert_eq!(last.api, "ChatGPT");
+    assert!(last.error.is_some());
+
+    openai::chat(&mut http, "hei").unwrap();
+    let last = retry::OPENAI.last_call().unwrap();
+    assert!(last.error.is_none() && last.latency < Duration::from_secs(1));
+}
+
+#[test]
+fn client_errors_are_not_retried() {
+    let (_g, cloud, mut http) = setup(FAST);
+    cloud.fail_next(Fault::status(401));
+
+    let err = openai::chat(&mut http, "hei").unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(CloudError::Auth { .. })));
+    assert_eq!(cloud.requests().len(), 1);
+    assert!(!retry::OPENAI.is_open());
+}
+
+#[test]
+fn retry_after_is_honored() {
+    let (_g, cloud, mut http) = setup(FAST);
+    cloud.fail_next(Fault::rate_limited(1));
+
+    let t = Instant::now();
+    assert!(openai::chat(&mut http, "hei").is_ok());
+    assert!(t.elapsed() >= Duration::from_secs(1));
+}
+
+#[test]
+fn long_retry_after_gives_up_immediately() {
+    let (_g, cloud, mut http) = setup(FAST);
+    cloud.fail_next(Fault::rate_limited(60));
+
+    let t = Instant::now();
+    let err = openai::chat(&mut http, "hei").unwrap_err();
+    assert!(err.to_string().contains("429"));
+    assert!(t.elapsed() < Duration::from_secs(1));
+    assert_eq!(cloud.requests().len(), 1);
+}
+
+#[test]
+fn breaker_opens_fast_fails_and_recovers() {
+    let (_g, cloud, mut http) = setup(FAST);
+    for _ in 0..2 * FAST.attempts {
+        cloud.fail_next(Fault::status(503));
+    }
+
+    assert!(openai::chat(&mut http, "hei").is_err());
+    assert!(!retry::OPENAI.is_open());
+    assert!(openai::chat(&mut http, "hei").is_err());
+    assert!(retry::OPENAI.is_open());
+
+    // deschis: nici o cerere nu mai pleacă
+    let sent = cloud.requests().len();
+    let err = openai::chat(&mut http, "hei").unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(CloudError::Offline { service: "ChatGPT", .. })));
+    assert_eq!(cloud.requests().len(), sent);
+
+    // Azure are breaker-ul lui
+    let mut sink = MemorySink::default();
+    azure_tts::tts_and_play(&mut http, &AzureAuth::new(cloud_client(&cloud)), &mut sink, "Salut").unwrap();
+
+    // după `breaker_open` o cerere de probă trece şi închide circuitul
+    std::thread::sleep(FAST.breaker_open);
+    assert!(openai::chat(&mut http, "hei").is_ok());
+    assert!(!retry::OPENAI.is_open());
+}
+
+#[test]
+fn tts_is_not_replayed_after_audio_started() {
+    let (_g, cloud, mut http) = setup(FAST);
+    cloud.fail_next_on("/cognitiveservices", Fault::Truncate(4096));
+
+    let mut sink = MemorySink::default();
+    let err = azure_tts::tts_and_play(&mut http, &AzureAuth::new(cloud_client(&cloud)), &mut sink, "Salut robotule").unwrap_err();
+    assert!(err.to_string().contains("trunchiat"), "{err}");
+    assert_eq!(cloud.requests().iter().filter(|r| r.path == "/cognitiveservices/v1").count(), 1);
+    assert!(!sink.pcm.is_empty());
+}
+
+#[test]
+fn offline_earcon_is_short_audible_pcm() {
+    let pcm = earcon::offline();
+    let ms = pcm.len() as u32 / 2 * 1000 / earcon::SAMPLE_RATE;
+    assert!((300..1000).contains(&ms), "{ms} ms");
+    assert!(pcm.chunks_exact(2).any(|s| i16::from_le_bytes([s[0], s[1]]).abs() > 4000));
+
+    let mut sink = MemorySink::default();
+    earcon::play_offline(&mut sink).unwrap();
+    assert_eq!(sink.pcm, pcm);
+    assert_eq!(sink.utterances, 1);
+}
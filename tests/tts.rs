@@ -0,0 +1,306 @@
+//! `azure_tts`: vocea, limba şi stilul în SSML, alegerea după limba
+//! replicii şi lista vocilor (`/api/tts/voices`).
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    azure_auth::AzureAuth,
+    azure_tts::{self, Speech, Voice, VoiceCatalog, VoiceOptions},
+    config::Azure,
+    hal::MemorySink,
+    http,
+    lang,
+    queue,
+    tts_text,
+};
+use mock_cloud::{validate_ssml, Fault, MockCloud};
+use serde_json::Value;
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+mod common;
+
+use common::{cloud_client as client, mock_cloud};
+
+#[test]
+fn detects_the_language_of_a_reply() {
+    for (text, expected) in [
+        ("Bună! Sunt robotul tău şi sunt aici să te ajut.", Some("ro")),
+        ("Eu sunt bine, si tu ce mai faci?", Some("ro")),
+        ("Hello! I am your robot and I can help you with that.", Some("en")),
+        ("Bonjour, je suis ton robot. C'est un plaisir!", Some("fr")),
+        ("Hallo, ich bin dein Roboter und das ist schön.", Some("de")),
+        ("Hola, soy tu robot y estoy muy contento.", Some("es")),
+        ("Ciao, io sono il tuo robot e non dormo mai.", Some("it")),
+        ("Привет, я твой робот.", Some("ru")),
+        ("Привіт, я твій робот і їжак.", Some("uk")),
+        ("OK", None),
+        ("12 + 30 = 42", None),
+    ] {
+        assert_eq!(lang::detect(text), expected, "{text}");
+    }
+    assert_eq!(lang::language_of("en-GB"), "en");
+}
+
+#[test]
+fn voice_follows_request_then_reply_language_then_config() {
+    let azure = Azure { rate: "+10%".into(), ..Azure::default() };
+    let pick = |opts: VoiceOptions, text: &str| {
+        let v = Voice::select(&azure, &opts, text);
+        (v.name, v.locale)
+    };
+    let none = VoiceOptions::default;
+    let pair = |name: &str, locale: &str| (name.to_owned(), locale.to_owned());
+
+    assert_eq!(pick(none(), "Bună ziua, sunt aici."), pair("ro-RO-AlinaNeural", "ro-RO"));
+    assert_eq!(pick(none(), "Hello, how are you today? I am fine."), pair("en-US-JennyNeural", "en-US"));
+    // limbă fără voce în `voices` sau nedetectată → vocea implicită
+    assert_eq!(pick(none(), "Olá, tudo bem?"), pair("ro-RO-AlinaNeural", "ro-RO"));
+    // cerute explicit
+    let voice = |v: &str| VoiceOptions { voice: Some(v.into()), ..none() };
+    assert_eq!(pick(voice("ro-RO-EmilNeural"), "Hello, how are you?"), pair("ro-RO-EmilNeural", "ro-RO"));
+    assert_eq!(pick(voice("en-GB-RyanNeural"), "Bună"), pair("en-GB-RyanNeural", "en-GB"));
+    let locale = VoiceOptions { locale: Some("de-AT".into()), ..none() };
+    assert_eq!(pick(locale, "Bună"), pair("de-DE-KatjaNeural", "de-AT"));
+
+    let styled = VoiceOptions { style: Some("cheerful".into()), pitch: Some("-2st".into()), ..none() };
+    let v = Voice::select(&azure, &styled, "Bună");
+    assert_eq!((v.rate.as_str(), v.pitch.as_str(), v.style.as_str()), ("+10%", "-2st", "cheerful"));
+    let ssml = azure_tts::ssml(&v, "Bună");
+    assert!(ssml.contains(r#"<mstts:express-as style="cheerful"><prosody rate="+10%" pitch="-2st">Bună</prosody></mstts:express-as>"#), "{ssml}");
+    assert_eq!(validate_ssml(&ssml).unwrap(), "Bună");
+    // fără nimic setat: doar `<voice>`
+    let plain = azure_tts::ssml(&Voice::select(&Azure::default(), &none(), "Bună"), "Bună");
+    assert!(!plain.contains("prosody") && !plain.contains("express-as"), "{plain}");
+
+    // valorile ajung în atribute – aceleaşi reguli ca în `config`
+    for (opts, error) in [
+        (VoiceOptions { rate: Some("fast\"/>".into()), ..none() }, "azure.rate"),
+        (VoiceOptions { pitch: Some("+2 st".into()), ..none() }, "azure.pitch"),
+        (VoiceOptions { style: Some("vesel<".into()), ..none() }, "azure.style"),
+        (VoiceOptions { locale: Some("romana".into()), ..none() }, "azure.locale"),
+        (voice("Alina Neural"), "azure.voice"),
+    ] {
+        let err = opts.validate().unwrap_err().to_string();
+        assert!(err.contains(error), "{opts:?} → {err}");
+    }
+    assert!(serde_json::from_str::<VoiceOptions>(r#"{"speed": "fast"}"#).is_err());
+    assert!(Azure { voices: [("eng".into(), "en-US-JennyNeural".into())].into(), ..Azure::default() }.validate().is_err());
+    for ok in ["", "slow", "1.5", "-20%", "+0%"] {
+        assert!(Azure { rate: ok.into(), ..Azure::default() }.validate().is_ok(), "{ok}");
+    }
+}
+
+#[test]
+fn speech_reaches_azure_with_the_chosen_voice() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.tts_ms_per_char(1);
+    let azure_auth = Arc::new(AzureAuth::new(client(&cloud)));
+
+    azure_tts::tts_and_play(&mut client(&cloud), &azure_auth, &mut MemorySink::default(), "Hello, how are you? I am your robot.").unwrap();
+    let speech = Speech {
+        text:  "Salut".into(),
+        voice: VoiceOptions { voice: Some("ro-RO-EmilNeural".into()), style: Some("cheerful".into()), ..Default::default() },
+        ..Default::default()
+    };
+    azure_tts::speak(&mut client(&cloud), &azure_auth, &mut MemorySink::default(), &speech).unwrap();
+    // replica modelului: Markdown, `<`, `&` – SSML valid, citit în litere
+    azure_tts::tts_and_play(&mut client(&cloud), &azure_auth, &mut MemorySink::default(), "**Atenţie:** 3 < 5 & `x` e 20%.").unwrap();
+    // SSML de la client, neatins
+    let doc = r#"<speak version="1.0" xml:lang="ro-RO"><voice name="ro-RO-EmilNeural"><break time="300ms"/>Gata</voice></speak>"#;
+    azure_tts::speak(&mut client(&cloud), &azure_auth, &mut MemorySink::default(), &Speech::ssml(doc.into())).unwrap();
+    // `tts_start` arată doar textul rostit
+    assert_eq!(Speech::ssml(doc.into()).caption(), "Gata");
+    assert_eq!(Speech::ssml("<speak><p>3 &lt; 5 &amp;</p>\n<s>gata</s></speak>".into()).caption(), "3 < 5 & gata");
+    assert_eq!(Speech::from("**Atenţie:** gata".to_owned()).caption(), "Atenţie: gata");
+
+    let synthesis: Vec<_> = cloud.requests().into_iter().filter(|r| r.path == "/cognitiveservices/v1").collect();
+    assert!(synthesis.iter().all(|r| r.header("Authorization").is_some_and(|v| v.starts_with("Bearer "))));
+    let bodies: Vec<String> = synthesis.iter().map(|r| String::from_utf8_lossy(&r.body).into_owned()).collect();
+    assert!(bodies[0].contains(r#"xml:lang="en-US"><voice name="en-US-JennyNeural">"#), "{}", bodies[0]);
+    assert!(bodies[1].contains(r#"<voice name="ro-RO-EmilNeural"><mstts:express-as style="cheerful">"#), "{}", bodies[1]);
+    assert_eq!(validate_ssml(&bodies[2]).unwrap(), "Atenţie: trei < cinci şi x e douăzeci la sută.");
+    assert_eq!(bodies[3], doc);
+
+    /* -------- lista vocilor: descărcată o dată, apoi din cache -------- */
+    let catalog = Arc::new(VoiceCatalog::new(Arc::new(Mutex::new(client(&cloud))), azure_auth.clone()));
+    // prima cerere nu aşteaptă descărcarea
+    assert!(catalog.get().unwrap().is_none());
+    let voices = common::voices(&catalog).unwrap();
+    assert_eq!(voices.len(), 6);
+    let jenny = voices.iter().find(|v| v.name == "en-US-JennyNeural").unwrap();
+    assert_eq!((jenny.locale.as_str(), jenny.gender.as_str(), jenny.styles.len()), ("en-US", "Female", 4));
+    catalog.get().unwrap().unwrap();
+    let lists = |cloud: &MockCloud| cloud.requests().iter().filter(|r| r.path.ends_with("/voices/list")).count();
+    assert_eq!(lists(&cloud), 1);
+
+    // HTTP
+    let api = common::api(|srv, auth| http::register_tts(srv, auth, catalog.clone()));
+    let call = |query: &str, token: &str| {
+        let reply = api.request(Method::Get, &format!("/api/tts/voices{query}"), &[("Authorization", token)], b"");
+        (reply.status, reply.json())
+    };
+    let names = |body: &Value| -> Vec<String> {
+        body["voices"].as_array().unwrap().iter().map(|v| v["name"].as_str().unwrap().to_owned()).collect()
+    };
+
+    assert_eq!(call("", "Bearer x").0, 401);
+    let (status, body) = call("?lang=en", &api.bearer);
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(names(&body), ["en-US-JennyNeural", "en-GB-RyanNeural"]);
+    assert_eq!((body["region"].as_str(), body["default"]["voice"].as_str()), (Some("eastus"), Some("ro-RO-AlinaNeural")));
+    assert_eq!(names(&call("?locale=ro-RO", &api.bearer).1), ["ro-RO-AlinaNeural", "ro-RO-EmilNeural"]);
+    assert_eq!(lists(&cloud), 1);
+
+    // `?refresh=1` descarcă din nou în fundal; până atunci rămâne lista veche
+    assert_eq!(call("?refresh=1", &api.bearer).1["voices"].as_array().unwrap().len(), 6);
+    while lists(&cloud) < 2 {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // fără listă: 503 cât se descarcă, apoi eroarea Azure ajunge la pagină
+    let fresh = Arc::new(VoiceCatalog::new(Arc::new(Mutex::new(client(&cloud))), azure_auth));
+    let api = common::api(|srv, auth| http::register_tts(srv, auth, fresh.clone()));
+    cloud.fail_next_on("/cognitiveservices/voices", Fault::status(500));
+    let mut reply = api.send(Method::Get, "/api/tts/voices", &[], b"");
+    assert_eq!((reply.status, reply.header("Retry-After")), (503, Some("2")));
+    while reply.status == 503 {
+        thread::sleep(Duration::from_millis(10));
+        reply = api.send(Method::Get, "/api/tts/voices", &[], b"");
+    }
+    let body = reply.json();
+    assert_eq!((reply.status, body["error"]["service"].as_str()), (502, Some("Azure voices")), "{body}");
+    // eroarea e arătată o dată; următoarea descărcare a pornit deja
+    assert_eq!(common::voices(&fresh).unwrap().len(), 6);
+    assert_eq!(lists(&cloud), 4);
+    // un singur token pentru toate
+    assert_eq!(cloud.requests().iter().filter(|r| r.path == "/sts/v1.0/issueToken").count(), 1);
+}
+
+#[test]
+fn voice_list_does_not_wait_for_the_tts_connection() {
+    let (_retry, cloud) = mock_cloud();
+    let tts_http = Arc::new(Mutex::new(client(&cloud)));
+    let catalog = Arc::new(VoiceCatalog::new(tts_http.clone(), Arc::new(AzureAuth::new(client(&cloud)))));
+    let api = common::api(|srv, auth| http::register_tts(srv, auth, catalog.clone()));
+
+    // workerul TTS ţine conexiunea pe durata unei replici
+    let speaking = tts_http.lock().unwrap();
+    let started = std::time::Instant::now();
+    let reply = api.send(Method::Get, "/api/tts/voices", &[], b"");
+    assert_eq!(reply.status, 503);
+    assert!(started.elapsed() < Duration::from_millis(500), "{:?}", started.elapsed());
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(api.send(Method::Get, "/api/tts/voices", &[], b"").status, 503);
+
+    // replica s-a terminat → lista soseşte pe aceeaşi conexiune
+    drop(speaking);
+    assert_eq!(common::voices(&catalog).unwrap().len(), 6);
+    assert_eq!(api.send(Method::Get, "/api/tts/voices", &[], b"").status, 200);
+}
+
+#[test]
+fn token_is_reused_refreshed_and_replaced_when_rejected() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.tts_ms_per_char(1);
+    let auth = AzureAuth::with_lifetime(client(&cloud), Duration::from_millis(300), Duration::from_secs(5));
+    let mut http = client(&cloud);
+    let mut say = || azure_tts::tts_and_play(&mut http, &auth, &mut MemorySink::default(), "Salut");
+    let tokens = |cloud: &MockCloud| cloud.requests().iter().filter(|r| r.path == "/sts/v1.0/issueToken").count();
+
+    say().unwrap();
+    say().unwrap();
+    assert_eq!(tokens(&cloud), 1);
+    let requests = cloud.requests();
+    let issued = requests.iter().find(|r| r.path == "/sts/v1.0/issueToken").unwrap();
+    assert!(issued.header("Ocp-Apim-Subscription-Key").is_some());
+    assert!(requests.iter().filter(|r| r.path == "/cognitiveservices/v1").all(|r| r.header("Ocp-Apim-Subscription-Key").is_none()));
+
+    // revocat (cheie rotită) → 401 → alt token, replica merge
+    cloud.revoke_azure_tokens();
+    say().unwrap();
+    assert_eq!(tokens(&cloud), 2);
+
+    // reînnoit înainte să expire; dacă `issueToken` cade, cel vechi rămâne bun
+    thread::sleep(Duration::from_millis(350));
+    say().unwrap();
+    assert_eq!(tokens(&cloud), 3);
+    thread::sleep(Duration::from_millis(350));
+    cloud.fail_next_on("/sts", Fault::status(503));
+    say().unwrap();
+    assert_eq!(tokens(&cloud), 4);
+    say().unwrap();
+    assert_eq!(tokens(&cloud), 5);
+}
+
+#[test]
+fn idle_worker_refreshes_the_token_ahead_of_time() {
+    let (_retry, cloud) = mock_cloud();
+    cloud.tts_ms_per_char(1);
+    let auth = Arc::new(AzureAuth::with_lifetime(client(&cloud), Duration::from_millis(400), Duration::from_secs(5)));
+    let tokens = |cloud: &MockCloud| cloud.requests().iter().filter(|r| r.path == "/sts/v1.0/issueToken").count();
+    let (tx, rx) = queue::channel::<Speech>("tts_test");
+    let worker = {
+        let auth = auth.clone();
+        let mut http = client(&cloud);
+        thread::spawn(move || {
+            while let Some(speech) = azure_tts::next_speech(&rx, &auth) {
+                azure_tts::speak(&mut http, &auth, &mut MemorySink::default(), &speech).unwrap();
+            }
+        })
+    };
+
+    // înainte de prima replică nu e nimic de reînnoit
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(tokens(&cloud), 0);
+    tx.send("Salut".into()).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(tokens(&cloud), 1);
+
+    // coada goală: tokenul e reînnoit fără nicio replică
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(tokens(&cloud), 2);
+    let before = cloud.requests().len();
+    tx.send("Gata".into()).unwrap();
+    drop(tx);
+    worker.join().unwrap();
+    let after = cloud.requests();
+    assert_eq!((after.len(), after.last().unwrap().path.as_str()), (before + 1, "/cognitiveservices/v1"));
+}
+
+#[test]
+fn replies_are_prepared_for_speech() {
+    assert_eq!(tts_text::escape_xml(r#"a < b && "c" > 'd'"#), "a &lt; b &amp;&amp; &quot;c&quot; &gt; &apos;d&apos;");
+
+    let md = "# Reţetă\n\nAi nevoie de:\n- **făină**\n* _ouă_ şi `lapte`\n1. Amestecă\n\n---\n> Vezi [site-ul](https://example.com) ![poza](p.png)\n\n```rust\nlet x_y = 2 * 3;\n```\n| a | b |\n|---|:-:|\n| 1 | 2 |";
+    assert_eq!(
+        tts_text::strip_markdown(md),
+        "Reţetă.\n\nAi nevoie de:\nfăină.\nouă şi lapte.\n1. Amestecă.\n\nVezi site-ul poza\n\nlet x_y = 2 * 3;\na, b.\n1, 2."
+    );
+
+    for (text, spoken) in [
+        ("Am 1 robot şi 2 servo.", "Am unu robot şi doi servo."),
+        ("0, 12, 21, 100, 101, 1000, 2024", "zero, doisprezece, douăzeci şi unu, o sută, o sută unu, o mie, două mii douăzeci şi patru"),
+        ("1.500.000 de oameni", "un milion cinci sute de mii de oameni"),
+        ("Afară sunt -3,5 °C sau 22°C.", "Afară sunt minus trei virgulă cinci grade Celsius sau douăzeci şi două de grade Celsius."),
+        ("1 km, 2 kg, 20 km/h, 1 ms, 12 kg", "un kilometru, două kilograme, douăzeci de kilometri pe oră, o milisecundă, douăsprezece kilograme"),
+        ("Bateria e la 75%.", "Bateria e la şaptezeci şi cinci la sută."),
+        ("Costă 3,05 lei.", "Costă trei virgulă zero cinci lei."),
+        ("ESP32 are 2 nuclee, mp3 şi 3D.", "ESP32 are doi nuclee, mp3 şi 3D."),
+        ("Dl. Popescu, nr. 5, str. Lungă etc.", "Dl. Popescu, numărul cinci, strada Lungă etcetera."),
+        ("de ex. mere, pere ş.a.m.d.", "de exemplu mere, pere şi aşa mai departe."),
+        ("2 + 2 = 4 & gata", "doi + doi egal patru şi gata"),
+        ("GPT-4 şi 10-12", "GPT-4 şi zece-doisprezece"),
+    ] {
+        assert_eq!(tts_text::expand_ro(text), spoken, "{text}");
+    }
+    // numai pentru vocile româneşti
+    assert_eq!(tts_text::prepare("**Ai** 5 mesaje", "en-US"), "Ai 5 mesaje");
+    assert_eq!(tts_text::prepare("**Ai** 5 mesaje", "ro-RO"), "Ai cinci mesaje");
+
+    assert!(azure_tts::check_ssml(" <speak version=\"1.0\">x</speak>\n").is_ok());
+    assert!(azure_tts::check_ssml("Salut").is_err());
+    assert!(azure_tts::check_ssml(&format!("<speak>{}</speak>", "a".repeat(azure_tts::MAX_SSML))).is_err());
+}
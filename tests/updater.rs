@@ -0,0 +1,192 @@
+//! `updater`: manifest de pe un server local, versiuni, fereastră de mentenanţă.
+
+use embedded_svc::{http::Method, io::Write as _};
+use esp32_hello_world::{
+    clock,
+    hal::{HostHttp, HostHttpServer, HttpServer, MemoryFirmware, MemoryKv},
+    http,
+    ota::{self, Ota},
+    updater::{self, PullState, Updater},
+    util::{self, sha256},
+};
+use serde_json::{json, Value};
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+mod common;
+
+const FLEET: &str = "https://fleet.example";
+
+fn image(len: usize) -> Vec<u8> {
+    let mut img: Vec<u8> = (0..len).map(|i| (i * 13) as u8).collect();
+    img[0] = ota::IMAGE_MAGIC;
+    img
+}
+
+/// Serverul flotei: `/fleet/manifest.json` şi `/fleet/fw.bin`.
+fn fleet(manifest: Value, img: Vec<u8>) -> SocketAddr {
+    let mut srv = HostHttpServer::new();
+    let manifest = manifest.to_string();
+    srv.handler("/fleet/manifest.json", Method::Get, move |req| {
+        req.into_ok_response()?.write_all(manifest.as_bytes())?;
+        Ok(())
+    })
+    .unwrap();
+    srv.handler("/fleet/fw.bin", Method::Get, move |req| {
+        let len = img.len().to_string();
+        req.into_response(200, None, &[("Content-Length", &len)])?.write_all(&img)?;
+        Ok(())
+    })
+    .unwrap();
+    srv.start("127.0.0.1:0").unwrap()
+}
+
+/// `https://fleet.example` → serverul local
+fn updater(addr: SocketAddr, window: Option<&str>) -> (Updater, MemoryFirmware) {
+    let fw = MemoryFirmware::new(256 * 1024);
+    let http = HostHttp::new().redirect(FLEET, &format!("http://{addr}"));
+    let up = Updater::new(Arc::new(Ota::new(fw.clone(), None)), http, MemoryKv::default()).unwrap();
+    let mut settings = up.settings();
+    settings.url = Some(format!("{FLEET}/fleet/manifest.json"));
+    settings.window = window.map(str::to_owned);
+    up.set_settings(settings).unwrap();
+    (up, fw)
+}
+
+#[test]
+fn versions_and_windows() {
+    assert_eq!(updater::parse_version("v1.2.3-beta"), Some((1, 2, 3)));
+    assert_eq!(updater::parse_version("2"), Some((2, 0, 0)));
+    assert_eq!(updater::parse_version("1.x"), None);
+    assert!(updater::newer("0.10.0", "0.9.9"));
+    assert!(!updater::newer("0.1.0", "0.1.0"));
+    assert!(!updater::newer("necunoscut", "0.1.0"));
+
+    assert!(updater::in_window("02:00-05:00", 3 * 60));
+    assert!(!updater::in_window("02:00-05:00", 5 * 60));
+    // peste miezul nopţii
+    assert!(updater::in_window("22:30-04:00", 23 * 60));
+    assert!(updater::in_window("22:30-04:00", 60));
+    assert!(!updater::in_window("22:30-04:00", 12 * 60));
+    assert!(!updater::in_window("25:00-04:00", 60));
+}
+
+#[test]
+fn newer_version_is_downloaded_and_applied() {
+    let img = image(100_000);
+    let sha = util::hex(&sha256(&img));
+    let addr = fleet(json!({"version": "99.0.0", "url": "fw.bin", "sha256": sha}), img.clone());
+    let (up, fw) = updater(addr, None);
+
+    assert_eq!(up.check(false), PullState::Ready);
+    let report = up.report();
+    assert_eq!((report.available.as_deref(), report.received), (Some("99.0.0"), img.len()));
+    assert_eq!(fw.0.lock().unwrap().image, img);
+    assert!(fw.0.lock().unwrap().activated);
+
+    thread::sleep(Duration::from_millis(1500));
+    assert_eq!(fw.0.lock().unwrap().restarts, 1);
+}
+
+#[test]
+fn same_version_or_min_version_skip_the_download() {
+    let img = image(1000);
+    let sha = util::hex(&sha256(&img));
+    let addr = fleet(json!({"version": esp32_hello_world::VERSION, "url": "/fleet/fw.bin", "sha256": sha}), img.clone());
+    let (up, fw) = updater(addr, None);
+    assert_eq!(up.check(false), PullState::UpToDate);
+
+    let addr = fleet(json!({"version": "99.0.0", "url": "fw.bin", "sha256": sha, "min_version": "98.0.0"}), img);
+    let (up, _) = updater(addr, None);
+    assert_eq!(up.check(false), PullState::Blocked);
+    assert!(fw.0.lock().unwrap().image.is_empty());
+}
+
+#[test]
+fn maintenance_window_is_respected_unless_forced() {
+    let img = image(5000);
+    let sha = util::hex(&sha256(&img));
+    let addr = fleet(json!({"version": "99.0.0", "url": "fw.bin", "sha256": sha}), img);
+
+    // ceasul PC-ului, fără fus orar setat = UTC; fereastra începe peste o oră
+    clock::mark_synced();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() / 60 % 1440;
+    let hm = |m: u64| format!("{:02}:{:02}", m % 1440 / 60, m % 60);
+    let window = format!("{}-{}", hm(now + 60), hm(now + 120));
+    let (up, fw) = updater(addr, Some(&window));
+
+    assert_eq!(up.check(false), PullState::Waiting);
+    assert!(!fw.0.lock().unwrap().activated);
+    assert_eq!(up.check(true), PullState::Ready);
+}
+
+#[test]
+fn wrong_checksum_fails_without_activating() {
+    let img = image(5000);
+    let addr = fleet(json!({"version": "99.0.0", "url": "fw.bin", "sha256": "ab".repeat(32)}), img);
+    let (up, fw) = updater(addr, None);
+
+    assert_eq!(up.check(false), PullState::Failed);
+    assert!(up.report().error.unwrap().contains("SHA-256"));
+    assert!(!fw.0.lock().unwrap().activated);
+}
+
+#[test]
+fn api_configures_the_manifest() {
+    let fw = MemoryFirmware::new(1024);
+    let up = Updater::new(Arc::new(Ota::new(fw, None)), HostHttp::new(), MemoryKv::default()).unwrap();
+    let api = common::api(|srv, auth| http::register_updater(srv, auth, up.start()?));
+    let call = |method, path: &str, body: Value| api.call(method, path, &body);
+
+    let (status, body) = call(Method::Get, "/api/ota/pull", json!(null));
+    assert_eq!((status, body["report"]["state"].as_str()), (200, Some("disabled")));
+    assert_eq!(call(Method::Post, "/api/ota/check", json!(null)).0, 409);
+
+    assert_eq!(call(Method::Put, "/api/ota/pull", json!({"window": "2-5"})).0, 400);
+    assert_eq!(call(Method::Put, "/api/ota/pull", json!({"url": "ftp://x"})).0, 400);
+    // imagini nesemnate: manifestul doar prin TLS
+    assert_eq!(call(Method::Put, "/api/ota/pull", json!({"url": "http://127.0.0.1:9/m.json"})).0, 400);
+    let (status, body) = call(Method::Put, "/api/ota/pull", json!({"url": "https://127.0.0.1:9/m.json", "window": "02:00-05:00"}));
+    assert_eq!(status, 200);
+    assert_eq!(body["settings"]["interval_min"], updater::DEFAULT_INTERVAL_MIN);
+    assert_eq!(body["report"]["state"], "idle");
+
+    // serverul nu există – verificarea eşuează, dar raportul o spune
+    assert_eq!(call(Method::Post, "/api/ota/check", json!(null)).0, 202);
+    let mut state = String::new();
+    for _ in 0..100 {
+        state = call(Method::Get, "/api/ota/pull", json!(null)).1["report"]["state"].as_str().unwrap().to_owned();
+        if state == "failed" {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(state, "failed");
+}
+
+#[test]
+fn oversized_manifest_is_not_read_whole() {
+    let big = json!({"version": "99.0.0", "url": "fw.bin", "sha256": "ab".repeat(32), "notes": "x".repeat(64 * 1024)});
+    let addr = fleet(big, image(1000));
+    let (up, fw) = updater(addr, None);
+
+    assert_eq!(up.check(false), PullState::Failed);
+    assert!(up.report().error.unwrap().contains("prea mare"));
+    assert!(fw.0.lock().unwrap().image.is_empty());
+}
+
+#[test]
+fn plain_http_needs_signed_images() {
+    let signer = p256::ecdsa::SigningKey::from_slice(&[7; 32]).unwrap();
+    let public = util::hex(signer.verifying_key().to_encoded_point(true).as_bytes());
+    let ota = Ota::new(MemoryFirmware::new(1024), Some(ota::public_key(&public).unwrap()));
+    let up = Updater::new(Arc::new(ota), HostHttp::new(), MemoryKv::default()).unwrap();
+
+    let mut settings = up.settings();
+    settings.url = Some("http://fleet.local/manifest.json".into());
+    up.set_settings(settings).unwrap();
+}
@@ -0,0 +1,278 @@
+//! `wifi`: ordinea reţelelor, backoff, trecerea pe altă reţea şi `/api/wifi`.
+
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    hal::{ApInfo, IpConfig, KeyValueStore, MemoryKv, ScriptedRadio, StaticIp},
+    http,
+    wifi::{self, Backoff, Link, Network, WifiManager},
+};
+use serde_json::{json, Value};
+use std::{
+    net::Ipv4Addr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+mod common;
+
+const FAST: Backoff = Backoff { min: Duration::from_millis(20), max: Duration::from_millis(100) };
+
+fn ap(ssid: &str, rssi: i32) -> ApInfo {
+    ApInfo { ssid: ssid.into(), rssi }
+}
+
+fn ssids(list: Vec<&Network>) -> Vec<&str> {
+    list.into_iter().map(|n| n.ssid.as_str()).collect()
+}
+
+fn wait_for(wifi: &WifiManager, what: impl Fn(&Link) -> bool) -> Link {
+    for _ in 0..200 {
+        let link = wifi.link();
+        if what(&link) {
+            return link;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    wifi.link()
+}
+
+#[test]
+fn networks_are_ranked_by_visibility_signal_and_priority() {
+    let mut known = vec![
+        Network::new("Birou", "parola-birou", 1),
+        Network::new("Acasa", "parola-acasa", 2),
+        Network::new("Telefon", "parola-tel", 0),
+        Network::new("Ascunsa", "parola-asc", 5),
+    ];
+    let seen = [ap("Birou", -60), ap("Acasa", -90), ap("Telefon", -40), ap("Vecin", -30)];
+    // „Acasa” are prioritate mai mare, dar semnalul e prea slab;
+    // „Ascunsa” nu apare la scanare – la final
+    assert_eq!(ssids(wifi::rank(&known, &seen)), ["Birou", "Telefon", "Acasa", "Ascunsa"]);
+
+    // la aceeaşi prioritate câştigă ultima reuşită, apoi semnalul
+    known[2].priority = 1;
+    known[2].last_ok = 3;
+    assert_eq!(ssids(wifi::rank(&known, &seen))[..2], ["Telefon", "Birou"]);
+    known[2].last_ok = 0;
+    assert_eq!(ssids(wifi::rank(&known, &seen))[..2], ["Telefon", "Birou"]);
+    known[0].last_ok = 1;
+    assert_eq!(ssids(wifi::rank(&known, &seen))[..2], ["Birou", "Telefon"]);
+
+    // scanare eşuată → toate, după prioritate
+    assert_eq!(ssids(wifi::rank(&known, &[])), ["Ascunsa", "Acasa", "Birou", "Telefon"]);
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let b = Backoff { min: Duration::from_secs(2), max: Duration::from_secs(60) };
+    let delays: Vec<_> = (0..8).map(|n| b.delay(n).as_secs()).collect();
+    assert_eq!(delays, [0, 2, 4, 8, 16, 32, 60, 60]);
+    assert_eq!(Backoff::DEFAULT.delay(1000), Backoff::DEFAULT.max);
+}
+
+#[test]
+fn failed_network_falls_over_to_the_next_one() {
+    let radio = ScriptedRadio::new();
+    radio.network("Acasa", "parola-buna", -50).network("Birou", "parola-birou", -70);
+    let wifi = WifiManager::new(radio.clone(), MemoryKv::default(), FAST).unwrap();
+    wifi.set_networks(vec![Network::new("Acasa", "parola-veche", 2), Network::new("Birou", "parola-birou", 1)])
+        .unwrap();
+
+    let link = wifi.connect_best().unwrap();
+    assert!(matches!(&link, Link::Up { ssid, rssi: -70, .. } if ssid == "Birou"), "{link:?}");
+    assert_eq!(radio.0.lock().unwrap().attempts, ["Acasa", "Birou"]);
+    let last_ok: Vec<_> = wifi.networks().iter().map(|n| n.last_ok).collect();
+    assert_eq!(last_ok, [0, 1]);
+
+    radio.drop_network("Birou");
+    assert!(wifi.connect_best().is_err());
+    assert_eq!(wifi.link(), Link::Down);
+}
+
+#[test]
+fn lost_link_reconnects_and_notifies_subscribers() {
+    let radio = ScriptedRadio::new();
+    radio.network("Acasa", "parola-acasa", -50).network("Birou", "parola-birou", -70);
+    let wifi = WifiManager::new(radio.clone(), MemoryKv::default(), FAST).unwrap();
+    wifi.set_networks(vec![Network::new("Acasa", "parola-acasa", 1), Network::new("Birou", "parola-birou", 0)])
+        .unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    wifi.subscribe({
+        let seen = seen.clone();
+        move |link| seen.lock().unwrap().push(link.clone())
+    });
+    let wifi = wifi.start().unwrap();
+    assert!(wifi.wait_up(Duration::from_secs(2)));
+
+    // routerul de acasă cade: evenimentul de deconectare → „Birou”
+    radio.drop_network("Acasa");
+    wifi.link_lost();
+    let link = wait_for(&wifi, |l| matches!(l, Link::Up { ssid, .. } if ssid == "Birou"));
+    assert!(matches!(&link, Link::Up { ssid, .. } if ssid == "Birou"), "{link:?}");
+
+    let seen: Vec<_> = seen.lock().unwrap().iter().map(|l| serde_json::to_value(l).unwrap()).collect();
+    assert_eq!(seen.len(), 3, "{seen:?}");
+    assert_eq!((seen[0]["state"].as_str(), seen[0]["ssid"].as_str()), (Some("up"), Some("Acasa")));
+    assert_eq!(seen[1]["state"], "down");
+    assert_eq!((seen[2]["state"].as_str(), seen[2]["ssid"].as_str()), (Some("up"), Some("Birou")));
+    assert!(seen[2]["ip"].as_str().is_some_and(|ip| ip.starts_with("192.168.1.")));
+
+    // deconectare scurtă, reţeaua e tot acolo → o singură încercare, aceeaşi reţea
+    let attempts = radio.0.lock().unwrap().attempts.len();
+    wifi.link_lost();
+    thread::sleep(Duration::from_millis(50));
+    assert!(wifi.link().is_up());
+    assert_eq!(radio.0.lock().unwrap().attempts.len(), attempts + 1);
+}
+
+#[test]
+fn new_network_is_tried_right_away_despite_backoff() {
+    let radio = ScriptedRadio::new();
+    let slow = Backoff { min: Duration::from_secs(30), max: Duration::from_secs(60) };
+    let wifi = WifiManager::new(radio.clone(), MemoryKv::default(), slow).unwrap().start().unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(wifi.link(), Link::Down);
+
+    radio.network("Telefon", "parola-tel", -40);
+    wifi.add_network("Telefon", "parola-tel", 0).unwrap();
+    assert!(wifi.wait_up(Duration::from_secs(2)));
+}
+
+#[test]
+fn networks_survive_a_restart_and_bad_ones_are_rejected() {
+    let mut kv = MemoryKv::default();
+    kv.set_str("wifi_nets", r#"[{"ssid": "Acasa", "pass": "parola-acasa", "priority": 3, "last_ok": 7}]"#)
+        .unwrap();
+    let wifi = WifiManager::new(ScriptedRadio::new(), kv, FAST).unwrap();
+    assert_eq!(wifi.networks()[0].last_ok, 7);
+
+    assert!(wifi.add_network("Scurta", "1234", 0).is_err());
+    assert!(wifi.add_network("", "", 0).is_err());
+    assert!(wifi.set_networks(vec![Network::new("A", "", 0); 2]).is_err());
+    let many: Vec<_> = (0..=wifi::MAX_NETWORKS).map(|i| Network::new(&format!("n{i}"), "", 0)).collect();
+    assert!(wifi.set_networks(many).is_err());
+    assert_eq!(wifi.networks().len(), 1);
+
+    // aceeaşi reţea, altă prioritate – istoria rămâne
+    wifi.add_network("Acasa", "parola-acasa", 1).unwrap();
+    assert_eq!((wifi.networks()[0].priority, wifi.networks()[0].last_ok), (1, 7));
+}
+
+fn fixed(ip: [u8; 4], gateway: [u8; 4], netmask: [u8; 4]) -> IpConfig {
+    IpConfig {
+        hostname:  "myrobo".into(),
+        static_ip: Some(StaticIp {
+            ip:      ip.into(),
+            gateway: gateway.into(),
+            netmask: netmask.into(),
+            dns:     vec![Ipv4Addr::new(1, 1, 1, 1)],
+        }),
+    }
+}
+
+#[test]
+fn static_addresses_are_validated() {
+    assert!(wifi::validate_ip(&wifi::dhcp()).is_ok());
+    assert!(wifi::validate_ip(&fixed([192, 168, 43, 50], [192, 168, 43, 1], [255, 255, 255, 0])).is_ok());
+    assert!(wifi::validate_ip(&fixed([10, 0, 7, 9], [10, 0, 0, 1], [255, 255, 0, 0])).is_ok());
+
+    // altă reţea, mască necontiguă, adresa reţelei / broadcast, gateway-ul însuşi
+    assert!(wifi::validate_ip(&fixed([192, 168, 2, 50], [192, 168, 1, 1], [255, 255, 255, 0])).is_err());
+    assert!(wifi::validate_ip(&fixed([192, 168, 1, 50], [192, 168, 1, 1], [255, 0, 255, 0])).is_err());
+    assert!(wifi::validate_ip(&fixed([192, 168, 1, 0], [192, 168, 1, 1], [255, 255, 255, 0])).is_err());
+    assert!(wifi::validate_ip(&fixed([192, 168, 1, 255], [192, 168, 1, 1], [255, 255, 255, 0])).is_err());
+    assert!(wifi::validate_ip(&fixed([192, 168, 1, 1], [192, 168, 1, 1], [255, 255, 255, 0])).is_err());
+
+    let mut cfg = fixed([192, 168, 1, 50], [192, 168, 1, 1], [255, 255, 255, 0]);
+    cfg.static_ip.as_mut().unwrap().dns = vec![Ipv4Addr::new(1, 1, 1, 1); 3];
+    assert!(wifi::validate_ip(&cfg).is_err());
+    let cfg = IpConfig { hostname: "un-nume-mult-prea-lung-pentru-dhcp".into(), static_ip: None };
+    assert!(wifi::validate_ip(&cfg).is_err());
+}
+
+#[test]
+fn static_address_is_applied_at_startup_and_survives_a_restart() {
+    let mut kv = MemoryKv::default();
+    let cfg = fixed([192, 168, 43, 50], [192, 168, 43, 1], [255, 255, 255, 0]);
+    kv.set("wifi_ip", &serde_json::to_vec(&cfg).unwrap()).unwrap();
+    let radio = ScriptedRadio::new();
+    radio.network("Telefon", "parola-tel", -40);
+    let wifi = WifiManager::new(radio.clone(), kv, FAST).unwrap();
+    assert_eq!(radio.0.lock().unwrap().ip.as_ref(), Some(&cfg));
+    wifi.add_network("Telefon", "parola-tel", 0).unwrap();
+    let link = wifi.connect_best().unwrap();
+    assert!(matches!(link, Link::Up { ip, .. } if ip == Ipv4Addr::new(192, 168, 43, 50)), "{link:?}");
+
+    // configuraţie stricată în NVS → DHCP
+    let mut kv = MemoryKv::default();
+    kv.set_str("wifi_ip", r#"{"hostname": "myrobo", "static": {"ip": "10.0.0.0", "gateway": "10.0.0.1", "netmask": "255.0.0.0"}}"#)
+        .unwrap();
+    let radio = ScriptedRadio::new();
+    let wifi = WifiManager::new(radio.clone(), kv, FAST).unwrap();
+    assert_eq!(wifi.ip_config(), wifi::dhcp());
+    assert_eq!(radio.0.lock().unwrap().ip, Some(wifi::dhcp()));
+}
+
+#[test]
+fn changing_the_address_reconnects() {
+    let radio = ScriptedRadio::new();
+    radio.network("Acasa", "parola-acasa", -50);
+    let wifi = WifiManager::new(radio.clone(), MemoryKv::default(), FAST).unwrap();
+    wifi.add_network("Acasa", "parola-acasa", 0).unwrap();
+    let wifi = wifi.start().unwrap();
+    assert!(wifi.wait_up(Duration::from_secs(2)));
+
+    wifi.set_ip_config(fixed([192, 168, 1, 77], [192, 168, 1, 1], [255, 255, 255, 0])).unwrap();
+    let link = wait_for(&wifi, |l| matches!(l, Link::Up { ip, .. } if ip.octets()[3] == 77));
+    assert!(matches!(link, Link::Up { ip, .. } if ip == Ipv4Addr::new(192, 168, 1, 77)), "{link:?}");
+    assert_eq!(radio.0.lock().unwrap().attempts.len(), 2);
+}
+
+#[test]
+fn api_lists_networks_without_passwords() {
+    let radio = ScriptedRadio::new();
+    radio.network("Acasa", "parola-acasa", -50);
+    let wifi = WifiManager::new(radio, MemoryKv::default(), FAST).unwrap();
+    wifi.add_network("Acasa", "parola-acasa", 1).unwrap();
+    let wifi = wifi.start().unwrap();
+    assert!(wifi.wait_up(Duration::from_secs(2)));
+
+    let api = common::api(|srv, auth| http::register_wifi(srv, auth, wifi.clone()));
+    let call = |method, body: Value| api.call(method, "/api/wifi", &body);
+
+    let (status, body) = call(Method::Get, json!(null));
+    assert_eq!(status, 200);
+    assert_eq!((body["link"]["state"].as_str(), body["link"]["ssid"].as_str()), (Some("up"), Some("Acasa")));
+    assert_eq!(body["networks"][0]["open"], false);
+    assert!(!body.to_string().contains("parola-acasa"));
+
+    // fără `pass` parola rămâne; reţea deschisă nouă
+    let (status, body) = call(
+        Method::Put,
+        json!({"networks": [{"ssid": "Acasa", "priority": 4}, {"ssid": "Cafenea", "pass": ""}]}),
+    );
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["networks"][1]["open"], true);
+    assert_eq!(wifi.networks()[0].pass, "parola-acasa");
+    assert_eq!(wifi.networks()[0].priority, 4);
+
+    assert_eq!(call(Method::Put, json!({"networks": [{"ssid": "X", "pass": "scurta"}]})).0, 400);
+    assert_eq!(call(Method::Put, json!({"retele": []})).0, 400);
+    assert_eq!(wifi.networks().len(), 2);
+
+    // adresă fixă + hostname DHCP; cheile lipsă rămân
+    assert_eq!(body["ip"], json!({"hostname": "myrobo", "static": null}));
+    let fixed = json!({"ip": "192.168.1.60", "gateway": "192.168.1.1", "netmask": "255.255.255.0", "dns": ["9.9.9.9"]});
+    let (status, body) = call(Method::Put, json!({"ip": {"hostname": "Robo-Sufragerie", "static": fixed}}));
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["ip"]["hostname"], "robo-sufragerie");
+    assert_eq!(body["ip"]["static"]["dns"], json!(["9.9.9.9"]));
+    let (_, body) = call(Method::Put, json!({"ip": {"static": null}}));
+    assert_eq!(body["ip"], json!({"hostname": "robo-sufragerie", "static": null}));
+
+    assert_eq!(call(Method::Put, json!({"ip": {"static": {"ip": "192.168.1.999"}}})).0, 400);
+    let other_net = json!({"ip": "10.1.1.5", "gateway": "192.168.1.1", "netmask": "255.255.255.0"});
+    assert_eq!(call(Method::Put, json!({"ip": {"static": other_net}})).0, 400);
+    assert_eq!(wifi.ip_config().hostname, "robo-sufragerie");
+}
@@ -0,0 +1,194 @@
+//! `/ws`: comenzi JSON, evenimente către toţi clienţii, oprire „dead-man”.
+
+use anyhow::Result;
+use embedded_svc::http::Method;
+use esp32_hello_world::{
+    audio::Job,
+    hal::{HostWsClient, RecordingMotors, ServoBank},
+    motion::{self, Motion},
+    servo::ServoId,
+    drive,
+};
+use serde_json::{json, Value};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+mod common;
+
+use common::Robot;
+
+const WAIT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Default)]
+struct SharedServos(Arc<Mutex<Vec<(ServoId, f32)>>>);
+
+impl ServoBank for SharedServos {
+    fn set_angle(&mut self, id: ServoId, deg: f32) -> Result<()> {
+        self.0.lock().unwrap().push((id, deg));
+        Ok(())
+    }
+}
+
+fn start() -> Robot {
+    start_with(SharedServos::default())
+}
+
+fn start_with(servos: SharedServos) -> Robot {
+    common::robot(Motion::new(RecordingMotors::default(), servos), |rx_audio, tx_reply| {
+        while let Ok(job) = rx_audio.recv() {
+            if let Job::Text(text) = job {
+                let _ = tx_reply.send(Ok((text.clone(), text.to_uppercase())));
+            }
+        }
+    })
+}
+
+fn connect(robot: &Robot) -> HostWsClient {
+    let mut ws = HostWsClient::connect(robot.addr, "/ws").unwrap();
+    send(&mut ws, json!({"cmd": "auth", "token": robot.auth.token()}));
+    // starea motoarelor vine imediat după autentificare
+    assert_eq!(next(&mut ws)["event"], "motors");
+    ws
+}
+
+fn next(ws: &mut HostWsClient) -> Value {
+    let text = ws.recv_text(WAIT).expect("niciun eveniment");
+    serde_json::from_str(&text).unwrap()
+}
+
+fn send(ws: &mut HostWsClient, cmd: Value) {
+    ws.send_text(&cmd.to_string()).unwrap();
+}
+
+/// Repetă comanda la 10 Hz (ca joystick-ul) până la primul eveniment.
+fn hold(ws: &mut HostWsClient, cmd: Value) -> Value {
+    let t0 = Instant::now();
+    while t0.elapsed() < WAIT {
+        send(ws, cmd.clone());
+        if let Some(text) = ws.recv_text(Duration::from_millis(100)) {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+    panic!("niciun eveniment pentru {cmd}");
+}
+
+#[test]
+fn drive_moves_motors_and_notifies_every_client() {
+    let robot = start();
+    let mut a = connect(&robot);
+    let mut b = connect(&robot);
+
+    let expected = json!({"event": "motors", "left": 100, "right": 33});
+    assert_eq!(hold(&mut a, json!({"cmd": "drive", "linear": 1.0, "angular": 0.5})), expected);
+    assert_eq!(next(&mut b), expected);
+    assert_eq!(robot.motion.lock().unwrap().speeds(), (100, 33));
+
+    send(&mut b, json!({"cmd": "stop"}));
+    assert_eq!(next(&mut a), json!({"event": "motors", "left": 0, "right": 0}));
+    assert_eq!(robot.motion.lock().unwrap().speeds(), (0, 0));
+}
+
+#[test]
+fn motors_stop_without_heartbeat() {
+    let robot = start();
+    let mut ws = connect(&robot);
+
+    assert_eq!(hold(&mut ws, json!({"cmd": "drive", "linear": 0.5, "angular": 0.0}))["left"], 50);
+
+    // ping-urile la 10 Hz ţin comanda în viaţă
+    let t0 = Instant::now();
+    while t0.elapsed() < drive::DEADMAN * 3 {
+        send(&mut ws, json!({"cmd": "ping"}));
+        assert_eq!(next(&mut ws), json!({"event": "pong"}));
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(robot.motion.lock().unwrap().speeds(), (50, 50));
+
+    let t0 = Instant::now();
+    assert_eq!(next(&mut ws), json!({"event": "motors", "left": 0, "right": 0}));
+    assert!(t0.elapsed() >= drive::DEADMAN / 2);
+}
+
+#[test]
+fn dropped_socket_stops_its_drive() {
+    let robot = start();
+    let mut watcher = connect(&robot);
+    let mut driver = connect(&robot);
+
+    assert_eq!(hold(&mut driver, json!({"cmd": "drive", "linear": -1.0, "angular": 0.0}))["left"], -100);
+    assert_eq!(next(&mut watcher)["left"], -100);
+
+    let t0 = Instant::now();
+    driver.close().unwrap();
+    assert_eq!(next(&mut watcher), json!({"event": "motors", "left": 0, "right": 0}));
+    assert!(t0.elapsed() < drive::DEADMAN);
+}
+
+#[test]
+fn servo_say_and_bad_commands() {
+    let servos = SharedServos::default();
+    let robot = start_with(servos.clone());
+    let mut ws = connect(&robot);
+    let mut other = connect(&robot);
+
+    send(&mut ws, json!({"cmd": "servo", "right": 45.0}));
+    send(&mut ws, json!({"cmd": "say", "text": " Bună! "}));
+    assert_eq!(robot.tts.recv_timeout(WAIT).unwrap().text, "Bună!");
+    send(&mut ws, json!({"cmd": "say", "text": "Salut", "voice": {"voice": "ro-RO-EmilNeural"}}));
+    assert_eq!(robot.tts.recv_timeout(WAIT).unwrap().voice.voice.as_deref(), Some("ro-RO-EmilNeural"));
+    send(&mut ws, json!({"cmd": "say", "text": "Salut", "voice": {"style": "vesel!"}}));
+    assert!(next(&mut ws)["message"].as_str().unwrap().contains("azure.style"));
+    send(&mut ws, json!({"cmd": "say", "ssml": "<speak version=\"1.0\" xml:lang=\"ro-RO\"><voice name=\"ro-RO-EmilNeural\">Da</voice></speak>"}));
+    assert!(robot.tts.recv_timeout(WAIT).unwrap().ssml);
+    send(&mut ws, json!({"cmd": "say", "ssml": "Da"}));
+    assert!(next(&mut ws)["message"].as_str().unwrap().contains("ssml"));
+    send(&mut ws, json!({"cmd": "say", "ssml": "<speak>Da</speak>", "voice": {"voice": "ro-RO-EmilNeural"}}));
+    assert!(next(&mut ws)["message"].as_str().unwrap().contains("fără voice"));
+    assert_eq!(*servos.0.lock().unwrap(), [(ServoId::Right, 45.0)]);
+
+    send(&mut ws, json!({"cmd": "zbor"}));
+    assert_eq!(next(&mut ws)["event"], "error");
+    assert!(other.recv_text(Duration::from_millis(200)).is_none());
+}
+
+#[test]
+fn chat_exchanges_are_pushed() {
+    let robot = start();
+    let mut ws = connect(&robot);
+
+    let reply = robot.send(Method::Post, "/api/chat", &[], br#"{"text":"salut","speak":false}"#);
+    assert_eq!(reply.status, 200);
+
+    assert_eq!(next(&mut ws), json!({"event": "transcript", "text": "salut"}));
+    assert_eq!(next(&mut ws), json!({"event": "reply", "text": "SALUT"}));
+}
+
+#[test]
+fn commands_need_the_token_first() {
+    let robot = start();
+    let mut watcher = connect(&robot);
+    let mut ws = HostWsClient::connect(robot.addr, "/ws").unwrap();
+
+    send(&mut ws, json!({"cmd": "drive", "linear": 1.0, "angular": 0.0}));
+    assert_eq!(next(&mut ws)["event"], "error");
+    send(&mut ws, json!({"cmd": "auth", "token": "0000"}));
+    assert_eq!(next(&mut ws)["event"], "error");
+    thread::sleep(drive::DEADMAN);
+    assert_eq!(robot.motion.lock().unwrap().speeds(), (0, 0));
+
+    // evenimentele nu ajung la sesiunile neautentificate
+    assert_eq!(hold(&mut watcher, json!({"cmd": "drive", "linear": 0.5, "angular": 0.0}))["left"], 50);
+    assert!(ws.recv_text(Duration::from_millis(200)).is_none());
+}
+
+#[test]
+fn joystick_mixing() {
+    assert_eq!(motion::mix(0.0, 0.0), (0, 0));
+    assert_eq!(motion::mix(1.0, 0.0), (100, 100));
+    assert_eq!(motion::mix(0.0, 1.0), (100, -100));
+    assert_eq!(motion::mix(-0.5, -0.5), (-100, 0));
+    assert_eq!(motion::mix(f32::NAN, 3.0), (100, -100));
+}